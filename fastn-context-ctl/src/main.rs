@@ -0,0 +1,115 @@
+//! # fastn-context-ctl
+//!
+//! Command line client for the `fastn_context::control` socket (enabled with the
+//! `control` feature of `fastn-context`).
+//!
+//! ```text
+//! fastn-context-ctl [--socket <path>] tree
+//! fastn-context-ctl [--socket <path>] json
+//! fastn-context-ctl [--socket <path>] cancel <pattern>
+//! fastn-context-ctl [--socket <path>] watch [--interval <secs>]
+//! ```
+//!
+//! The socket defaults to `$FASTN_CONTEXT_SOCKET`.
+
+#![deny(unused_crate_dependencies)]
+
+const USAGE: &str = "usage: fastn-context-ctl [--socket <path>] <tree | json | cancel <pattern> | watch [--interval <secs>]>";
+
+/// Environment variable holding the default socket path
+const SOCKET_ENV: &str = "FASTN_CONTEXT_SOCKET";
+
+fn main() {
+    if let Err(e) = run(std::env::args().skip(1).collect()) {
+        eprintln!("fastn-context-ctl: {e}");
+        std::process::exit(1);
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut socket = std::env::var(SOCKET_ENV).ok();
+    let mut interval = std::time::Duration::from_secs(1);
+    let mut positional = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--socket" | "-s" => socket = Some(args.next().ok_or(USAGE)?),
+            "--interval" | "-i" => {
+                let secs: f64 = args
+                    .next()
+                    .and_then(|secs| secs.parse().ok())
+                    .filter(|secs: &f64| *secs > 0.0)
+                    .ok_or("--interval expects a positive number of seconds")?;
+                interval = std::time::Duration::from_secs_f64(secs);
+            }
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let socket = socket.ok_or(format!("no socket given (use --socket or ${SOCKET_ENV})"))?;
+    let positional: Vec<&str> = positional.iter().map(String::as_str).collect();
+
+    match positional.as_slice() {
+        ["tree"] => print!("{}", status(&socket)?.render_tree()),
+        ["json"] => println!("{}", request(&socket, "status")?),
+        ["cancel", pattern] => {
+            let response = request(&socket, &format!("cancel {pattern}"))?;
+            let cancelled = response["cancelled"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default();
+            if cancelled.is_empty() {
+                println!("no live contexts matched {pattern}");
+            }
+            for path in cancelled {
+                println!("cancelled {}", path.as_str().unwrap_or_default());
+            }
+        }
+        ["watch"] => loop {
+            let tree = status(&socket)?.render_tree();
+            // Clear the screen and move the cursor home before redrawing
+            print!("\x1b[2J\x1b[H{tree}");
+            std::thread::sleep(interval);
+        },
+        _ => return Err(USAGE.to_string()),
+    }
+
+    Ok(())
+}
+
+/// Fetch the status tree
+fn status(socket: &str) -> Result<fastn_context::Status, String> {
+    serde_json::from_value(request(socket, "status")?).map_err(|e| e.to_string())
+}
+
+/// Send one request over the control socket and parse its JSON response
+#[cfg(unix)]
+fn request(socket: &str, line: &str) -> Result<serde_json::Value, String> {
+    use std::io::{BufRead, Write};
+
+    let mut stream = std::os::unix::net::UnixStream::connect(socket)
+        .map_err(|e| format!("cannot connect to {socket}: {e}"))?;
+    writeln!(stream, "{line}").map_err(|e| e.to_string())?;
+
+    let mut response = String::new();
+    std::io::BufReader::new(stream)
+        .read_line(&mut response)
+        .map_err(|e| e.to_string())?;
+
+    let response: serde_json::Value =
+        serde_json::from_str(&response).map_err(|e| format!("invalid response: {e}"))?;
+    match response["error"].as_str() {
+        Some(error) => Err(error.to_string()),
+        None => Ok(response),
+    }
+}
+
+#[cfg(not(unix))]
+fn request(_socket: &str, _line: &str) -> Result<serde_json::Value, String> {
+    Err("the control socket is only available on unix".to_string())
+}
@@ -3,7 +3,9 @@
 //! Procedural macros for the `fastn-context` crate.
 //!
 //! This crate provides the `#[main]` attribute macro that simplifies setting up
-//! fastn applications with automatic context management.
+//! fastn applications with automatic context management, and the `#[test]` attribute
+//! macro for running async tests under an isolated root context. `#[instrument_context]`
+//! runs each call of an async function under its own child context.
 //!
 //! ## Usage
 //!
@@ -57,50 +59,398 @@ use syn::{ItemFn, parse_macro_input};
 ///     println!("Application starting");
 ///     
 ///     // Global context is available
-///     let ctx = fastn_context::global().await;
-///     println!("App context: {}", ctx.name());
+///     let ctx = fastn_context::global();
+///     println!("App context: {}", ctx.name);
 ///     
 ///     Ok(())
 /// }
 /// ```
 ///
+/// ## Arguments
+///
+/// - `signal_handlers` - Cancel the global context on SIGINT/SIGTERM (escalating on
+///   repeated signals), see
+///   `fastn_context::install_signal_handlers()`
+/// - `panic_hook` - Cancel the global context and dump the context tree on any
+///   panic, see `fastn_context::install_panic_hook()`
+/// - `flavor = "multi_thread" | "current_thread"` - Runtime flavor (default `multi_thread`)
+/// - `worker_threads = N` - Worker threads for the multi-threaded runtime
+/// - `name = "my-service"` - Name of the global context (default `global`)
+/// - `before = init_fn` - Async function run before your main function, under the
+///   freshly created global context (e.g. tracing init, config load). Returns `()` or
+///   `Result<(), E>`; on error your main function is skipped.
+/// - `after = teardown_fn` - Async function run after the global context has shut
+///   down, before the runtime stops. Returns `()` or `Result<(), E>`.
+/// - `shutdown_grace_secs = N` - How long the shutdown after `main` returns waits for
+///   tasks before force-aborting them (default 5)
+/// - `error_exit_code = N` - Exit code when `main` returns an error (default 1)
+/// - `unclean_exit_code = N` - Exit code when tasks panicked or had to be
+///   force-aborted during shutdown (default 2)
+///
+/// ```rust,no_run
+/// #[fastn_context::main(signal_handlers, worker_threads = 4, name = "my-service")]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     fastn_context::global().cancelled().await;
+///     Ok(())
+/// }
+/// ```
+///
 /// ## Return Types
 ///
-/// Your main function can return:
+/// Your main function keeps its declared return type, which can be:
 /// - `()` - No error handling
-/// - `Result<(), E>` where `E: std::error::Error` - With error handling
+/// - `Result<(), E>` where `E: std::fmt::Display` - e.g. `anyhow::Result<()>`,
+///   `Result<(), Box<dyn std::error::Error>>` or a custom error enum. An error is
+///   printed as `Error: {e:#}` (including the cause chain for `anyhow`) and exits
+///   with `error_exit_code`.
 ///
 /// ## Generated Code
 ///
-/// The macro generates a standard `fn main()` that creates the tokio runtime and calls
-/// your async function. Error handling is automatically provided.
+/// The macro generates a standard `fn main() -> std::process::ExitCode` that creates the
+/// tokio runtime and calls your async function. Once it returns, the global context is
+/// shut down gracefully (see `Context::shutdown()`) and the outcome becomes the exit
+/// code: `0` for success with a clean shutdown, `error_exit_code` if your function
+/// returned an error, `unclean_exit_code` if the shutdown was not clean.
 #[proc_macro_attribute]
-pub fn main(_args: TokenStream, input: TokenStream) -> TokenStream {
+pub fn main(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut main_args = MainArgs::default();
+    let args_parser = syn::meta::parser(|meta| main_args.parse(meta));
+    parse_macro_input!(args with args_parser);
+
     let input_fn = parse_macro_input!(input as ItemFn);
 
     let user_fn_name = syn::Ident::new("__fastn_user_main", proc_macro2::Span::call_site());
     let fn_block = &input_fn.block;
     let fn_attrs = &input_fn.attrs;
     let fn_vis = &input_fn.vis;
+    let fn_output = &input_fn.sig.output;
+
+    let runtime_builder = match main_args.flavor {
+        Flavor::MultiThread => quote! { tokio::runtime::Builder::new_multi_thread() },
+        Flavor::CurrentThread => quote! { tokio::runtime::Builder::new_current_thread() },
+    };
+    let worker_threads = main_args
+        .worker_threads
+        .as_ref()
+        .map(|worker_threads| quote! { .worker_threads(#worker_threads) });
+    let init_global = main_args.name.as_ref().map(|name| {
+        quote! {
+            fastn_context::init_global(#name);
+        }
+    });
+
+    let signal_handlers = main_args.signal_handlers.then(|| {
+        quote! {
+            if let Err(e) = fastn_context::install_signal_handlers() {
+                eprintln!("failed to install signal handlers: {e}");
+                return std::process::ExitCode::FAILURE;
+            }
+        }
+    });
+
+    let panic_hook = main_args
+        .panic_hook
+        .then(|| quote! { fastn_context::install_panic_hook(); });
+
+    let before = match &main_args.before {
+        Some(before) => quote! { #before() },
+        None => quote! { async {} },
+    };
+    let after = match &main_args.after {
+        Some(after) => quote! { #after() },
+        None => quote! { async {} },
+    };
+
+    let shutdown_grace_secs = main_args.shutdown_grace_secs;
+    let error_exit_code = main_args.error_exit_code;
+    let unclean_exit_code = main_args.unclean_exit_code;
 
     quote! {
         #(#fn_attrs)*
-        #fn_vis fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        #fn_vis fn main() -> std::process::ExitCode {
+            #init_global
+
             // Initialize tokio runtime
-            tokio::runtime::Builder::new_multi_thread()
+            let runtime = match #runtime_builder
+                #worker_threads
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    eprintln!("failed to build tokio runtime: {e}");
+                    return std::process::ExitCode::FAILURE;
+                }
+            };
+
+            runtime.block_on(async {
+                // Global context automatically created
+                #signal_handlers
+                #panic_hook
+
+                // Call user's main function
+                fastn_context::__run_main(
+                    #before,
+                    #user_fn_name(),
+                    #after,
+                    std::time::Duration::from_secs(#shutdown_grace_secs),
+                    #error_exit_code,
+                    #unclean_exit_code,
+                )
+                .await
+            })
+        }
+
+        async fn #user_fn_name() #fn_output #fn_block
+    }
+    .into()
+}
+
+/// Arguments accepted by `#[fastn_context::main(...)]`
+struct MainArgs {
+    signal_handlers: bool,
+    panic_hook: bool,
+    flavor: Flavor,
+    worker_threads: Option<syn::LitInt>,
+    name: Option<syn::LitStr>,
+    before: Option<syn::Path>,
+    after: Option<syn::Path>,
+    shutdown_grace_secs: u64,
+    error_exit_code: u8,
+    unclean_exit_code: u8,
+}
+
+impl Default for MainArgs {
+    fn default() -> Self {
+        MainArgs {
+            signal_handlers: false,
+            panic_hook: false,
+            flavor: Flavor::default(),
+            worker_threads: None,
+            name: None,
+            before: None,
+            after: None,
+            shutdown_grace_secs: 5,
+            error_exit_code: 1,
+            unclean_exit_code: 2,
+        }
+    }
+}
+
+/// Tokio runtime flavor
+#[derive(Default, PartialEq)]
+enum Flavor {
+    #[default]
+    MultiThread,
+    CurrentThread,
+}
+
+impl MainArgs {
+    fn parse(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("signal_handlers") {
+            self.signal_handlers = true;
+        } else if meta.path.is_ident("panic_hook") {
+            self.panic_hook = true;
+        } else if meta.path.is_ident("flavor") {
+            let flavor: syn::LitStr = meta.value()?.parse()?;
+            self.flavor = match flavor.value().as_str() {
+                "multi_thread" => Flavor::MultiThread,
+                "current_thread" => Flavor::CurrentThread,
+                _ => {
+                    return Err(syn::Error::new(
+                        flavor.span(),
+                        "flavor must be \"multi_thread\" or \"current_thread\"",
+                    ));
+                }
+            };
+        } else if meta.path.is_ident("worker_threads") {
+            let worker_threads: syn::LitInt = meta.value()?.parse()?;
+            if worker_threads.base10_parse::<usize>()? == 0 {
+                return Err(syn::Error::new(
+                    worker_threads.span(),
+                    "worker_threads must be greater than 0",
+                ));
+            }
+            self.worker_threads = Some(worker_threads);
+        } else if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("before") {
+            self.before = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("after") {
+            self.after = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("shutdown_grace_secs") {
+            let secs: syn::LitInt = meta.value()?.parse()?;
+            self.shutdown_grace_secs = secs.base10_parse()?;
+        } else if meta.path.is_ident("error_exit_code") {
+            let code: syn::LitInt = meta.value()?.parse()?;
+            self.error_exit_code = code.base10_parse()?;
+        } else if meta.path.is_ident("unclean_exit_code") {
+            let code: syn::LitInt = meta.value()?.parse()?;
+            self.unclean_exit_code = code.base10_parse()?;
+        } else {
+            return Err(meta.error("unsupported fastn_context::main argument"));
+        }
+
+        if self.flavor == Flavor::CurrentThread {
+            if let Some(worker_threads) = &self.worker_threads {
+                return Err(syn::Error::new(
+                    worker_threads.span(),
+                    "worker_threads is not supported with flavor = \"current_thread\"",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Test attribute macro that runs an async test under a fresh root context.
+///
+/// Each test gets its own isolated root context (named after the test) on a
+/// current-thread tokio runtime. The context is available via
+/// `fastn_context::Context::current()` or as the test function's only argument.
+/// The test fails if any child context still has live tasks, or is still referenced,
+/// when the body returns.
+///
+/// ## Example
+///
+/// ```rust
+/// #[fastn_context::test]
+/// async fn worker_stops(ctx: std::sync::Arc<fastn_context::Context>) {
+///     let handle = ctx.spawn_child("worker", |task_ctx| async move {
+///         task_ctx.cancelled().await;
+///     });
+///
+///     ctx.cancel();
+///     handle.await.unwrap();
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn test(args: TokenStream, input: TokenStream) -> TokenStream {
+    if !args.is_empty() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "fastn_context::test does not take arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let input_fn = parse_macro_input!(input as ItemFn);
+
+    if input_fn.sig.asyncness.is_none() {
+        return syn::Error::new_spanned(input_fn.sig.fn_token, "test function must be async")
+            .to_compile_error()
+            .into();
+    }
+    if input_fn.sig.inputs.len() > 1 {
+        return syn::Error::new_spanned(
+            &input_fn.sig.inputs,
+            "test function takes at most one argument (the root context)",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let body_fn_name = syn::Ident::new("__fastn_test_body", proc_macro2::Span::call_site());
+    let fn_name = &input_fn.sig.ident;
+    let fn_inputs = &input_fn.sig.inputs;
+    let fn_output = &input_fn.sig.output;
+    let fn_block = &input_fn.block;
+    let fn_attrs = &input_fn.attrs;
+    let fn_vis = &input_fn.vis;
+
+    let call_body = if fn_inputs.is_empty() {
+        quote! { |_| #body_fn_name() }
+    } else {
+        quote! { #body_fn_name }
+    };
+
+    quote! {
+        #[::core::prelude::v1::test]
+        #(#fn_attrs)*
+        #fn_vis fn #fn_name() #fn_output {
+            async fn #body_fn_name(#fn_inputs) #fn_output #fn_block
+
+            tokio::runtime::Builder::new_current_thread()
                 .enable_all()
-                .build()?
-                .block_on(async {
-                    // Global context automatically created
+                .build()
+                .expect("failed to build tokio runtime")
+                .block_on(fastn_context::__run_test(stringify!(#fn_name), #call_body))
+        }
+    }
+    .into()
+}
 
-                    // Call user's main function
-                    let result = #user_fn_name().await;
+/// Run each call of an async function under its own child context.
+///
+/// A context analogue of `#[tracing::instrument]`: every call creates a child of
+/// the current context (see `fastn_context::Context::current()`), named after the
+/// function, and runs the body with that child as the current context. When the
+/// call returns (including early returns via `return` or `?`) or its future is
+/// dropped, the child is cancelled, stopping anything spawned under it. The call
+/// duration shows up in status as the child's `duration_ms` gauge.
+///
+/// ## Arguments
+///
+/// - `name = "load-user"` - Name of the child context (default: the function name)
+///
+/// ## Example
+///
+/// ```rust
+/// #[fastn_context::instrument_context(name = "load-user")]
+/// async fn load_user(id: u64) -> Result<String, std::io::Error> {
+///     let ctx = fastn_context::Context::current(); // global/.../load-user
+///     assert!(ctx.path().ends_with("/load-user"));
+///     Ok(format!("user-{id}"))
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// assert_eq!(load_user(7).await.unwrap(), "user-7");
+/// # }
+/// ```
+#[proc_macro_attribute]
+pub fn instrument_context(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut name: Option<syn::LitStr> = None;
+    let args_parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("name") {
+            name = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported fastn_context::instrument_context argument"))
+        }
+    });
+    parse_macro_input!(args with args_parser);
+
+    let input_fn = parse_macro_input!(input as ItemFn);
 
-                    result
-                })
+    if input_fn.sig.asyncness.is_none() {
+        return syn::Error::new_spanned(
+            input_fn.sig.fn_token,
+            "instrument_context function must be async",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let fn_attrs = &input_fn.attrs;
+    let fn_vis = &input_fn.vis;
+    let fn_sig = &input_fn.sig;
+    let fn_block = &input_fn.block;
+    let name = match name {
+        Some(name) => quote! { #name },
+        None => {
+            let fn_name = fn_sig.ident.to_string();
+            quote! { #fn_name }
         }
+    };
 
-        async fn #user_fn_name() -> std::result::Result<(), Box<dyn std::error::Error>> #fn_block
+    quote! {
+        #(#fn_attrs)*
+        #fn_vis #fn_sig {
+            fastn_context::__instrument(#name, async move #fn_block).await
+        }
     }
     .into()
 }
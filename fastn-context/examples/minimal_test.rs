@@ -36,6 +36,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== Context Tree Status ===");
     let status = fastn_context::status();
     println!("{}", status);
+    println!("{}", status.render_tree());
 
     // Test persistence functionality
     global_ctx.spawn_child("persist-test", |task_ctx| async move {
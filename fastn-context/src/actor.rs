@@ -0,0 +1,232 @@
+/// What an actor does with messages still in its mailbox when its context is
+/// cancelled, see [`crate::ContextBuilder::mailbox`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MailboxPolicy {
+    /// Handle the remaining messages, then stop
+    #[default]
+    Drain,
+    /// Drop the remaining messages and stop right away
+    Discard,
+}
+
+/// Mailbox of an actor context, see [`crate::Context::actor`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MailboxStatus {
+    /// Messages waiting to be handled
+    pub queued: usize,
+    pub processed: u64,
+    /// Messages dropped under [`MailboxPolicy::Discard`]
+    pub discarded: u64,
+    /// No longer accepting messages
+    pub closed: bool,
+}
+
+/// Mailbox counters, stored as a typed value on the actor context
+#[derive(Default)]
+pub(crate) struct Mailbox {
+    queued: std::sync::atomic::AtomicUsize,
+    processed: std::sync::atomic::AtomicU64,
+    discarded: std::sync::atomic::AtomicU64,
+    closed: std::sync::atomic::AtomicBool,
+}
+
+impl Mailbox {
+    pub(crate) fn status(&self) -> MailboxStatus {
+        MailboxStatus {
+            queued: self.queued.load(std::sync::atomic::Ordering::Relaxed),
+            processed: self.processed.load(std::sync::atomic::Ordering::Relaxed),
+            discarded: self.discarded.load(std::sync::atomic::Ordering::Relaxed),
+            closed: self.closed.load(std::sync::atomic::Ordering::Relaxed),
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    fn taken(&self) {
+        self.queued
+            .fetch_sub(1, std::sync::atomic::Ordering::Relaxed);
+        crate::status_stream::tree_changed();
+    }
+}
+
+/// Address of an actor: sends messages to its mailbox, cheap to clone
+///
+/// The actor stops (and its context completes) once every address is dropped and
+/// the mailbox is empty.
+pub struct Address<M> {
+    sender: tokio::sync::mpsc::UnboundedSender<M>,
+    mailbox: std::sync::Arc<Mailbox>,
+    /// The actor context's cancellation, which closes the mailbox
+    cancelled: tokio_util::sync::CancellationToken,
+}
+
+impl<M> Clone for Address<M> {
+    fn clone(&self) -> Self {
+        Address {
+            sender: self.sender.clone(),
+            mailbox: self.mailbox.clone(),
+            cancelled: self.cancelled.clone(),
+        }
+    }
+}
+
+impl<M> Address<M> {
+    /// Queue a message, handing it back if the mailbox is closed (the actor's
+    /// context was cancelled or the actor stopped)
+    pub fn send(&self, message: M) -> Result<(), M> {
+        if self.is_closed() {
+            return Err(message);
+        }
+        self.mailbox
+            .queued
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        match self.sender.send(message) {
+            Ok(()) => {
+                crate::status_stream::tree_changed();
+                Ok(())
+            }
+            Err(tokio::sync::mpsc::error::SendError(message)) => {
+                self.mailbox
+                    .queued
+                    .fetch_sub(1, std::sync::atomic::Ordering::Relaxed);
+                Err(message)
+            }
+        }
+    }
+
+    /// Check if the mailbox no longer accepts messages
+    pub fn is_closed(&self) -> bool {
+        self.cancelled.is_cancelled()
+            || self
+                .mailbox
+                .closed
+                .load(std::sync::atomic::Ordering::Relaxed)
+            || self.sender.is_closed()
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl crate::ContextBuilder {
+    /// What the actor started with [`crate::ContextBuilder::actor`] does with queued
+    /// messages once this context is cancelled
+    pub fn mailbox(self, policy: MailboxPolicy) -> Self {
+        self.configure(move |context| context.set(policy))
+    }
+
+    /// Start an actor owning `state` in this context, see [`crate::Context::actor`]
+    pub fn actor<S, M, F, Fut>(self, state: S, mut handler: F) -> Address<M>
+    where
+        S: Send + 'static,
+        M: Send + 'static,
+        F: FnMut(S, M) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = S> + Send,
+    {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let context = self
+            .configure(|context| context.set(Mailbox::default()))
+            .build();
+        let mailbox = context
+            .get_local::<Mailbox>()
+            .expect("mailbox was just set");
+        let policy = context
+            .get_local::<MailboxPolicy>()
+            .map(|policy| *policy)
+            .unwrap_or_default();
+
+        let address = Address {
+            sender,
+            mailbox: mailbox.clone(),
+            cancelled: context.cancellation_token(),
+        };
+
+        crate::ContextBuilder::spawn_built(context, move |context| async move {
+            let mut state = state;
+            loop {
+                let message = tokio::select! {
+                    biased;
+                    _ = context.cancelled() => break,
+                    message = receiver.recv() => message,
+                };
+                let Some(message) = message else {
+                    break;
+                };
+                mailbox.taken();
+                state = handler(state, message).await;
+                mailbox
+                    .processed
+                    .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+
+            mailbox
+                .closed
+                .store(true, std::sync::atomic::Ordering::Relaxed);
+            receiver.close();
+            while let Ok(message) = receiver.try_recv() {
+                mailbox.taken();
+                match policy {
+                    MailboxPolicy::Drain => {
+                        state = handler(state, message).await;
+                        mailbox
+                            .processed
+                            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    }
+                    MailboxPolicy::Discard => {
+                        mailbox
+                            .discarded
+                            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    }
+                }
+            }
+            context.complete();
+        });
+
+        address
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl crate::Context {
+    /// Start an actor in a child context named `name`: `handler` processes the
+    /// messages sent to the returned [`Address`] one at a time, owning `state`
+    ///
+    /// When the context is cancelled the mailbox closes and the remaining messages
+    /// are handled or dropped, per [`crate::ContextBuilder::mailbox`] (handled by
+    /// default). Queue depth shows up in status. For replies, send a
+    /// `tokio::sync::oneshot::Sender` along with the message.
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// enum Message {
+    ///     Add(u64),
+    ///     Get(tokio::sync::oneshot::Sender<u64>),
+    /// }
+    ///
+    /// let ctx = fastn_context::Context::new("app");
+    /// let counter = ctx.actor("counter", 0, |total, message| async move {
+    ///     match message {
+    ///         Message::Add(n) => total + n,
+    ///         Message::Get(reply) => {
+    ///             let _ = reply.send(total);
+    ///             total
+    ///         }
+    ///     }
+    /// });
+    ///
+    /// counter.send(Message::Add(2)).ok();
+    /// counter.send(Message::Add(3)).ok();
+    /// let (reply, total) = tokio::sync::oneshot::channel();
+    /// counter.send(Message::Get(reply)).ok();
+    /// assert_eq!(total.await.unwrap(), 5);
+    /// # }
+    /// ```
+    pub fn actor<S, M, F, Fut>(&self, name: &str, state: S, handler: F) -> Address<M>
+    where
+        S: Send + 'static,
+        M: Send + 'static,
+        F: FnMut(S, M) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = S> + Send,
+    {
+        self.child(name).actor(state, handler)
+    }
+}
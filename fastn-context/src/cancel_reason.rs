@@ -0,0 +1,56 @@
+/// Why a context was cancelled
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CancelReason {
+    /// `cancel()` was called without a reason
+    Requested,
+    /// The context's deadline passed
+    DeadlineExceeded,
+    /// The context was cancelled as part of `shutdown()`
+    Shutdown,
+    /// A `ContextDropGuard` for the context was dropped
+    Dropped,
+    /// The process received a termination signal (e.g. `SIGINT`)
+    Signal(String),
+    /// A context in a fail-fast subtree failed (`path: error`)
+    Failure(String),
+    /// A task panicked under `PanicPolicy::Cancel`
+    Panicked(String),
+    /// No heartbeat arrived within the watchdog timeout, see `ContextBuilder::watchdog()`
+    Watchdog(std::time::Duration),
+    /// Replaced by a new child with the same name, see `ChildNamePolicy::Replace`
+    Replaced,
+    /// Application supplied reason
+    Custom(String),
+}
+
+impl From<&str> for CancelReason {
+    fn from(reason: &str) -> Self {
+        CancelReason::Custom(reason.to_string())
+    }
+}
+
+impl From<String> for CancelReason {
+    fn from(reason: String) -> Self {
+        CancelReason::Custom(reason)
+    }
+}
+
+impl std::fmt::Display for CancelReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CancelReason::Requested => write!(f, "requested"),
+            CancelReason::DeadlineExceeded => write!(f, "deadline exceeded"),
+            CancelReason::Shutdown => write!(f, "shutdown"),
+            CancelReason::Dropped => write!(f, "guard dropped"),
+            CancelReason::Signal(signal) => write!(f, "received {signal}"),
+            CancelReason::Failure(error) => write!(f, "failure in {error}"),
+            CancelReason::Panicked(message) => write!(f, "task panicked: {message}"),
+            CancelReason::Watchdog(timeout) => {
+                write!(f, "watchdog: no heartbeat within {timeout:?}")
+            }
+            CancelReason::Replaced => write!(f, "replaced"),
+            CancelReason::Custom(reason) => write!(f, "{reason}"),
+        }
+    }
+}
@@ -0,0 +1,138 @@
+/// What happens when a child is created with the name of an existing child, see
+/// [`crate::ContextBuilder::child_names`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChildNamePolicy {
+    /// Allow duplicate names
+    #[default]
+    Allow,
+    /// Refuse the child: [`crate::ContextBuilder::build`] panics,
+    /// [`crate::ContextBuilder::try_build`] fails with [`ChildError::NameTaken`]
+    Error,
+    /// Append the first free suffix: `worker`, `worker-2`, `worker-3`, ...
+    Suffix,
+    /// Cancel the active children with that name (with
+    /// [`crate::CancelReason::Replaced`]) and hide them from status and
+    /// `children()`; they stay in the tree, and are waited for by `shutdown()`,
+    /// until their tasks finish
+    Replace,
+}
+
+/// Marks a child replaced under [`ChildNamePolicy::Replace`]
+struct Replaced;
+
+/// Returned by [`crate::ContextBuilder::try_build`] (and checked early by
+/// [`crate::Context::try_child`]) when the child is refused
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChildError {
+    Quota(crate::QuotaExceeded),
+    /// An active child with this path already exists, under [`ChildNamePolicy::Error`]
+    NameTaken {
+        path: String,
+    },
+}
+
+impl std::fmt::Display for ChildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChildError::Quota(quota) => write!(f, "{quota}"),
+            ChildError::NameTaken { path } => write!(f, "{path} already exists"),
+        }
+    }
+}
+
+impl std::error::Error for ChildError {}
+
+impl From<crate::QuotaExceeded> for ChildError {
+    fn from(quota: crate::QuotaExceeded) -> Self {
+        ChildError::Quota(quota)
+    }
+}
+
+impl crate::ContextBuilder {
+    /// How duplicate names among this context's children are handled
+    ///
+    /// Without a policy duplicates are allowed, which makes status and `find()`
+    /// ambiguous. The resolved name is the `name` of the built child.
+    pub fn child_names(self, policy: ChildNamePolicy) -> Self {
+        self.configure(move |context| context.set(policy))
+    }
+
+    /// Name requested for the context being built; the parent's
+    /// [`ChildNamePolicy`] may suffix it when the child is built
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Name a new child of `parent` gets under the parent's policy, and the
+/// children it replaces
+pub(crate) struct Claim {
+    pub(crate) name: String,
+    pub(crate) replaces: Vec<std::sync::Arc<crate::Context>>,
+}
+
+/// Apply the parent's policy to a new child `name`, called with the parent's
+/// `children` locked so concurrent callers can't pick the same name
+///
+/// Fails under [`ChildNamePolicy::Error`] if an active child `name` exists. The
+/// children to replace are only marked by [`replace`], once the new child passed
+/// its quota checks.
+pub(crate) fn claim(
+    parent: &crate::Context,
+    children: &[std::sync::Arc<crate::Context>],
+    name: &str,
+) -> Result<Claim, ChildError> {
+    let policy = parent
+        .get_local::<ChildNamePolicy>()
+        .map(|policy| *policy)
+        .unwrap_or_default();
+    let active = |child: &&std::sync::Arc<crate::Context>| {
+        child.name == name && child.is_active() && !child.is_replaced()
+    };
+
+    let name = match policy {
+        ChildNamePolicy::Allow | ChildNamePolicy::Replace => name.to_string(),
+        ChildNamePolicy::Error => {
+            if children.iter().any(|child| active(&child)) {
+                return Err(ChildError::NameTaken {
+                    path: format!("{}/{name}", parent.path()),
+                });
+            }
+            name.to_string()
+        }
+        ChildNamePolicy::Suffix => {
+            let taken: std::collections::HashSet<&str> =
+                children.iter().map(|child| child.name.as_str()).collect();
+            if taken.contains(name) {
+                (2..)
+                    .map(|n| format!("{name}-{n}"))
+                    .find(|candidate| !taken.contains(candidate.as_str()))
+                    .unwrap_or_default()
+            } else {
+                name.to_string()
+            }
+        }
+    };
+    let replaces = match policy {
+        ChildNamePolicy::Replace => children.iter().filter(active).cloned().collect(),
+        _ => Vec::new(),
+    };
+
+    Ok(Claim { name, replaces })
+}
+
+/// Hide the children a new child replaces from status and `children()`, called
+/// with the parent's `children` locked once the new child is attached (the caller
+/// cancels them after releasing the lock)
+pub(crate) fn replace(replaces: &[std::sync::Arc<crate::Context>]) {
+    for child in replaces {
+        child.set(Replaced);
+    }
+}
+
+impl crate::Context {
+    /// Replaced by a sibling under [`ChildNamePolicy::Replace`], hidden from status
+    pub(crate) fn is_replaced(&self) -> bool {
+        self.get_local::<Replaced>().is_some()
+    }
+}
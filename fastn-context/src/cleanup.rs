@@ -0,0 +1,47 @@
+/// Async cleanup future registered with `Context::on_cancel`
+type CleanupHook = std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>;
+
+/// Cleanup hooks waiting for their context to be cancelled
+#[derive(Default)]
+pub(crate) struct CleanupHooks {
+    hooks: Vec<CleanupHook>,
+    /// Runtime the hooks run on, captured by the first `on_cancel()`
+    runtime: Option<tokio::runtime::Handle>,
+    /// Whether a task is already running the hooks
+    running: bool,
+}
+
+impl CleanupHooks {
+    /// Queue a hook, returns true for the first one (the caller has to start
+    /// watching for cancellation)
+    pub(crate) fn push<F>(&mut self, hook: F, runtime: tokio::runtime::Handle) -> bool
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        self.hooks.push(Box::pin(hook));
+        if self.runtime.is_some() {
+            return false;
+        }
+        self.runtime = Some(runtime);
+        true
+    }
+
+    /// Claim the queued hooks for a new runner task, returns the runtime to spawn it
+    /// on (None if there is nothing to run or a runner is already active)
+    pub(crate) fn start(&mut self) -> Option<tokio::runtime::Handle> {
+        if self.running || self.hooks.is_empty() {
+            return None;
+        }
+        self.running = true;
+        self.runtime.clone()
+    }
+
+    /// Take the most recently registered hook, marking the runner done when empty
+    pub(crate) fn pop(&mut self) -> Option<CleanupHook> {
+        let hook = self.hooks.pop();
+        if hook.is_none() {
+            self.running = false;
+        }
+        hook
+    }
+}
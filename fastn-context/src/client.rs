@@ -0,0 +1,182 @@
+/// `tower::Layer` for HTTP clients, tying outgoing requests to a context
+///
+/// Requests are made under the current context ([`crate::Context::current`]) or
+/// the one given with [`ClientLayer::context`]: they are aborted when it is
+/// cancelled (which includes its deadline passing), and the `traceparent` and
+/// `baggage` headers from [`crate::Context::propagation_headers`] are added unless
+/// the request already has them.
+///
+/// ```rust
+/// # async fn example<S>(client: S) -> Result<(), Box<dyn std::error::Error>>
+/// # where
+/// #     S: tower_service::Service<http::Request<String>, Response = http::Response<String>>,
+/// #     S::Future: Send + 'static,
+/// #     S::Error: std::error::Error + 'static,
+/// # {
+/// use tower_layer::Layer as _;
+/// use tower_service::Service as _;
+///
+/// let mut client = fastn_context::ClientLayer::new()
+///     .timeout(std::time::Duration::from_secs(5)) // never past the context's deadline
+///     .layer(client);
+/// let response = client.call(http::Request::new(String::new())).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct ClientLayer {
+    context: Option<std::sync::Arc<crate::Context>>,
+    timeout: Option<std::time::Duration>,
+    no_propagation: bool,
+}
+
+impl ClientLayer {
+    /// Make requests under the current context, without a timeout of their own
+    pub fn new() -> Self {
+        ClientLayer::default()
+    }
+
+    /// Make requests under `context` instead of the current context
+    pub fn context(mut self, context: std::sync::Arc<crate::Context>) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Time out each request after `timeout`, clamped to the context's deadline
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Don't add propagation headers to requests
+    pub fn without_propagation(mut self) -> Self {
+        self.no_propagation = true;
+        self
+    }
+}
+
+impl<S> tower_layer::Layer<S> for ClientLayer {
+    type Service = ClientService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ClientService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Service produced by [`ClientLayer`]
+#[derive(Clone)]
+pub struct ClientService<S> {
+    inner: S,
+    layer: ClientLayer,
+}
+
+impl<S, B> tower_service::Service<http::Request<B>> for ClientService<S>
+where
+    S: tower_service::Service<http::Request<B>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = ClientError<S::Error>;
+    type Future = std::pin::Pin<
+        Box<dyn std::future::Future<Output = Result<S::Response, Self::Error>> + Send>,
+    >;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(ClientError::Service)
+    }
+
+    fn call(&mut self, mut request: http::Request<B>) -> Self::Future {
+        let context = self
+            .layer
+            .context
+            .clone()
+            .unwrap_or_else(crate::Context::current);
+        if !self.layer.no_propagation {
+            context.inject_headers(request.headers_mut());
+        }
+
+        let response = self.inner.call(request);
+        let timeout = self.layer.timeout;
+        Box::pin(async move {
+            let result = match timeout {
+                Some(timeout) => match context.timeout(timeout, response).await {
+                    crate::TimeoutResult::Completed(result) => result,
+                    crate::TimeoutResult::TimedOut => return Err(ClientError::TimedOut),
+                    crate::TimeoutResult::Cancelled(cancelled) => {
+                        return Err(ClientError::Cancelled(cancelled));
+                    }
+                },
+                None => context
+                    .run(response)
+                    .await
+                    .map_err(ClientError::Cancelled)?,
+            };
+            result.map_err(ClientError::Service)
+        })
+    }
+}
+
+/// Error of a request made through [`ClientService`]
+#[derive(Debug)]
+pub enum ClientError<E> {
+    /// The context was cancelled (or its deadline passed) before the response
+    Cancelled(crate::Cancelled),
+    /// The timeout set with [`ClientLayer::timeout`] elapsed
+    TimedOut,
+    /// The inner client failed
+    Service(E),
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for ClientError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::Cancelled(cancelled) => write!(f, "request aborted: {cancelled}"),
+            ClientError::TimedOut => write!(f, "request timed out"),
+            ClientError::Service(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for ClientError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClientError::Cancelled(cancelled) => Some(cancelled),
+            ClientError::TimedOut => None,
+            ClientError::Service(e) => Some(e),
+        }
+    }
+}
+
+impl crate::Context {
+    /// Add this context's propagation headers to `headers`, keeping ones already set
+    ///
+    /// Works with any client using the `http` crate's `HeaderMap` (e.g.
+    /// `reqwest::header::HeaderMap`), see [`crate::Context::propagation_headers`].
+    pub fn inject_headers(&self, headers: &mut http::HeaderMap) {
+        for (name, value) in self.propagation_headers() {
+            if headers.contains_key(name) {
+                continue;
+            }
+            if let Ok(value) = http::HeaderValue::from_str(&value) {
+                headers.insert(name, value);
+            }
+        }
+    }
+
+    /// `timeout` clamped to this context's remaining deadline, for clients with
+    /// their own per-request timeout (e.g. `reqwest::RequestBuilder::timeout`)
+    pub fn clamp_timeout(&self, timeout: std::time::Duration) -> std::time::Duration {
+        match self.deadline() {
+            Some(deadline) => {
+                timeout.min(deadline.saturating_duration_since(std::time::Instant::now()))
+            }
+            None => timeout,
+        }
+    }
+}
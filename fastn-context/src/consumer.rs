@@ -0,0 +1,188 @@
+/// Message consumption of a context, see [`crate::Context::consume`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConsumerStatus {
+    /// Messages being handled right now
+    pub in_flight: usize,
+    /// Messages handled successfully
+    pub processed: u64,
+    /// Messages whose handler failed (or panicked)
+    pub failed: u64,
+    /// Backlog reported via `Context::set_consumer_lag()`, e.g. Kafka consumer lag
+    pub lag: Option<u64>,
+}
+
+/// Consumer counters, stored as a typed value on the consuming context
+#[derive(Default)]
+pub(crate) struct Consumer {
+    in_flight: std::sync::atomic::AtomicUsize,
+    processed: std::sync::atomic::AtomicU64,
+    failed: std::sync::atomic::AtomicU64,
+    lag: std::sync::Mutex<Option<u64>>,
+}
+
+impl Consumer {
+    pub(crate) fn status(&self) -> ConsumerStatus {
+        ConsumerStatus {
+            in_flight: self.in_flight.load(std::sync::atomic::Ordering::Relaxed),
+            processed: self.processed.load(std::sync::atomic::Ordering::Relaxed),
+            failed: self.failed.load(std::sync::atomic::Ordering::Relaxed),
+            lag: self.lag.lock().ok().and_then(|lag| *lag),
+        }
+    }
+}
+
+/// Counts a message in flight until dropped, as failed unless handled
+#[cfg(feature = "tokio")]
+struct InFlight {
+    consumer: std::sync::Arc<Consumer>,
+    handled: bool,
+}
+
+#[cfg(feature = "tokio")]
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if !self.handled {
+            self.consumer
+                .failed
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        }
+        self.consumer
+            .in_flight
+            .fetch_sub(1, std::sync::atomic::Ordering::Relaxed);
+        crate::status_stream::tree_changed();
+    }
+}
+
+impl crate::Context {
+    /// Consume `stream` (e.g. a Kafka or NATS subscription), handling each message
+    /// in its own child context named `message`
+    ///
+    /// Messages are handled one at a time, or up to `N` concurrently if this context
+    /// was built with [`crate::ContextBuilder::max_concurrent_tasks`]`(N)`; the next
+    /// message is only pulled once a slot is free. A handler returning `Err` fails
+    /// its message context. Stops pulling when this context is cancelled or the
+    /// stream ends, then waits for in-flight handlers and returns the totals. Counts
+    /// show up in status, see [`ConsumerStatus`].
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let ctx = fastn_context::Context::new("orders");
+    /// # struct Subscription(tokio::sync::mpsc::Receiver<u32>);
+    /// # impl futures_core::Stream for Subscription {
+    /// #     type Item = u32;
+    /// #     fn poll_next(
+    /// #         mut self: std::pin::Pin<&mut Self>,
+    /// #         cx: &mut std::task::Context<'_>,
+    /// #     ) -> std::task::Poll<Option<u32>> {
+    /// #         self.0.poll_recv(cx)
+    /// #     }
+    /// # }
+    /// let (sender, receiver) = tokio::sync::mpsc::channel(16);
+    /// let stream = Subscription(receiver); // e.g. a NATS subscription
+    ///
+    /// for order in 0..3 {
+    ///     sender.send(order).await.unwrap();
+    /// }
+    /// drop(sender);
+    ///
+    /// let totals = ctx
+    ///     .consume(stream, |_ctx, order| async move {
+    ///         if order == 2 { Err("out of stock") } else { Ok(()) }
+    ///     })
+    ///     .await;
+    /// assert_eq!((totals.processed, totals.failed), (2, 1));
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub async fn consume<S, H, Fut, E>(&self, mut stream: S, handler: H) -> ConsumerStatus
+    where
+        S: futures_core::Stream + Unpin,
+        H: Fn(std::sync::Arc<crate::Context>, S::Item) -> Fut,
+        Fut: std::future::Future<Output = Result<(), E>> + Send + 'static,
+        E: std::fmt::Display,
+    {
+        let consumer = match self.get_local::<Consumer>() {
+            Some(consumer) => consumer,
+            None => {
+                self.set(Consumer::default());
+                self.get_local::<Consumer>().expect("consumer was just set")
+            }
+        };
+        let limit = self.get_local::<crate::limit::ConcurrencyLimit>();
+        let sequential = std::sync::Arc::new(tokio::sync::Semaphore::new(1));
+        let handlers = tokio_util::task::TaskTracker::new();
+
+        loop {
+            let slot = async {
+                match &limit {
+                    Some(limit) => limit.acquire().await,
+                    None => sequential.clone().acquire_owned().await.ok(),
+                }
+            };
+            let permit = tokio::select! {
+                biased;
+                _ = self.cancelled() => break,
+                permit = slot => permit,
+            };
+            let message = tokio::select! {
+                biased;
+                _ = self.cancelled() => break,
+                message = std::future::poll_fn(|cx| std::pin::Pin::new(&mut stream).poll_next(cx)) => message,
+            };
+            let Some(message) = message else {
+                break;
+            };
+
+            consumer
+                .in_flight
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            let mut in_flight = InFlight {
+                consumer: consumer.clone(),
+                handled: false,
+            };
+
+            let context = self.child("message").build();
+            let finish = crate::state::FinishGuard {
+                context: context.clone(),
+            };
+            let handled = handler(context.clone(), message);
+            let task = handlers.track_future(async move {
+                let _permit = permit;
+                match handled.await {
+                    Ok(()) => {
+                        in_flight.handled = true;
+                        in_flight
+                            .consumer
+                            .processed
+                            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                        finish.context.complete();
+                    }
+                    Err(e) => finish.context.fail(e),
+                }
+            });
+            context.spawn(task);
+        }
+
+        handlers.close();
+        handlers.wait().await;
+        consumer.status()
+    }
+
+    /// Report the backlog of the stream this context consumes, shown in status
+    pub fn set_consumer_lag(&self, lag: u64) {
+        match self.get_local::<Consumer>() {
+            Some(consumer) => {
+                if let Ok(mut current) = consumer.lag.lock() {
+                    *current = Some(lag);
+                }
+            }
+            None => self.set(Consumer {
+                lag: std::sync::Mutex::new(Some(lag)),
+                ..Default::default()
+            }),
+        }
+        crate::status_stream::tree_changed();
+    }
+}
@@ -1,63 +1,751 @@
 /// Hierarchical context for task management and cancellation
 pub struct Context {
+    /// Unique ID (stable for the lifetime of the process)
+    id: u64,
+
     /// Context name for debugging
     pub name: String,
 
+    /// Slash separated names from the root down to this context
+    path: String,
+
     /// When this context was created
     pub created_at: std::time::Instant,
 
-    /// Parent context (None for root)
-    parent: Option<std::sync::Arc<Context>>,
+    /// Wall-clock time this context was created, reported in status
+    created_at_system: std::time::SystemTime,
+
+    /// The `Arc` handed out for this context (by `new()`, `child()`, ...), shared by
+    /// clones, see `Context::arc()`
+    this: std::sync::Weak<Context>,
+
+    /// Parent context (None for root), weak so children don't keep a finished
+    /// subtree alive
+    parent: Option<std::sync::Weak<Context>>,
 
     /// Child contexts
     children: std::sync::Arc<std::sync::Mutex<Vec<std::sync::Arc<Context>>>>,
 
     /// Cancellation token (proper async cancellation)
     cancellation_token: tokio_util::sync::CancellationToken,
+
+    /// Typed values attached to this context
+    values: std::sync::Arc<std::sync::Mutex<crate::type_map::TypeMap>>,
+
+    /// Tasks spawned through this context
+    pub(crate) tasks: std::sync::Arc<crate::task::TaskRegistry>,
+
+    /// When this context auto-cancels (never later than the parent's deadline)
+    deadline: std::sync::Arc<std::sync::Mutex<Option<std::time::Instant>>>,
+
+    /// Why this context was cancelled (first reason wins)
+    cancel_reason: std::sync::Arc<std::sync::Mutex<Option<crate::CancelReason>>>,
+
+    /// Counters and gauges reported in status
+    metrics: std::sync::Arc<std::sync::Mutex<crate::metrics::Metrics>>,
+
+    /// Async cleanup run (LIFO) once this context is cancelled
+    #[cfg(feature = "tokio")]
+    cleanup_hooks: std::sync::Arc<std::sync::Mutex<crate::cleanup::CleanupHooks>>,
+
+    /// Outcome and first failure, see `complete()` / `fail()`
+    pub(crate) lifecycle: std::sync::Arc<std::sync::Mutex<crate::state::Lifecycle>>,
+
+    /// Progress and message reported in status
+    pub(crate) progress: std::sync::Arc<std::sync::Mutex<crate::progress::ProgressState>>,
+
+    /// Recent events recorded via `event()`
+    pub(crate) events: std::sync::Arc<std::sync::Mutex<crate::events::EventLog>>,
+
+    /// OS processes owned by this context
+    processes: std::sync::Arc<crate::process::ProcessRegistry>,
+
+    /// Tracing span mirroring this context (child of the parent's span)
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
 }
 
 impl Context {
     /// Create new root context (typically only used by main macro)
     pub fn new(name: &str) -> std::sync::Arc<Context> {
-        std::sync::Arc::new(Context {
+        Context::create(name, None)
+    }
+
+    /// Create a root context cancelled whenever `token` is cancelled
+    ///
+    /// Links code built on raw `CancellationToken`s into the context tree: cancelling
+    /// the context does not cancel `token`, see [`Context::adopt_token`] for that.
+    pub fn from_token(
+        name: &str,
+        token: &tokio_util::sync::CancellationToken,
+    ) -> std::sync::Arc<Context> {
+        Context::create_with_token(name, None, token.child_token())
+    }
+
+    /// Cancel an external `CancellationToken` whenever this context is cancelled
+    ///
+    /// The token behaves like a child of this context: cancelling the token itself
+    /// does not cancel the context.
+    #[cfg(feature = "tokio")]
+    pub fn adopt_token(&self, token: tokio_util::sync::CancellationToken) {
+        let context = self.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = context.cancelled() => token.cancel(),
+                _ = token.cancelled() => {}
+            }
+        });
+    }
+
+    /// Create a registered context, inheriting cancellation and deadline from `parent`
+    fn create(name: &str, parent: Option<&Context>) -> std::sync::Arc<Context> {
+        let context = Context::unannounced(name, parent);
+        context.announce();
+        context
+    }
+
+    /// Like `create`, but neither registered nor reported to the observer yet, so
+    /// it can be created under the parent's `children` lock (see `announce`)
+    fn unannounced(name: &str, parent: Option<&Context>) -> std::sync::Arc<Context> {
+        let cancellation_token = match parent {
+            Some(parent) => parent.cancellation_token.child_token(),
+            None => tokio_util::sync::CancellationToken::new(),
+        };
+        let deadline = parent.and_then(|parent| parent.deadline());
+        Context::construct(name, parent, cancellation_token, deadline)
+    }
+
+    fn create_with_token(
+        name: &str,
+        parent: Option<&Context>,
+        cancellation_token: tokio_util::sync::CancellationToken,
+    ) -> std::sync::Arc<Context> {
+        let deadline = parent.and_then(|parent| parent.deadline());
+        let context = Context::construct(name, parent, cancellation_token, deadline);
+        context.announce();
+        context
+    }
+
+    fn construct(
+        name: &str,
+        parent: Option<&Context>,
+        cancellation_token: tokio_util::sync::CancellationToken,
+        deadline: Option<std::time::Instant>,
+    ) -> std::sync::Arc<Context> {
+        let id = crate::registry::next_id();
+        let path = match parent {
+            Some(parent) => format!("{}/{name}", parent.path),
+            None => name.to_string(),
+        };
+
+        #[cfg(feature = "tracing")]
+        let span = match parent {
+            Some(parent) => tracing::info_span!(parent: &parent.span, "context", id, path = %path),
+            None => tracing::info_span!("context", id, path = %path),
+        };
+
+        std::sync::Arc::new_cyclic(|this| Context {
+            id,
             name: name.to_string(),
+            path,
             created_at: std::time::Instant::now(),
-            parent: None,
+            created_at_system: std::time::SystemTime::now(),
+            this: this.clone(),
+            parent: parent.map(|parent| parent.this.clone()),
             children: std::sync::Arc::new(std::sync::Mutex::new(Vec::new())),
-            cancellation_token: tokio_util::sync::CancellationToken::new(),
+            cancellation_token,
+            values: Default::default(),
+            tasks: Default::default(),
+            deadline: std::sync::Arc::new(std::sync::Mutex::new(deadline)),
+            cancel_reason: Default::default(),
+            metrics: Default::default(),
+            #[cfg(feature = "tokio")]
+            cleanup_hooks: Default::default(),
+            lifecycle: Default::default(),
+            progress: Default::default(),
+            events: Default::default(),
+            processes: Default::default(),
+            #[cfg(feature = "tracing")]
+            span,
         })
     }
 
-    /// Create child context
+    /// Register a new context and report it to the observer, outside any lock (the
+    /// observer may walk the tree)
+    fn announce(self: &std::sync::Arc<Context>) {
+        crate::registry::register(self);
+        crate::observer::notify(|observer| observer.on_created(self));
+    }
+
+    /// Slash separated path from the root (e.g. `global/http/worker-3`), see [`crate::find`]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Tracing span for this context, entered by every task spawned through it
+    #[cfg(feature = "tracing")]
+    pub fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+
+    /// Unique ID of this context, see [`crate::find_by_id`]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Parent context (None for root)
+    ///
+    /// Children don't keep their parent alive: once the parent's last handle is gone
+    /// (and its tasks finished) this is None as well.
+    pub fn parent(&self) -> Option<std::sync::Arc<Context>> {
+        self.parent.as_ref().and_then(std::sync::Weak::upgrade)
+    }
+
+    /// Created without a parent (by `new()` and the like)
+    pub(crate) fn is_root(&self) -> bool {
+        self.parent.is_none()
+    }
+
+    /// References to this context held by the tree itself: 1 while it is in its
+    /// parent's children, 0 otherwise
+    pub(crate) fn tree_references(self: &std::sync::Arc<Context>) -> usize {
+        let listed = self.parent().is_some_and(|parent| {
+            parent.children.lock().is_ok_and(|children| {
+                children
+                    .iter()
+                    .any(|child| std::sync::Arc::ptr_eq(child, self))
+            })
+        });
+        usize::from(listed)
+    }
+
+    /// The `Arc` handed out for this context, also for clones of it
+    pub(crate) fn arc(&self) -> std::sync::Arc<Context> {
+        self.this
+            .upgrade()
+            .unwrap_or_else(|| std::sync::Arc::new(self.clone()))
+    }
+
+    /// Active children (with live tasks, or neither finished nor cancelled), in
+    /// creation order
+    ///
+    /// A snapshot of live handles, e.g. to message sibling workers:
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let pool = fastn_context::Context::new("pool");
+    /// for _ in 0..3 {
+    ///     pool.child("worker").spawn(|ctx| async move { ctx.cancelled().await });
+    /// }
+    /// for worker in pool.children() {
+    ///     worker.event("config reloaded");
+    /// }
+    /// assert_eq!(pool.children().len(), 3);
+    /// # }
+    /// ```
+    pub fn children(&self) -> Vec<std::sync::Arc<Context>> {
+        self.listed_children()
+            .into_iter()
+            .filter(|child| child.is_active())
+            .collect()
+    }
+
+    /// OS processes owned by this context
+    #[cfg(feature = "tokio")]
+    pub(crate) fn processes(&self) -> std::sync::Arc<crate::process::ProcessRegistry> {
+        self.processes.clone()
+    }
+
+    /// Get the context the current task was spawned under (falls back to global)
+    pub fn current() -> std::sync::Arc<Context> {
+        crate::current::try_current().unwrap_or_else(global)
+    }
+
+    /// Create child context, returning a builder to configure it
+    ///
+    /// Configure the child (deadline, labels, quotas, `detach()`, ...) on the
+    /// builder, then either run a task under it with [`ContextBuilder::spawn`] or
+    /// get the context itself with [`ContextBuilder::build`]. Nothing is created
+    /// until then, the child shows up in the tree fully configured.
     pub fn child(&self, name: &str) -> ContextBuilder {
-        let child_context = std::sync::Arc::new(Context {
+        ContextBuilder {
+            parent: self.clone(),
             name: name.to_string(),
-            created_at: std::time::Instant::now(),
-            parent: Some(std::sync::Arc::new(self.clone())),
-            children: std::sync::Arc::new(std::sync::Mutex::new(Vec::new())),
-            cancellation_token: self.cancellation_token.child_token(),
-        });
+            detached: false,
+            deadline: None,
+            setup: Vec::new(),
+            started: Vec::new(),
+        }
+    }
 
-        // Add to parent's children list
-        if let Ok(mut children) = self.children.lock() {
+    /// Create the child `name`, configure it with `setup` and add it to this
+    /// context's children, unless that would exceed a quota or the name is taken
+    ///
+    /// The name is resolved under the [`crate::ChildNamePolicy`] and the
+    /// `max_children()` quota checked under the children lock, so concurrent
+    /// callers can't overshoot or duplicate. Children replaced under
+    /// [`crate::ChildNamePolicy::Replace`] are only cancelled once the new child
+    /// passed every check. A detached child gets its own cancellation token and no
+    /// inherited deadline.
+    pub(crate) fn attach_child(
+        &self,
+        name: &str,
+        detached: bool,
+        setup: impl FnOnce(&Context),
+    ) -> Result<std::sync::Arc<Context>, crate::ChildError> {
+        crate::quota::check_depth(self)?;
+
+        let (child_context, replaces) = {
+            let mut children = self.children.lock().unwrap_or_else(|e| e.into_inner());
+            // Amortized pruning keeps fan-out heavy parents bounded between status walks
+            if children.len() >= 64 && children.len().is_power_of_two() {
+                prune_children(self, &mut children);
+            }
+            let claim = crate::child_names::claim(self, &children, name)?;
+            crate::quota::check_children(self, &children, claim.replaces.len())?;
+            let child_context = if detached {
+                let token = tokio_util::sync::CancellationToken::new();
+                let child_context = Context::construct(&claim.name, Some(self), token, None);
+                child_context.set(Detached);
+                child_context
+            } else {
+                Context::unannounced(&claim.name, Some(self))
+            };
+            setup(&child_context);
             children.push(child_context.clone());
+            crate::child_names::replace(&claim.replaces);
+            (child_context, claim.replaces)
+        };
+
+        child_context.announce();
+        for replaced in replaces {
+            replaced.cancel_with_reason(crate::CancelReason::Replaced);
         }
+        crate::status_stream::tree_changed();
+        Ok(child_context)
+    }
 
-        ContextBuilder {
-            context: child_context,
+    /// Whether `attach_child()` would accept the child `name` right now
+    pub(crate) fn check_child(&self, name: &str) -> Result<(), crate::ChildError> {
+        crate::quota::check_depth(self)?;
+        let children = self.children.lock().unwrap_or_else(|e| e.into_inner());
+        let claim = crate::child_names::claim(self, &children, name)?;
+        crate::quota::check_children(self, &children, claim.replaces.len())?;
+        Ok(())
+    }
+
+    /// The active child named `name`, or a new child with that name
+    ///
+    /// Idempotent, for long-lived named subsystems created lazily from several code
+    /// paths: all callers get the same context as long as it is active (has live
+    /// tasks, or is neither finished nor cancelled). Unlike [`Context::child`] there
+    /// is no builder, a newly created child gets default settings.
+    ///
+    /// # Panics
+    ///
+    /// Like [`ContextBuilder::build`], if a new child would exceed a quota.
+    #[track_caller]
+    pub fn child_or_existing(&self, name: &str) -> std::sync::Arc<Context> {
+        let find = |children: &[std::sync::Arc<Context>]| {
+            children
+                .iter()
+                .find(|child| child.name == name && child.is_active() && !child.is_replaced())
+                .cloned()
+        };
+        if let Some(existing) = find(&self.children_snapshot()) {
+            return existing;
+        }
+
+        if let Err(quota) = crate::quota::check_depth(self) {
+            panic!("child refused: {quota}");
         }
+        // Checked again under the lock in case another caller won the race
+        let created = {
+            let mut children = self.children.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(existing) = find(&children) {
+                return existing;
+            }
+            if let Err(quota) = crate::quota::check_children(self, &children, 0) {
+                drop(children);
+                panic!("child refused: {quota}");
+            }
+            let created = Context::unannounced(name, Some(self));
+            children.push(created.clone());
+            created
+        };
+        created.announce();
+        crate::status_stream::tree_changed();
+        created
     }
 
-    /// Simple spawn (inherits current context, no child creation)
+    /// Still running work: has live tasks, or is neither finished nor cancelled
+    pub(crate) fn is_active(&self) -> bool {
+        self.task_count() > 0 || (self.total_duration().is_none() && !self.is_cancelled())
+    }
+
+    /// Current children, copied out so the lock isn't held while walking the tree
+    ///
+    /// Children nobody holds anymore are dropped from the tree first.
+    pub(crate) fn children_snapshot(&self) -> Vec<std::sync::Arc<Context>> {
+        self.children
+            .lock()
+            .map(|mut children| {
+                prune_children(self, &mut children);
+                children.clone()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Current children except replaced ones (see [`crate::ChildNamePolicy::Replace`]),
+    /// as listed in status
+    pub(crate) fn listed_children(&self) -> Vec<std::sync::Arc<Context>> {
+        self.children_snapshot()
+            .into_iter()
+            .filter(|child| !child.is_replaced())
+            .collect()
+    }
+
+    /// Remove a child from this context's children (e.g. once it is rolled up)
+    #[cfg(feature = "tokio")]
+    pub(crate) fn remove_child(&self, id: u64) {
+        if let Ok(mut children) = self.children.lock() {
+            children.retain(|child| child.id != id);
+        }
+        crate::status_stream::tree_changed();
+    }
+
+    /// Create a context cancelled as soon as any of `parents` is cancelled
+    ///
+    /// For work owned by several scopes (e.g. a request and its connection). The
+    /// context lives in the tree under the first parent; it adopts the earliest
+    /// deadline and the cancel reason of whichever parent is cancelled first. With no
+    /// parents this is the same as [`Context::new`].
+    #[cfg(feature = "tokio")]
+    pub fn linked(name: &str, parents: &[&Context]) -> std::sync::Arc<Context> {
+        let Some((first, others)) = parents.split_first() else {
+            return Context::new(name);
+        };
+
+        let context = first.child(name).build();
+        for parent in others {
+            if let Some(deadline) = parent.deadline() {
+                context.set_deadline(deadline);
+            }
+
+            let parent = (*parent).clone();
+            let linked = context.clone();
+            tokio::spawn(async move {
+                tokio::select! {
+                    _ = parent.cancelled() => linked.cancel_with_reason(
+                        parent.cancel_reason().unwrap_or(crate::CancelReason::Requested),
+                    ),
+                    _ = linked.cancelled() => {}
+                }
+            });
+        }
+
+        context
+    }
+
+    /// Spawn task tracked by this context (no child creation)
+    ///
+    /// The task counts towards this context's live tasks until it finishes. It is
+    /// expected to watch [`Context::cancelled`]; [`Context::shutdown`] force-aborts
+    /// tasks that ignore cancellation. Panics are recorded on this context and handled
+    /// according to its [`crate::PanicPolicy`].
+    ///
+    /// With [`ContextBuilder::max_concurrent_tasks`] the task waits for a free slot
+    /// before it starts.
+    ///
+    /// # Panics
+    ///
+    /// If this context already tracks its [`ContextBuilder::max_tracked_tasks`]
+    /// quota of tasks, see [`Context::try_spawn_tracked`]. The same goes for the
+    /// other ways of spawning.
+    #[cfg(feature = "tokio")]
+    #[track_caller]
     pub fn spawn<F>(&self, task: F) -> tokio::task::JoinHandle<F::Output>
     where
         F: std::future::Future + Send + 'static,
         F::Output: Send + 'static,
     {
-        tokio::spawn(task)
+        self.spawn_limited(self.task_slot(None), task)
+    }
+
+    /// Like [`Context::spawn`], naming the task so it can be identified in the
+    /// [`crate::ShutdownReport`] if it has to be force-aborted
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    pub fn spawn_named<F>(&self, name: &str, task: F) -> tokio::task::JoinHandle<F::Output>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.spawn_limited(self.task_slot(Some(name.to_string())), task)
+    }
+
+    /// Like [`Context::spawn`], spawning the task on `runtime`
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let compute = tokio::runtime::Builder::new_multi_thread()
+    ///     .worker_threads(1)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let ctx = fastn_context::Context::new("app");
+    /// let sum = ctx.spawn_on(compute.handle(), async { (1..=10).sum::<u32>() });
+    /// assert_eq!(sum.await.unwrap(), 55);
+    /// # compute.shutdown_background();
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    pub fn spawn_on<F>(
+        &self,
+        runtime: &tokio::runtime::Handle,
+        task: F,
+    ) -> tokio::task::JoinHandle<F::Output>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.tasks
+            .spawn(self.task_slot(None), Some(runtime), self.wrap_limited(task))
+    }
+
+    /// Like [`Context::spawn`], spawning the task on another executor
+    ///
+    /// The task is tracked, cancelled and drained like any other task of this
+    /// context, and is aborted at its next await point if [`Context::shutdown`]
+    /// has to force-abort it.
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let ctx = fastn_context::Context::new("app");
+    /// let runtime = tokio::runtime::Handle::current(); // or e.g. `|task| smol::spawn(task).detach()`
+    /// let answer = ctx.spawn_with(&runtime, async { 42 });
+    /// assert_eq!(answer.await, Ok(42));
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn spawn_with<F>(
+        &self,
+        spawner: &dyn crate::Spawner,
+        task: F,
+    ) -> crate::TaskHandle<F::Output>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (sender, result) = tokio::sync::oneshot::channel();
+        let task = crate::spawner::CatchUnwind::new(self.wrap_limited(task));
+        let abort = self
+            .tasks
+            .spawn_with(self.task_slot(None), spawner, async move {
+                let _ = sender.send(task.await);
+            });
+        crate::TaskHandle::new(result, abort)
+    }
+
+    /// Spawn a `!Send` task tracked by this context on the current
+    /// `tokio::task::LocalSet`
+    ///
+    /// For single-threaded workloads (GUI event loops, `Rc`-based state) that still
+    /// want tracking, cancellation and shutdown through the context tree. Like
+    /// `tokio::task::spawn_local`, this panics outside of a `LocalSet`.
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let ctx = fastn_context::Context::new("ui");
+    /// let local = tokio::task::LocalSet::new();
+    ///
+    /// local
+    ///     .run_until(async {
+    ///         let state = std::rc::Rc::new(std::cell::Cell::new(0));
+    ///         let task_state = state.clone();
+    ///         ctx.spawn_local(async move { task_state.set(1) }).await.unwrap();
+    ///         assert_eq!(state.get(), 1);
+    ///     })
+    ///     .await;
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    pub fn spawn_local<F>(&self, task: F) -> tokio::task::JoinHandle<F::Output>
+    where
+        F: std::future::Future + 'static,
+        F::Output: 'static,
+    {
+        self.tasks
+            .spawn_local(self.task_slot(None), self.wrap_limited(task))
+    }
+
+    /// Run the blocking closure `f` on tokio's blocking thread pool, tracked like a
+    /// spawned task
+    ///
+    /// Blocking code can't be interrupted, so `f` gets this context to poll
+    /// [`Context::is_cancelled`] and return early. [`Context::shutdown`] waits for it
+    /// like for any other task; if it is still running when the grace period ends
+    /// it is reported as hung, but keeps running in the background.
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let ctx = fastn_context::Context::new("app");
+    /// let handle = ctx.spawn_blocking(|ctx| {
+    ///     let mut chunks = 0;
+    ///     while !ctx.is_cancelled() && chunks < 3 {
+    ///         chunks += 1; // process a chunk
+    ///     }
+    ///     chunks
+    /// });
+    /// assert_eq!(handle.await.unwrap(), 3);
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    pub fn spawn_blocking<F, R>(&self, f: F) -> tokio::task::JoinHandle<R>
+    where
+        F: FnOnce(std::sync::Arc<Context>) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let context = self.arc();
+        let runtime = self.runtime();
+        let slot = self.task_slot(None);
+        self.tasks.spawn_blocking(slot, runtime.as_ref(), move || {
+            #[cfg(feature = "tracing")]
+            let _entered = context.span.enter();
+
+            crate::panic::catch_panic_blocking(&context, || f(context.clone()))
+        })
+    }
+
+    /// Spawn a task into the reserved `slot`, waiting for the concurrency limit
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    pub(crate) fn spawn_limited<F>(
+        &self,
+        slot: crate::task::TaskGuard,
+        task: F,
+    ) -> tokio::task::JoinHandle<F::Output>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        match self.get_local::<crate::limit::ConcurrencyLimit>() {
+            Some(limit) => self.spawn_tracked(slot, async move {
+                let _permit = limit.acquire().await;
+                task.await
+            }),
+            None => self.spawn_tracked(slot, task),
+        }
+    }
+
+    /// Spawn a tracked task, bypassing the concurrency limit
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    pub(crate) fn spawn_unlimited<F>(&self, task: F) -> tokio::task::JoinHandle<F::Output>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.spawn_tracked(self.task_slot(None), task)
+    }
+
+    /// Spawn cleanup work as a tracked task, bypassing the concurrency limit and the
+    /// `max_tracked_tasks()` quota: cleanup must run even on a busy context
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    pub(crate) fn spawn_cleanup<F>(&self, task: F) -> tokio::task::JoinHandle<F::Output>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.spawn_tracked(self.tasks.register(None), task)
+    }
+
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    fn spawn_tracked<F>(
+        &self,
+        slot: crate::task::TaskGuard,
+        task: F,
+    ) -> tokio::task::JoinHandle<F::Output>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let context = self.arc();
+        let task = crate::panic::CatchPanic::new(task, context.clone());
+
+        #[cfg(feature = "tracing")]
+        let task = tracing::Instrument::instrument(task, self.span.clone());
+
+        self.tasks.spawn(
+            slot,
+            self.runtime().as_ref(),
+            crate::current::with_current(context, task),
+        )
+    }
+
+    /// Like [`Context::spawn`], spawning the task into `set`, see [`crate::TaskGroup`]
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    pub(crate) fn spawn_in<F>(
+        &self,
+        set: &mut tokio::task::JoinSet<F::Output>,
+        task: F,
+    ) -> tokio::task::AbortHandle
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.tasks.spawn_in(
+            set,
+            self.task_slot(None),
+            self.runtime().as_ref(),
+            self.wrap_limited(task),
+        )
+    }
+
+    /// Wrap a task the way [`Context::spawn`] does: wait for a concurrency slot,
+    /// record panics, enter the span and make this the current context
+    fn wrap_limited<F: std::future::Future>(
+        &self,
+        task: F,
+    ) -> impl std::future::Future<Output = F::Output> {
+        let context = self.arc();
+        let limit = self.get_local::<crate::limit::ConcurrencyLimit>();
+        let task = crate::panic::CatchPanic::new(
+            async move {
+                let _permit = match &limit {
+                    Some(limit) => Some(limit.acquire().await),
+                    None => None,
+                };
+                task.await
+            },
+            context.clone(),
+        );
+
+        #[cfg(feature = "tracing")]
+        let task = tracing::Instrument::instrument(task, self.span.clone());
+
+        crate::current::with_current(context, task)
+    }
+
+    /// Number of tasks spawned through this context that are still alive
+    pub fn task_count(&self) -> usize {
+        self.tasks.len()
     }
 
     /// Spawn task with named child context (common case shortcut)
+    #[cfg(feature = "tokio")]
+    #[track_caller]
     pub fn spawn_child<F, Fut>(&self, name: &str, task: F) -> tokio::task::JoinHandle<Fut::Output>
     where
         F: FnOnce(std::sync::Arc<Context>) -> Fut + Send + 'static,
@@ -70,14 +758,7 @@ impl Context {
 
     /// Wait for cancellation signal (for use in tokio::select!)
     pub async fn wait(&self) {
-        // Poll-based future that completes when cancelled
-        loop {
-            if self.is_cancelled() {
-                return;
-            }
-            // Yield to allow other tasks to run, then check again
-            tokio::task::yield_now().await;
-        }
+        self.cancelled().await;
     }
 
     /// Wait for cancellation signal (returns proper Future for tokio::select!)
@@ -85,6 +766,16 @@ impl Context {
         self.cancellation_token.cancelled()
     }
 
+    /// Owned variant of `cancelled()` for futures that outlive the borrow
+    pub(crate) fn cancelled_owned(&self) -> tokio_util::sync::WaitForCancellationFutureOwned {
+        self.cancellation_token.clone().cancelled_owned()
+    }
+
+    /// Clone of the cancellation token, for observers that outlive the context
+    pub(crate) fn cancellation_token(&self) -> tokio_util::sync::CancellationToken {
+        self.cancellation_token.clone()
+    }
+
     /// Check if this context is cancelled
     pub fn is_cancelled(&self) -> bool {
         self.cancellation_token.is_cancelled()
@@ -92,7 +783,438 @@ impl Context {
 
     /// Cancel this context and all children recursively
     pub fn cancel(&self) {
+        self.cancel_with_reason(crate::CancelReason::Requested);
+    }
+
+    /// Cancel this context and all children recursively, recording why
+    pub fn cancel_with_reason(&self, reason: impl Into<crate::CancelReason>) {
+        let reason = reason.into();
+        let newly_cancelled = !self.is_cancelled();
+        if let Ok(mut cancel_reason) = self.cancel_reason.lock() {
+            if cancel_reason.is_none() && newly_cancelled {
+                *cancel_reason = Some(reason.clone());
+            }
+        }
         self.cancellation_token.cancel();
+        // Started here rather than by the hooks' watcher task, so that a
+        // `close_and_wait()` right after cancelling waits for them
+        #[cfg(feature = "tokio")]
+        self.run_subtree_cleanup_hooks();
+        crate::status_stream::tree_changed();
+
+        if newly_cancelled {
+            crate::observer::notify(|observer| observer.on_cancelled(self, &reason));
+        }
+    }
+
+    /// Like [`Context::cancel_with_reason`], without blocking on any lock or running
+    /// observers, for the panic hook (the panicking thread may hold a lock)
+    ///
+    /// `on_cancel()` hooks are started by their watcher tasks.
+    pub(crate) fn try_cancel_with_reason(&self, reason: crate::CancelReason) {
+        if let Ok(mut cancel_reason) = self.cancel_reason.try_lock() {
+            if cancel_reason.is_none() && !self.is_cancelled() {
+                *cancel_reason = Some(reason);
+            }
+        }
+        self.cancellation_token.cancel();
+        crate::status_stream::tree_changed();
+    }
+
+    /// Cancel every current child (and their subtrees) while this context stays alive
+    ///
+    /// Lets a supervisor tear down and rebuild its subtree, e.g. on config reload.
+    /// Detached children are left alone. Children created afterwards are unaffected.
+    pub fn cancel_children(&self) {
+        for child in self
+            .children_snapshot()
+            .iter()
+            .filter(|child| !child.is_detached())
+        {
+            child.cancel();
+        }
+    }
+
+    /// Guard that cancels this context (and its subtree) when dropped
+    pub fn drop_guard(&self) -> crate::ContextDropGuard {
+        crate::ContextDropGuard::new(self.clone())
+    }
+
+    /// Run async cleanup once this context is cancelled
+    ///
+    /// Hooks run one after another in reverse registration order (LIFO). Nothing is
+    /// spawned until the context is cancelled; the hooks then run as a tracked task,
+    /// so [`Context::shutdown`] waits for them within its grace period. A hook
+    /// registered after cancellation runs right away.
+    #[cfg(feature = "tokio")]
+    pub fn on_cancel<F>(&self, cleanup: F)
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let runtime = self
+            .runtime()
+            .unwrap_or_else(tokio::runtime::Handle::current);
+        let first = match self.cleanup_hooks.lock() {
+            Ok(mut hooks) => hooks.push(cleanup, runtime.clone()),
+            Err(_) => return,
+        };
+
+        if self.is_cancelled() {
+            self.run_cleanup_hooks();
+        } else if first {
+            // `cancel()` starts the hooks of its whole subtree, this only catches
+            // cancellation through the token given to `from_token()` and by the panic
+            // hook. Not a tracked task: waiting for cancellation is not live work.
+            let context = self.clone();
+            runtime.spawn(async move {
+                context.cancelled().await;
+                context.run_cleanup_hooks();
+            });
+        }
+    }
+
+    /// Start the `on_cancel()` hooks of this context and its descendants (except
+    /// detached ones)
+    #[cfg(feature = "tokio")]
+    fn run_subtree_cleanup_hooks(&self) {
+        self.run_cleanup_hooks();
+        for child in self
+            .children_snapshot()
+            .iter()
+            .filter(|child| !child.is_detached())
+        {
+            child.run_subtree_cleanup_hooks();
+        }
+    }
+
+    /// Run the pending `on_cancel()` hooks as a tracked task, unless they already run
+    #[cfg(feature = "tokio")]
+    fn run_cleanup_hooks(&self) {
+        let runtime = match self.cleanup_hooks.lock() {
+            Ok(mut hooks) => hooks.start(),
+            Err(_) => None,
+        };
+        let Some(runtime) = runtime else {
+            return;
+        };
+
+        let context = self.clone();
+        let slot = self.tasks.register(Some("cleanup".to_string()));
+        self.tasks.spawn(slot, Some(&runtime), async move {
+            loop {
+                let hook = match context.cleanup_hooks.lock() {
+                    Ok(mut hooks) => hooks.pop(),
+                    Err(_) => None,
+                };
+                match hook {
+                    Some(hook) => hook.await,
+                    None => break,
+                }
+            }
+        });
+    }
+
+    /// Why this context was cancelled (inherited from the ancestor that was cancelled)
+    pub fn cancel_reason(&self) -> Option<crate::CancelReason> {
+        if !self.is_cancelled() {
+            return None;
+        }
+
+        if let Some(reason) = self.cancel_reason.lock().ok().and_then(|r| r.clone()) {
+            return Some(reason);
+        }
+
+        if self.is_detached() {
+            return None;
+        }
+        self.parent().and_then(|parent| parent.cancel_reason())
+    }
+
+    /// True if this context was created with [`ContextBuilder::detach`]
+    pub fn is_detached(&self) -> bool {
+        self.get_local::<Detached>().is_some()
+    }
+
+    /// Attach a value to this context, replacing any previous value of the same type
+    pub fn set<T: Send + Sync + 'static>(&self, value: T) {
+        if let Ok(mut values) = self.values.lock() {
+            values.insert(value);
+        }
+    }
+
+    /// Get a value of type `T`, looking it up through the parent chain
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<std::sync::Arc<T>> {
+        if let Some(value) = self.get_local::<T>() {
+            return Some(value);
+        }
+
+        self.parent().and_then(|parent| parent.get::<T>())
+    }
+
+    /// Get a value of type `T` stored on this context itself (parents are not consulted)
+    pub(crate) fn get_local<T: Send + Sync + 'static>(&self) -> Option<std::sync::Arc<T>> {
+        self.values.lock().ok().and_then(|values| values.get::<T>())
+    }
+
+    /// Remove a value of type `T` from this context (parents are not affected)
+    pub fn remove<T: Send + Sync + 'static>(&self) -> Option<std::sync::Arc<T>> {
+        self.values
+            .lock()
+            .ok()
+            .and_then(|mut values| values.remove::<T>())
+    }
+
+    /// Cancel this context, wait up to `grace` for its tasks and children to
+    /// finish, then force-abort whatever is still running
+    ///
+    /// Children given a [`ContextBuilder::shutdown_phase`] are shut down first, one
+    /// phase at a time in ascending order, each phase draining before the next one
+    /// starts. The rest of the subtree is cancelled last. `grace` bounds the whole
+    /// shutdown, not each phase.
+    #[cfg(feature = "tokio")]
+    pub async fn shutdown(&self, grace: std::time::Duration) -> crate::ShutdownReport {
+        let started = std::time::Instant::now();
+
+        let mut contexts = Vec::new();
+        self.collect_subtree(&mut contexts);
+        let panics_before: std::collections::HashMap<_, _> = contexts
+            .iter()
+            .map(|ctx| (ctx.id, ctx.panic_count()))
+            .collect();
+
+        let drained = tokio::time::timeout(grace, self.drain()).await.is_ok();
+
+        // Dump the tree while the hung tasks are still there
+        #[cfg(feature = "crash-dump")]
+        if !drained && self.id == global().id {
+            crate::crash_dump::fatal("forced shutdown: grace period ran out");
+        }
+
+        // Pick up children created while shutting down, keeping the ones that
+        // finished and were dropped from the tree meanwhile
+        let mut current = Vec::new();
+        self.collect_subtree(&mut current);
+        for ctx in current {
+            if !panics_before.contains_key(&ctx.id) {
+                contexts.push(ctx);
+            }
+        }
+
+        let mut report = crate::ShutdownReport {
+            timed_out: !drained,
+            ..Default::default()
+        };
+        for ctx in &contexts {
+            let hung_tasks = if drained {
+                Vec::new()
+            } else {
+                ctx.tasks.abort_all()
+            };
+            let aborted_tasks = hung_tasks.len();
+            let new_panics = ctx.panic_count() - panics_before.get(&ctx.id).copied().unwrap_or(0);
+
+            if aborted_tasks > 0 {
+                report.aborted.push(crate::shutdown::AbortedContext {
+                    name: ctx.name.clone(),
+                    path: ctx.path.clone(),
+                    aborted_tasks,
+                    tasks: hung_tasks,
+                });
+            }
+            if new_panics > 0 {
+                let panics = ctx.panics();
+                let skip = panics.len().saturating_sub(new_panics as usize);
+                report.panicked.push(crate::shutdown::PanickedContext {
+                    path: ctx.path.clone(),
+                    panics: panics.into_iter().skip(skip).collect(),
+                });
+            }
+            if aborted_tasks == 0 && new_panics == 0 {
+                report.finished.push(ctx.path.clone());
+            }
+        }
+
+        report.elapsed = started.elapsed();
+        report
+    }
+
+    /// Cancel and wait for children phase by phase, then for this context itself
+    #[cfg(feature = "tokio")]
+    fn drain(&self) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send + '_>> {
+        Box::pin(async move {
+            let mut phases = std::collections::BTreeMap::<u32, Vec<_>>::new();
+            if let Ok(children) = self.children.lock() {
+                for child in children.iter().filter(|child| !child.is_detached()) {
+                    if let Some(phase) = child.get_local::<crate::shutdown::ShutdownPhase>() {
+                        phases.entry(phase.0).or_default().push(child.clone());
+                    }
+                }
+            }
+
+            for contexts in phases.into_values() {
+                // Contexts of one phase drain concurrently (aborted if `shutdown()` times out)
+                let mut draining = tokio::task::JoinSet::new();
+                for context in contexts {
+                    draining.spawn(async move { context.drain().await });
+                }
+                while draining.join_next().await.is_some() {}
+            }
+
+            self.cancel_with_reason(crate::CancelReason::Shutdown);
+            self.close_and_wait().await;
+        })
+    }
+
+    /// Wait until every task spawned through this context and its descendants has
+    /// finished (structured concurrency)
+    ///
+    /// This does not cancel anything; call [`Context::cancel`] first to ask tasks to
+    /// stop, or use [`Context::shutdown`] to also bound the wait.
+    pub async fn close_and_wait(&self) {
+        let mut contexts = Vec::new();
+        self.collect_subtree(&mut contexts);
+
+        for ctx in &contexts {
+            ctx.tasks.close_and_wait().await;
+        }
+    }
+
+    /// Force-abort every task of this context and its descendants (except detached
+    /// ones) right away, returning how many were aborted
+    #[cfg(feature = "tokio")]
+    pub(crate) fn abort_subtree(&self) -> usize {
+        let mut contexts = Vec::new();
+        self.collect_subtree(&mut contexts);
+        contexts.iter().map(|ctx| ctx.tasks.abort_all().len()).sum()
+    }
+
+    /// Collect this context and all its descendants, except detached ones
+    fn collect_subtree(&self, out: &mut Vec<Context>) {
+        out.push(self.clone());
+        for child in self
+            .children_snapshot()
+            .iter()
+            .filter(|child| !child.is_detached())
+        {
+            child.collect_subtree(out);
+        }
+    }
+
+    /// One line per context in this subtree (name, live tasks, cancellation), at most
+    /// `limit` contexts
+    ///
+    /// Never blocks, so it is safe in the panic hook: whatever is locked at the
+    /// moment (e.g. by the panicking thread) is reported as busy.
+    pub(crate) fn try_outline(&self, limit: usize) -> String {
+        let mut outline = String::new();
+        let mut remaining = limit;
+        self.outline_into(0, &mut outline, &mut remaining);
+        outline
+    }
+
+    fn outline_into(&self, depth: usize, outline: &mut String, remaining: &mut usize) {
+        let indent = "  ".repeat(depth);
+        *remaining = remaining.saturating_sub(1);
+
+        let tasks = match self.tasks.try_len() {
+            Some(tasks) => format!("{tasks} task(s)"),
+            None => "tasks busy".to_string(),
+        };
+        let cancelled = if self.is_cancelled() {
+            ", cancelled"
+        } else {
+            ""
+        };
+        outline.push_str(&format!("{indent}{} ({tasks}{cancelled})\n", self.name));
+
+        let children = match self.children.try_lock() {
+            Ok(children) => children.clone(),
+            Err(_) => {
+                outline.push_str(&format!("{indent}  (children busy)\n"));
+                return;
+            }
+        };
+        for child in &children {
+            if *remaining == 0 {
+                outline.push_str(&format!("{indent}  ...\n"));
+                return;
+            }
+            child.outline_into(depth + 1, outline, remaining);
+        }
+    }
+
+    /// Describe contexts in this subtree that still hold live work
+    pub(crate) fn leaks(&self) -> Vec<String> {
+        let mut leaks = Vec::new();
+        if self.task_count() > 0 {
+            leaks.push(format!("{} ({} task(s))", self.path, self.task_count()));
+        }
+        self.collect_leaks(&mut leaks);
+        leaks
+    }
+
+    fn collect_leaks(&self, leaks: &mut Vec<String>) {
+        let Ok(children) = self.children.lock() else {
+            return;
+        };
+
+        for child in children.iter() {
+            let task_count = child.task_count();
+            if task_count > 0 {
+                leaks.push(format!("{} ({task_count} task(s))", child.path));
+            } else if std::sync::Arc::strong_count(child) > 1 && !child.is_cancelled() {
+                leaks.push(format!("{} (still referenced)", child.path));
+            }
+            child.collect_leaks(leaks);
+        }
+    }
+
+    /// Effective deadline of this context, if any
+    pub fn deadline(&self) -> Option<std::time::Instant> {
+        self.deadline.lock().ok().and_then(|deadline| *deadline)
+    }
+
+    /// Set a deadline, ignored if it is later than the current effective deadline
+    ///
+    /// Only enforced with the `tokio` feature, otherwise it is recorded (and
+    /// propagated) for the executor's own timers to honour.
+    pub(crate) fn set_deadline(&self, deadline: std::time::Instant) {
+        if let Ok(mut current) = self.deadline.lock() {
+            if current.is_some_and(|current| current <= deadline) {
+                return;
+            }
+            *current = Some(deadline);
+        }
+
+        // Auto-cancel once the deadline passes
+        #[cfg(feature = "tokio")]
+        {
+            let context = self.clone();
+            tokio::spawn(async move {
+                tokio::select! {
+                    _ = context.cancelled() => {}
+                    _ = tokio::time::sleep_until(deadline.into()) => {
+                        context.cancel_with_reason(crate::CancelReason::DeadlineExceeded);
+                    }
+                }
+            });
+        }
+    }
+
+    /// Get (or create) a named counter reported in this context's status
+    pub fn counter(&self, name: &str) -> crate::Counter {
+        match self.metrics.lock() {
+            Ok(mut metrics) => metrics.counter(name),
+            Err(_) => crate::Counter::default(),
+        }
+    }
+
+    /// Get (or create) a named gauge reported in this context's status
+    pub fn gauge(&self, name: &str) -> crate::Gauge {
+        match self.metrics.lock() {
+            Ok(mut metrics) => metrics.gauge(name),
+            Err(_) => crate::Gauge::default(),
+        }
     }
 
     /// Mark this context for persistence (distributed tracing)
@@ -102,18 +1224,134 @@ impl Context {
     }
 
     /// Get status information for this context and all children
+    ///
+    /// Each context's lock is only held while copying its children out, so
+    /// collecting a large tree doesn't block contexts being created or finished.
     pub fn status(&self) -> crate::status::ContextStatus {
-        let children = if let Ok(children_lock) = self.children.lock() {
-            children_lock.iter().map(|child| child.status()).collect()
-        } else {
-            Vec::new()
+        self.status_with(&crate::StatusOptions::default())
+    }
+
+    /// Like [`Context::status`], collecting only the part of the subtree allowed by
+    /// `options`
+    pub fn status_with(&self, options: &crate::StatusOptions) -> crate::status::ContextStatus {
+        self.filtered_status(options, 0).unwrap_or_else(|| {
+            let omitted_children = self.listed_children().len();
+            self.status_node(Vec::new(), omitted_children)
+        })
+    }
+
+    /// Status of this context, None if neither it nor a descendant within
+    /// `max_depth` matches the name and label filters
+    fn filtered_status(
+        &self,
+        options: &crate::StatusOptions,
+        depth: usize,
+    ) -> Option<crate::status::ContextStatus> {
+        let snapshot = self.listed_children();
+
+        let mut children = Vec::new();
+        if options
+            .max_depth
+            .map_or(true, |max_depth| depth < max_depth)
+        {
+            for child in &snapshot {
+                if options
+                    .max_children
+                    .is_some_and(|max_children| children.len() >= max_children)
+                {
+                    break;
+                }
+                children.extend(child.filtered_status(options, depth + 1));
+            }
+        }
+
+        let matches = options
+            .name_filter
+            .as_deref()
+            .map_or(true, |filter| crate::glob::matches(filter, &self.name))
+            && options
+                .label_filter
+                .as_deref()
+                .map_or(true, |label| self.has_label(label));
+        if !matches && children.is_empty() {
+            return None;
+        }
+
+        let omitted_children = snapshot.len() - children.len();
+        Some(self.status_node(children, omitted_children))
+    }
+
+    /// Status of this context itself, with already collected children
+    fn status_node(
+        &self,
+        children: Vec<crate::status::ContextStatus>,
+        omitted_children: usize,
+    ) -> crate::status::ContextStatus {
+        let (counters, gauges) = match self.metrics.lock() {
+            Ok(metrics) => (metrics.counter_values(), metrics.gauge_values()),
+            Err(_) => Default::default(),
         };
 
+        let elapsed = self.created_at.elapsed();
+        // `duration` is still filled in for existing readers
+        #[allow(deprecated)]
         crate::status::ContextStatus {
+            id: self.id,
             name: self.name.clone(),
+            path: self.path.clone(),
+            state: self.state(),
+            labels: self.labels(),
+            detached: self.is_detached(),
             is_cancelled: self.is_cancelled(),
-            duration: self.created_at.elapsed(),
+            cancel_reason: self.cancel_reason(),
+            duration: elapsed,
+            created_at: self.created_at_system,
+            elapsed,
+            total_duration: self.total_duration(),
+            task_count: self.task_count(),
+            deadline_remaining: self
+                .deadline()
+                .map(|deadline| deadline.saturating_duration_since(std::time::Instant::now())),
+            counters,
+            gauges,
+            schedule: self
+                .get_local::<crate::schedule::Schedule>()
+                .map(|schedule| schedule.status()),
+            concurrency: self
+                .get_local::<crate::limit::ConcurrencyLimit>()
+                .map(|limit| limit.status()),
+            #[cfg(feature = "tokio")]
+            rate_limit: self
+                .get_local::<crate::rate_limit::RateLimiter>()
+                .map(|limiter| limiter.status()),
+            #[cfg(not(feature = "tokio"))]
+            rate_limit: None,
+            consumer: self
+                .get_local::<crate::consumer::Consumer>()
+                .map(|consumer| consumer.status()),
+            mailbox: self
+                .get_local::<crate::actor::Mailbox>()
+                .map(|mailbox| mailbox.status()),
+            roll_up: self
+                .get_local::<crate::rollup::RollUp>()
+                .map(|roll_up| roll_up.status()),
+            health: self.own_health(),
+            readiness: self.readiness(),
+            heartbeat: self
+                .get_local::<crate::heartbeat::Heartbeat>()
+                .map(|heartbeat| heartbeat.status(self.state() == crate::ContextState::Running)),
+            panics: self.panics(),
+            progress: self.progress(),
+            message: self.message(),
+            events: self.events(),
+            processes: self.processes.statuses(),
+            #[cfg(feature = "opentelemetry")]
+            trace_id: crate::otel::trace_id(self)
+                .or_else(|| self.trace_id().map(|trace_id| format!("{trace_id:032x}"))),
+            #[cfg(not(feature = "opentelemetry"))]
+            trace_id: self.trace_id().map(|trace_id| format!("{trace_id:032x}")),
             children,
+            omitted_children,
         }
     }
 }
@@ -121,38 +1359,222 @@ impl Context {
 impl Clone for Context {
     fn clone(&self) -> Self {
         Context {
+            id: self.id,
             name: self.name.clone(),
+            path: self.path.clone(),
             created_at: self.created_at,
+            created_at_system: self.created_at_system,
+            this: self.this.clone(),
             parent: self.parent.clone(),
             children: self.children.clone(),
             cancellation_token: self.cancellation_token.clone(),
+            values: self.values.clone(),
+            tasks: self.tasks.clone(),
+            deadline: self.deadline.clone(),
+            cancel_reason: self.cancel_reason.clone(),
+            metrics: self.metrics.clone(),
+            #[cfg(feature = "tokio")]
+            cleanup_hooks: self.cleanup_hooks.clone(),
+            lifecycle: self.lifecycle.clone(),
+            progress: self.progress.clone(),
+            events: self.events.clone(),
+            processes: self.processes.clone(),
+            #[cfg(feature = "tracing")]
+            span: self.span.clone(),
         }
     }
 }
 
-/// Builder for configuring child contexts before spawning
+/// Drop children of `parent` only referenced by this list, with no tasks and no
+/// children left
+///
+/// Their last handle is gone and their work is done, so they can't be used or
+/// change anymore; dropping them also frees their registry entry.
+fn prune_children(parent: &Context, children: &mut Vec<std::sync::Arc<Context>>) {
+    children.retain(|child| {
+        let keep = std::sync::Arc::strong_count(child) > 1
+            || child.task_count() > 0
+            || child
+                .children
+                .lock()
+                .map_or(true, |grandchildren| !grandchildren.is_empty());
+        if !keep {
+            crate::rollup::pruned(parent, child);
+        }
+        keep
+    });
+}
+
+/// Marks a context created with `ContextBuilder::detach()`
+struct Detached;
+
+/// Configuration step of a `ContextBuilder`, applied to the child before it is attached
+type Setup = Box<dyn FnOnce(&Context) + Send>;
+
+/// Step of a `ContextBuilder` run once the child is attached
+type Start = Box<dyn FnOnce(&std::sync::Arc<Context>) + Send>;
+
+/// Builder for configuring child contexts before spawning or building them
+///
+/// Only holds the configuration: the child is created, attached to its parent and
+/// reported to the observer once [`ContextBuilder::build`] or
+/// [`ContextBuilder::spawn`] is called. A builder dropped before that leaves no
+/// trace.
 pub struct ContextBuilder {
-    pub(crate) context: std::sync::Arc<Context>,
+    parent: Context,
+    pub(crate) name: String,
+    detached: bool,
+    deadline: Option<std::time::Instant>,
+    /// Applied in order to the child before it is attached
+    setup: Vec<Setup>,
+    /// Run once the child is attached, e.g. to start monitoring it
+    started: Vec<Start>,
 }
 
 impl ContextBuilder {
+    /// Opt out of the parent's cancellation and deadline
+    ///
+    /// For fire-and-forget work that should outlive its parent (e.g. audit logging
+    /// after a request). The context still shows up under the parent in status, but
+    /// is neither cancelled nor waited for by the parent's `shutdown()`. A deadline
+    /// or timeout set on the builder still applies.
+    pub fn detach(mut self) -> Self {
+        self.detached = true;
+        self
+    }
+
+    /// Cancel the context at `deadline` (clamped to the parent's deadline)
+    pub fn deadline(mut self, deadline: std::time::Instant) -> Self {
+        self.deadline = Some(match self.deadline {
+            Some(current) => current.min(deadline),
+            None => deadline,
+        });
+        self
+    }
+
+    /// Cancel the context after `timeout` (clamped to the parent's deadline)
+    pub fn timeout(self, timeout: std::time::Duration) -> Self {
+        self.deadline(std::time::Instant::now() + timeout)
+    }
+
+    /// Apply `setup` to the child when it is built, before it is attached
+    pub(crate) fn configure(mut self, setup: impl FnOnce(&Context) + Send + 'static) -> Self {
+        self.setup.push(Box::new(setup));
+        self
+    }
+
+    /// Run `start` once the child is built and attached
+    #[cfg(feature = "tokio")]
+    pub(crate) fn on_start(
+        mut self,
+        start: impl FnOnce(&std::sync::Arc<Context>) + Send + 'static,
+    ) -> Self {
+        self.started.push(Box::new(start));
+        self
+    }
+
+    /// Finish configuring and return the child context, without spawning a task
+    ///
+    /// For long-lived subsystems that spawn their own tasks. Call
+    /// [`Context::complete`] or [`Context::fail`] once its work is done, a spawned
+    /// child does that on its own.
+    ///
+    /// # Panics
+    ///
+    /// If the child would exceed the `max_children()` quota of its parent or a
+    /// `max_depth()` quota of the parent or an ancestor, or its name is taken under
+    /// [`crate::ChildNamePolicy::Error`]. Use [`ContextBuilder::try_build`] where
+    /// that can happen (e.g. per-request children).
+    #[track_caller]
+    pub fn build(self) -> std::sync::Arc<Context> {
+        match self.try_build() {
+            Ok(context) => context,
+            Err(error) => panic!("child refused: {error}"),
+        }
+    }
+
+    /// Like [`ContextBuilder::build`], but fails instead of panicking if the child
+    /// is refused
+    ///
+    /// The checks run atomically with attaching the child, so concurrent callers
+    /// can't overshoot a quota.
+    pub fn try_build(self) -> Result<std::sync::Arc<Context>, crate::ChildError> {
+        let ContextBuilder {
+            parent,
+            name,
+            detached,
+            deadline,
+            setup,
+            started,
+        } = self;
+
+        let context = parent.attach_child(&name, detached, |context| {
+            if let Some(deadline) = deadline {
+                context.set_deadline(deadline);
+            }
+            for setup in setup {
+                setup(context);
+            }
+        })?;
+        for start in started {
+            start(&context);
+        }
+        Ok(context)
+    }
+
     /// Spawn task with this child context
+    ///
+    /// # Panics
+    ///
+    /// Like [`ContextBuilder::build`], if the child is refused.
+    #[cfg(feature = "tokio")]
+    #[track_caller]
     pub fn spawn<F, Fut>(self, task: F) -> tokio::task::JoinHandle<Fut::Output>
     where
         F: FnOnce(std::sync::Arc<Context>) -> Fut + Send + 'static,
         Fut: std::future::Future + Send + 'static,
         Fut::Output: Send + 'static,
     {
-        let context = self.context;
-        tokio::spawn(async move { task(context).await })
+        ContextBuilder::spawn_built(self.build(), task)
+    }
+
+    /// Spawn `task` as the task of a child just built, finishing the child with it
+    #[cfg(feature = "tokio")]
+    pub(crate) fn spawn_built<F, Fut>(
+        context: std::sync::Arc<Context>,
+        task: F,
+    ) -> tokio::task::JoinHandle<Fut::Output>
+    where
+        F: FnOnce(std::sync::Arc<Context>) -> Fut + Send + 'static,
+        Fut: std::future::Future + Send + 'static,
+        Fut::Output: Send + 'static,
+    {
+        if let Some(parent) = context.parent() {
+            crate::observer::notify(|observer| observer.on_child_spawned(&parent, &context));
+        }
+        let guard = crate::state::FinishGuard {
+            context: context.clone(),
+        };
+        context.clone().spawn_unlimited(async move {
+            let _guard = guard;
+            task(context).await
+        })
     }
 }
 
 /// Global context storage
-static GLOBAL_CONTEXT: std::sync::LazyLock<std::sync::Arc<Context>> =
-    std::sync::LazyLock::new(|| Context::new("global"));
+static GLOBAL_CONTEXT: std::sync::OnceLock<std::sync::Arc<Context>> = std::sync::OnceLock::new();
 
 /// Get the global application context
 pub fn global() -> std::sync::Arc<Context> {
-    GLOBAL_CONTEXT.clone()
+    GLOBAL_CONTEXT
+        .get_or_init(|| Context::new("global"))
+        .clone()
+}
+
+/// Create the global context with a custom name (used by `#[main(name = "...")]`)
+///
+/// Returns false if the global context already exists.
+pub fn init_global(name: &str) -> bool {
+    GLOBAL_CONTEXT.set(Context::new(name)).is_ok()
 }
@@ -0,0 +1,104 @@
+//! Unix domain socket control endpoint for external tooling
+//!
+//! The protocol is line based: each request is one line, each response is one
+//! line of JSON.
+//!
+//! - `status`: the full [`crate::Status`] tree
+//! - `cancel <pattern>`: cancel contexts whose path matches the glob, responds
+//!   with `{"cancelled": [<paths>]}`
+//!
+//! Failures are reported as `{"error": "<message>"}`.
+
+/// Serve the control protocol on a Unix socket until the global context is cancelled
+///
+/// A stale socket at `path` is replaced, any other file there is an error. The
+/// socket is only accessible to the current user (mode `0600`), and removed again
+/// when the server stops.
+///
+/// ```rust,no_run
+/// #[fastn_context::main]
+/// async fn main() {
+///     fastn_context::global().spawn(fastn_context::control::serve("/run/app.sock"));
+/// }
+/// ```
+pub async fn serve(path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    let path = path.as_ref();
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)?,
+        Ok(_) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path.display()),
+            ));
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+
+    let listener = tokio::net::UnixListener::bind(path)?;
+    // Cancelling contexts must not be open to other users
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    let global = crate::global();
+
+    let result = loop {
+        tokio::select! {
+            _ = global.cancelled() => break Ok(()),
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    global.spawn(handle_connection(stream));
+                }
+                Err(e) => break Err(e),
+            },
+        }
+    };
+
+    let _ = std::fs::remove_file(path);
+    result
+}
+
+/// Answer requests on one connection until the client disconnects
+async fn handle_connection(stream: tokio::net::UnixStream) {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+    let global = crate::global();
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = tokio::io::BufReader::new(reader).lines();
+
+    loop {
+        let line = tokio::select! {
+            _ = global.cancelled() => return,
+            line = lines.next_line() => line,
+        };
+        let Ok(Some(line)) = line else {
+            return;
+        };
+
+        let mut response = handle_request(line.trim());
+        response.push('\n');
+        if writer.write_all(response.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Execute a single request, returning its JSON response
+fn handle_request(request: &str) -> String {
+    let (command, argument) = request
+        .split_once(char::is_whitespace)
+        .map(|(command, argument)| (command, argument.trim()))
+        .unwrap_or((request, ""));
+
+    let response = match (command, argument) {
+        ("status", "") => serde_json::to_value(crate::status())
+            .unwrap_or_else(|e| serde_json::json!({ "error": e.to_string() })),
+        ("cancel", "") => serde_json::json!({ "error": "usage: cancel <pattern>" }),
+        ("cancel", pattern) => {
+            serde_json::json!({ "cancelled": crate::cancel_matching(pattern) })
+        }
+        _ => serde_json::json!({ "error": format!("unknown command: {request}") }),
+    };
+
+    response.to_string()
+}
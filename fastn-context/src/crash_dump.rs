@@ -0,0 +1,73 @@
+//! JSON dump of the final context tree for post-mortem debugging
+
+/// Where crash dumps are written, see `set_crash_dump_path()`
+static CRASH_DUMP_PATH: std::sync::RwLock<Option<std::path::PathBuf>> =
+    std::sync::RwLock::new(None);
+
+/// Content of a crash dump file
+#[derive(serde::Serialize)]
+struct CrashDump<'a> {
+    reason: &'a str,
+    pid: u32,
+    status: crate::Status,
+}
+
+/// Write a crash dump to `path` on fatal errors and forced shutdowns, None to stop
+///
+/// A dump is written when a panic reaches [`crate::install_panic_hook`], when
+/// `#[fastn_context::main]` fails, when [`crate::shutdown`] has to force-abort
+/// tasks, and when repeated signals escalate (see [`crate::SignalPolicy`]). It holds
+/// the full [`crate::Status`] tree (events, errors, timings) as JSON along with the
+/// reason; a later dump replaces an earlier one.
+pub fn set_crash_dump_path(path: Option<std::path::PathBuf>) {
+    if let Ok(mut current) = CRASH_DUMP_PATH.write() {
+        *current = path;
+    }
+}
+
+/// Write a crash dump now, returning its path (None if no path is configured)
+pub fn write_crash_dump(reason: &str) -> std::io::Result<Option<std::path::PathBuf>> {
+    let Some(path) = CRASH_DUMP_PATH.read().ok().and_then(|path| path.clone()) else {
+        return Ok(None);
+    };
+
+    let dump = CrashDump {
+        reason,
+        pid: std::process::id(),
+        status: crate::status(),
+    };
+    let json = serde_json::to_vec_pretty(&dump).map_err(std::io::Error::other)?;
+
+    // Write next to the target and rename, so readers never see a partial dump
+    let mut partial = path.clone().into_os_string();
+    partial.push(".partial");
+    std::fs::write(&partial, json)?;
+    std::fs::rename(&partial, &path)?;
+    Ok(Some(path))
+}
+
+/// Write a crash dump if configured, reporting the outcome on stderr
+pub(crate) fn fatal(reason: &str) {
+    match write_crash_dump(reason) {
+        Ok(Some(path)) => eprintln!("crash dump written to {}", path.display()),
+        Ok(None) => {}
+        Err(e) => eprintln!("failed to write crash dump: {e}"),
+    }
+}
+
+/// Like [`fatal`], for the panic hook: writing the dump walks the whole tree, which
+/// would deadlock on a lock held by the panicking thread, so it happens on another
+/// thread and is given up on after a second
+pub(crate) fn fatal_from_panic_hook(reason: &str) {
+    let reason = reason.to_string();
+    let (done, written) = std::sync::mpsc::channel();
+    let spawned = std::thread::Builder::new()
+        .name("fastn-crash-dump".to_string())
+        .spawn(move || {
+            fatal(&reason);
+            let _ = done.send(());
+        });
+    if spawned.is_ok() {
+        let _ = written.recv_timeout(std::time::Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,58 @@
+std::thread_local! {
+    /// Context of the task being polled on this thread, set by [`Scoped`]
+    static CURRENT_CONTEXT: std::cell::RefCell<Option<std::sync::Arc<crate::Context>>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+pin_project_lite::pin_project! {
+    /// Makes `context` the current context while `future` is polled
+    ///
+    /// Like a tokio task-local, but works on any executor.
+    struct Scoped<F> {
+        context: std::sync::Arc<crate::Context>,
+        #[pin]
+        future: F,
+    }
+}
+
+/// Restores the previous current context, also when the poll panics
+struct Restore(Option<std::sync::Arc<crate::Context>>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        CURRENT_CONTEXT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+impl<F: std::future::Future> std::future::Future for Scoped<F> {
+    type Output = F::Output;
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        let this = self.project();
+        let previous =
+            CURRENT_CONTEXT.with(|current| current.borrow_mut().replace(this.context.clone()));
+        let _restore = Restore(previous);
+        this.future.poll(cx)
+    }
+}
+
+/// Run a future with `ctx` as its current context (see [`crate::Context::current`])
+pub async fn with_current<F>(ctx: std::sync::Arc<crate::Context>, fut: F) -> F::Output
+where
+    F: std::future::Future,
+{
+    Scoped {
+        context: ctx,
+        future: fut,
+    }
+    .await
+}
+
+/// Get the task-local context, if the current task is running inside one
+pub(crate) fn try_current() -> Option<std::sync::Arc<crate::Context>> {
+    CURRENT_CONTEXT.with(|current| current.borrow().clone())
+}
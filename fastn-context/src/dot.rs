@@ -0,0 +1,42 @@
+impl crate::Status {
+    /// Render the context tree as a Graphviz DOT graph
+    ///
+    /// Nodes are colored by state: green while running, blue once completed, red
+    /// when failed and orange when cancelled. Render with e.g.
+    /// `dot -Tsvg tree.dot -o tree.svg`.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph contexts {\n");
+        out.push_str("    rankdir=LR;\n");
+        out.push_str("    node [shape=box, style=\"rounded,filled\", fontname=\"monospace\"];\n");
+        write_node(&self.global_context, &mut out);
+        out.push_str("}\n");
+        out
+    }
+}
+
+fn write_node(ctx: &crate::ContextStatus, out: &mut String) {
+    let color = match (&ctx.state, ctx.is_cancelled) {
+        (crate::ContextState::Failed(_), _) => "#f4a6a6",
+        (crate::ContextState::Completed, _) => "#a6c8f4",
+        (crate::ContextState::Cancelled, _) | (crate::ContextState::Running, true) => "#f4d0a6",
+        (crate::ContextState::Running, false) => "#b6e3b6",
+    };
+
+    out.push_str(&format!(
+        "    c{} [label=\"{}\\n{}\", fillcolor=\"{color}\"];\n",
+        ctx.id,
+        escape(&ctx.name),
+        escape(&ctx.details("running"))
+    ));
+    for child in &ctx.children {
+        out.push_str(&format!("    c{} -> c{};\n", ctx.id, child.id));
+        write_node(child, out);
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
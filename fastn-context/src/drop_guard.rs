@@ -0,0 +1,26 @@
+/// Cancels its context when dropped, see [`crate::Context::drop_guard`]
+#[must_use = "the context is cancelled as soon as the guard is dropped"]
+pub struct ContextDropGuard {
+    context: Option<crate::Context>,
+}
+
+impl ContextDropGuard {
+    pub(crate) fn new(context: crate::Context) -> Self {
+        ContextDropGuard {
+            context: Some(context),
+        }
+    }
+
+    /// Keep the context alive: dropping the guard no longer cancels it
+    pub fn disarm(mut self) {
+        self.context = None;
+    }
+}
+
+impl Drop for ContextDropGuard {
+    fn drop(&mut self) {
+        if let Some(context) = self.context.take() {
+            context.cancel_with_reason(crate::CancelReason::Dropped);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+/// Return types accepted by `#[fastn_context::main]`: `()` and `Result<(), E>`
+#[doc(hidden)]
+pub trait MainOutput {
+    /// Message to report if the body failed
+    fn error_message(&self) -> Option<String>;
+}
+
+impl MainOutput for () {
+    fn error_message(&self) -> Option<String> {
+        None
+    }
+}
+
+impl<E: std::fmt::Display> MainOutput for Result<(), E> {
+    fn error_message(&self) -> Option<String> {
+        // `{:#}` includes the cause chain for `anyhow::Error` and friends
+        self.as_ref().err().map(|e| format!("{e:#}"))
+    }
+}
+
+/// Run the body of `#[fastn_context::main]`, then shut the global context down
+///
+/// `before` runs first; the body is skipped if it fails. `after` runs once the
+/// global context has shut down. Maps the outcome to the process exit code: `0` if
+/// everything succeeded and every task finished within `grace`, `error_exit_code`
+/// if a hook or the body returned an error, `unclean_exit_code` if tasks had to be
+/// force-aborted or panicked.
+#[cfg(feature = "tokio")]
+pub async fn run_main<B, M, A>(
+    before: B,
+    main: M,
+    after: A,
+    grace: std::time::Duration,
+    error_exit_code: u8,
+    unclean_exit_code: u8,
+) -> std::process::ExitCode
+where
+    B: std::future::Future,
+    B::Output: MainOutput,
+    M: std::future::Future,
+    M::Output: MainOutput,
+    A: std::future::Future,
+    A::Output: MainOutput,
+{
+    let mut failed = report_error(before.await.error_message());
+    if !failed {
+        failed = report_error(main.await.error_message());
+    }
+    #[cfg(feature = "crash-dump")]
+    if failed {
+        crate::crash_dump::fatal("main returned an error");
+    }
+
+    let report = crate::shutdown(grace).await;
+    if !report.is_clean() {
+        eprint!("{report}");
+    }
+
+    failed |= report_error(after.await.error_message());
+
+    if failed {
+        std::process::ExitCode::from(error_exit_code)
+    } else if !report.is_clean() {
+        std::process::ExitCode::from(unclean_exit_code)
+    } else {
+        std::process::ExitCode::SUCCESS
+    }
+}
+
+/// Print an error, returning whether there was one
+#[cfg(feature = "tokio")]
+fn report_error(error: Option<String>) -> bool {
+    match error {
+        Some(error) => {
+            eprintln!("Error: {error}");
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,42 @@
+/// Maximum number of events kept per context (oldest are dropped first)
+const MAX_EVENTS: usize = 32;
+
+/// Timestamped breadcrumb recorded via [`crate::Context::event`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContextEvent {
+    pub at: std::time::SystemTime,
+    pub message: String,
+}
+
+/// Bounded ring buffer of the most recent events of a context
+#[derive(Default)]
+pub(crate) struct EventLog {
+    events: std::collections::VecDeque<ContextEvent>,
+}
+
+impl crate::Context {
+    /// Record a timestamped event (e.g. "connected to db"), shown in this context's status
+    ///
+    /// Only the most recent events are kept.
+    pub fn event(&self, message: impl Into<String>) {
+        if let Ok(mut log) = self.events.lock() {
+            if log.events.len() == MAX_EVENTS {
+                log.events.pop_front();
+            }
+            log.events.push_back(ContextEvent {
+                at: std::time::SystemTime::now(),
+                message: message.into(),
+            });
+        }
+        crate::status_stream::tree_changed();
+    }
+
+    /// Events recorded via `event()`, oldest first
+    pub fn events(&self) -> Vec<ContextEvent> {
+        self.events
+            .lock()
+            .map(|log| log.events.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,37 @@
+/// axum extractor for the request context created by [`crate::ContextLayer`]
+///
+/// ```rust,ignore
+/// async fn handler(ctx: fastn_context::Ctx) -> &'static str {
+///     ctx.counter("hits").inc();
+///     "ok"
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Ctx(pub std::sync::Arc<crate::Context>);
+
+impl std::ops::Deref for Ctx {
+    type Target = std::sync::Arc<crate::Context>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<S: Sync> axum_core::extract::FromRequestParts<S> for Ctx {
+    type Rejection = (http::StatusCode, &'static str);
+
+    async fn from_request_parts(
+        parts: &mut http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<std::sync::Arc<crate::Context>>()
+            .cloned()
+            .map(Ctx)
+            .ok_or((
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                "request context missing, is fastn_context::ContextLayer installed?",
+            ))
+    }
+}
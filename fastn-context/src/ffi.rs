@@ -0,0 +1,252 @@
+//! C ABI for native plugins and host applications embedding Rust
+//!
+//! Contexts are passed around as opaque `FastnContext *` handles, each of which
+//! keeps its context alive until released with `fastn_context_free()`. Strings
+//! returned by the library are released with `fastn_context_string_free()`. All
+//! functions accept null handles and do nothing (or return null / false) for them.
+//! No function unwinds into the caller: should one panic, it returns null / false
+//! instead.
+//!
+//! ```c
+//! typedef struct FastnContext FastnContext;
+//! typedef struct FastnTask FastnTask;
+//!
+//! FastnContext *fastn_context_global(void);
+//! FastnContext *fastn_context_find(const char *path);
+//! FastnContext *fastn_context_child(const FastnContext *parent, const char *name);
+//! void fastn_context_free(FastnContext *context);
+//! void fastn_context_cancel(const FastnContext *context);
+//! bool fastn_context_is_cancelled(const FastnContext *context);
+//! bool fastn_context_wait_cancelled(const FastnContext *context, uint64_t timeout_ms);
+//! FastnTask *fastn_context_task_begin(const FastnContext *context, const char *name);
+//! void fastn_context_task_end(FastnTask *task);
+//! char *fastn_context_status_json(const FastnContext *context);
+//! void fastn_context_string_free(char *string);
+//! ```
+
+/// Opaque handle to a context
+pub struct FastnContext {
+    context: std::sync::Arc<crate::Context>,
+}
+
+/// Opaque handle to work tracked by a context, see `fastn_context_task_begin()`
+pub struct FastnTask {
+    _task: crate::SyncTask,
+}
+
+/// Run the body of an entry point, returning `fallback` if it panics (unwinding
+/// out of an `extern "C"` function is undefined behavior)
+fn guard<T>(fallback: T, body: impl FnOnce() -> T) -> T {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(body)).unwrap_or(fallback)
+}
+
+fn into_handle(context: std::sync::Arc<crate::Context>) -> *mut FastnContext {
+    Box::into_raw(Box::new(FastnContext { context }))
+}
+
+/// # Safety
+///
+/// `context` must be null or a live handle returned by this module.
+unsafe fn context<'a>(context: *const FastnContext) -> Option<&'a crate::Context> {
+    // SAFETY: guaranteed by the caller
+    unsafe { context.as_ref() }.map(|handle| &*handle.context)
+}
+
+/// # Safety
+///
+/// `string` must be null or a valid NUL-terminated string.
+unsafe fn str<'a>(string: *const std::ffi::c_char) -> Option<&'a str> {
+    if string.is_null() {
+        return None;
+    }
+    // SAFETY: guaranteed by the caller
+    unsafe { std::ffi::CStr::from_ptr(string) }.to_str().ok()
+}
+
+/// Handle to the global context
+#[no_mangle]
+pub extern "C" fn fastn_context_global() -> *mut FastnContext {
+    guard(std::ptr::null_mut(), || into_handle(crate::global()))
+}
+
+/// Handle to the live context at `path` (e.g. `global/plugins/foo`), null if
+/// there is none
+///
+/// # Safety
+///
+/// `path` must be null or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn fastn_context_find(path: *const std::ffi::c_char) -> *mut FastnContext {
+    // SAFETY: guaranteed by the caller
+    let Some(path) = (unsafe { str(path) }) else {
+        return std::ptr::null_mut();
+    };
+    guard(std::ptr::null_mut(), || {
+        match crate::find(path)
+            .into_iter()
+            .find(|context| context.path() == path)
+        {
+            Some(context) => into_handle(context),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Create a child context named `name`, null for a null parent or invalid name, or
+/// if the parent refuses the child (a quota or its child name policy)
+///
+/// # Safety
+///
+/// `parent` must be null or a live handle, `name` null or a valid NUL-terminated
+/// string.
+#[no_mangle]
+pub unsafe extern "C" fn fastn_context_child(
+    parent: *const FastnContext,
+    name: *const std::ffi::c_char,
+) -> *mut FastnContext {
+    // SAFETY: guaranteed by the caller
+    let (Some(parent), Some(name)) = (unsafe { (context(parent), str(name)) }) else {
+        return std::ptr::null_mut();
+    };
+    guard(std::ptr::null_mut(), || {
+        match parent.child(name).try_build() {
+            Ok(child) => into_handle(child),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Release a handle (the context itself is not cancelled)
+///
+/// # Safety
+///
+/// `context` must be null or a live handle; it must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn fastn_context_free(context: *mut FastnContext) {
+    if !context.is_null() {
+        // SAFETY: created by `into_handle()`, released only once per the contract
+        let handle = unsafe { Box::from_raw(context) };
+        guard((), || drop(handle));
+    }
+}
+
+/// Cancel the context and its subtree
+///
+/// # Safety
+///
+/// `context` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn fastn_context_cancel(context: *const FastnContext) {
+    // SAFETY: guaranteed by the caller
+    if let Some(context) = unsafe { self::context(context) } {
+        guard((), || context.cancel_with_reason("cancelled via FFI"));
+    }
+}
+
+/// Check if the context is cancelled
+///
+/// # Safety
+///
+/// `context` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn fastn_context_is_cancelled(context: *const FastnContext) -> bool {
+    // SAFETY: guaranteed by the caller
+    unsafe { self::context(context) }.is_some_and(|context| guard(false, || context.is_cancelled()))
+}
+
+/// Block the calling thread until the context is cancelled or `timeout_ms` passes,
+/// returns whether it is cancelled
+///
+/// # Safety
+///
+/// `context` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn fastn_context_wait_cancelled(
+    context: *const FastnContext,
+    timeout_ms: u64,
+) -> bool {
+    // SAFETY: guaranteed by the caller
+    unsafe { self::context(context) }.is_some_and(|context| {
+        guard(false, || {
+            context
+                .cancel_flag()
+                .park_until_cancelled(std::time::Duration::from_millis(timeout_ms))
+        })
+    })
+}
+
+/// Register native work with the context: it counts as a live task (and shutdown
+/// waits for it) until `fastn_context_task_end()`
+///
+/// Null for a null context or invalid name, or if the context reached its
+/// `max_tracked_tasks()` quota.
+///
+/// # Safety
+///
+/// `context` must be null or a live handle, `name` null or a valid NUL-terminated
+/// string.
+#[no_mangle]
+pub unsafe extern "C" fn fastn_context_task_begin(
+    context: *const FastnContext,
+    name: *const std::ffi::c_char,
+) -> *mut FastnTask {
+    // SAFETY: guaranteed by the caller
+    let (Some(context), Some(name)) = (unsafe { (self::context(context), str(name)) }) else {
+        return std::ptr::null_mut();
+    };
+    guard(std::ptr::null_mut(), || match context.try_sync_task(name) {
+        Ok(task) => Box::into_raw(Box::new(FastnTask { _task: task })),
+        Err(_) => std::ptr::null_mut(),
+    })
+}
+
+/// Mark work registered with `fastn_context_task_begin()` done
+///
+/// # Safety
+///
+/// `task` must be null or a live task handle; it must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn fastn_context_task_end(task: *mut FastnTask) {
+    if !task.is_null() {
+        // SAFETY: created by `fastn_context_task_begin()`, ended only once per the contract
+        let task = unsafe { Box::from_raw(task) };
+        guard((), || drop(task));
+    }
+}
+
+/// Status of the context's subtree as JSON, release with
+/// `fastn_context_string_free()`
+///
+/// # Safety
+///
+/// `context` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn fastn_context_status_json(
+    context: *const FastnContext,
+) -> *mut std::ffi::c_char {
+    // SAFETY: guaranteed by the caller
+    let Some(context) = (unsafe { self::context(context) }) else {
+        return std::ptr::null_mut();
+    };
+    guard(std::ptr::null_mut(), || {
+        serde_json::to_string(&context.status())
+            .ok()
+            .and_then(|json| std::ffi::CString::new(json).ok())
+            .map_or(std::ptr::null_mut(), std::ffi::CString::into_raw)
+    })
+}
+
+/// Release a string returned by this library
+///
+/// # Safety
+///
+/// `string` must be null or returned by this library; it must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn fastn_context_string_free(string: *mut std::ffi::c_char) {
+    if !string.is_null() {
+        // SAFETY: created by `CString::into_raw()`, released only once per the contract
+        let string = unsafe { std::ffi::CString::from_raw(string) };
+        guard((), || drop(string));
+    }
+}
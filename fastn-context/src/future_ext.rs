@@ -0,0 +1,127 @@
+/// Error returned when a future was interrupted by context cancellation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cancelled {
+    /// Why the context was cancelled, if known
+    pub reason: Option<crate::CancelReason>,
+}
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.reason {
+            Some(reason) => write!(f, "context cancelled: {reason}"),
+            None => write!(f, "context cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Outcome of [`crate::Context::timeout`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeoutResult<T> {
+    Completed(T),
+    /// The timeout (or the context's earlier deadline) elapsed first
+    TimedOut,
+    Cancelled(Cancelled),
+}
+
+impl<T> TimeoutResult<T> {
+    /// The output if the future completed
+    pub fn completed(self) -> Option<T> {
+        match self {
+            TimeoutResult::Completed(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Future returned by [`ContextFutureExt::with_context`] and [`crate::Context::run`]
+    #[must_use = "futures do nothing unless polled"]
+    pub struct WithContext<F> {
+        #[pin]
+        future: F,
+        #[pin]
+        cancelled: tokio_util::sync::WaitForCancellationFutureOwned,
+        context: crate::Context,
+    }
+}
+
+impl<F: std::future::Future> std::future::Future for WithContext<F> {
+    type Output = Result<F::Output, Cancelled>;
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        let this = self.project();
+
+        // Cancellation wins if both are ready
+        if this.cancelled.poll(cx).is_ready() {
+            return std::task::Poll::Ready(Err(Cancelled {
+                reason: this.context.cancel_reason(),
+            }));
+        }
+
+        this.future.poll(cx).map(Ok)
+    }
+}
+
+/// Race futures against context cancellation
+pub trait ContextFutureExt: std::future::Future + Sized {
+    /// Resolve to `Err(Cancelled)` as soon as `ctx` is cancelled, dropping the future
+    fn with_context(self, ctx: &crate::Context) -> WithContext<Self> {
+        WithContext {
+            future: self,
+            cancelled: ctx.cancelled_owned(),
+            context: ctx.clone(),
+        }
+    }
+}
+
+impl<F: std::future::Future> ContextFutureExt for F {}
+
+impl crate::Context {
+    /// Run a future until it completes or this context is cancelled
+    ///
+    /// Replaces the usual `select!` on [`crate::Context::cancelled`]:
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let ctx = fastn_context::Context::new("app");
+    /// ctx.cancel();
+    ///
+    /// let result = ctx.run(tokio::time::sleep(std::time::Duration::from_secs(10))).await;
+    /// assert!(result.is_err());
+    /// # }
+    /// ```
+    pub fn run<F: std::future::Future>(&self, future: F) -> WithContext<F> {
+        future.with_context(self)
+    }
+
+    /// Run a future with a timeout, clamped to this context's remaining deadline
+    #[cfg(feature = "tokio")]
+    pub async fn timeout<F: std::future::Future>(
+        &self,
+        duration: std::time::Duration,
+        future: F,
+    ) -> TimeoutResult<F::Output> {
+        let now = std::time::Instant::now();
+        let mut deadline = now.checked_add(duration);
+        if let Some(context_deadline) = self.deadline() {
+            deadline = Some(deadline.map_or(context_deadline, |d| d.min(context_deadline)));
+        }
+
+        let result = match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline.into(), self.run(future)).await,
+            None => Ok(self.run(future).await),
+        };
+
+        match result {
+            Ok(Ok(value)) => TimeoutResult::Completed(value),
+            Ok(Err(cancelled)) => TimeoutResult::Cancelled(cancelled),
+            Err(_) => TimeoutResult::TimedOut,
+        }
+    }
+}
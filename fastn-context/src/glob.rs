@@ -0,0 +1,37 @@
+/// Match a slash separated path against a glob pattern
+///
+/// `*` matches any characters within one segment, `?` matches one character and a
+/// `**` segment matches any number of segments (including none).
+pub(crate) fn matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    matches_segments(&pattern, &path)
+}
+
+fn matches_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match (pattern.first(), path.first()) {
+        (None, None) => true,
+        (Some(&"**"), _) => {
+            matches_segments(&pattern[1..], path)
+                || (!path.is_empty() && matches_segments(pattern, &path[1..]))
+        }
+        (Some(segment_pattern), Some(segment)) => {
+            matches_segment(segment_pattern.as_bytes(), segment.as_bytes())
+                && matches_segments(&pattern[1..], &path[1..])
+        }
+        _ => false,
+    }
+}
+
+fn matches_segment(pattern: &[u8], segment: &[u8]) -> bool {
+    match (pattern.first(), segment.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            matches_segment(&pattern[1..], segment)
+                || (!segment.is_empty() && matches_segment(pattern, &segment[1..]))
+        }
+        (Some(b'?'), Some(_)) => matches_segment(&pattern[1..], &segment[1..]),
+        (Some(p), Some(s)) if p == s => matches_segment(&pattern[1..], &segment[1..]),
+        _ => false,
+    }
+}
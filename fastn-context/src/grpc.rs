@@ -0,0 +1,231 @@
+//! gRPC context propagation as tower layers, usable with tonic clients and servers
+//!
+//! [`GrpcClientLayer`] wraps a client (e.g. a `tonic::transport::Channel`) and
+//! encodes the current context's deadline as `grpc-timeout` and its trace and
+//! baggage as metadata. [`GrpcServerLayer`] goes on the server (e.g.
+//! `tonic::transport::Server::builder().layer(..)`) and gives every call a child
+//! context linked to the caller, with the caller's deadline. Handlers get it from
+//! the request extensions:
+//!
+//! ```rust,ignore
+//! async fn say_hello(&self, request: tonic::Request<HelloRequest>) -> Result<..> {
+//!     let ctx = request.extensions().get::<std::sync::Arc<fastn_context::Context>>();
+//!     ..
+//! }
+//! ```
+
+/// gRPC metadata carrying the call's timeout
+pub const GRPC_TIMEOUT_HEADER: &str = "grpc-timeout";
+
+/// `tower::Layer` propagating the context of outgoing gRPC calls
+///
+/// Like [`crate::ClientLayer`] (calls are aborted when their context cancels and
+/// carry `traceparent` / `baggage`), additionally setting `grpc-timeout` to the
+/// context's remaining deadline so the server stops working on the call in time.
+/// A shorter timeout already set on the request is kept.
+#[derive(Clone, Default)]
+pub struct GrpcClientLayer {
+    context: Option<std::sync::Arc<crate::Context>>,
+}
+
+impl GrpcClientLayer {
+    /// Make calls under the current context
+    pub fn new() -> Self {
+        GrpcClientLayer::default()
+    }
+
+    /// Make calls under `context` instead of the current context
+    pub fn context(mut self, context: std::sync::Arc<crate::Context>) -> Self {
+        self.context = Some(context);
+        self
+    }
+}
+
+impl<S> tower_layer::Layer<S> for GrpcClientLayer {
+    type Service = GrpcClientService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        let client = match &self.context {
+            Some(context) => crate::ClientLayer::new().context(context.clone()),
+            None => crate::ClientLayer::new(),
+        };
+        GrpcClientService {
+            inner: tower_layer::Layer::layer(&client, inner),
+            context: self.context.clone(),
+        }
+    }
+}
+
+/// Service produced by [`GrpcClientLayer`]
+#[derive(Clone)]
+pub struct GrpcClientService<S> {
+    inner: crate::ClientService<S>,
+    context: Option<std::sync::Arc<crate::Context>>,
+}
+
+impl<S, B> tower_service::Service<http::Request<B>> for GrpcClientService<S>
+where
+    S: tower_service::Service<http::Request<B>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = crate::ClientError<S::Error>;
+    type Future = <crate::ClientService<S> as tower_service::Service<http::Request<B>>>::Future;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: http::Request<B>) -> Self::Future {
+        let context = self.context.clone().unwrap_or_else(crate::Context::current);
+
+        if let Some(deadline) = context.deadline() {
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            let current = request
+                .headers()
+                .get(GRPC_TIMEOUT_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_timeout);
+            if current.map_or(true, |current| remaining < current) {
+                if let Ok(value) = http::HeaderValue::from_str(&encode_timeout(remaining)) {
+                    request.headers_mut().insert(GRPC_TIMEOUT_HEADER, value);
+                }
+            }
+        }
+
+        self.inner.call(request)
+    }
+}
+
+/// `tower::Layer` giving every incoming gRPC call a child context linked to the
+/// caller
+///
+/// The child adopts the caller's trace, baggage and deadline (from `grpc-timeout`
+/// or the propagated baggage, whichever is sooner). It is inserted into the
+/// request extensions as `Arc<Context>` and cancelled once the call completes or
+/// is dropped.
+#[derive(Clone)]
+pub struct GrpcServerLayer {
+    parent: std::sync::Arc<crate::Context>,
+    name: String,
+}
+
+impl GrpcServerLayer {
+    /// Create call contexts as children of `parent`, named `grpc`
+    pub fn new(parent: std::sync::Arc<crate::Context>) -> Self {
+        GrpcServerLayer {
+            parent,
+            name: "grpc".to_string(),
+        }
+    }
+
+    /// Name used for the per-call child contexts
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+}
+
+impl<S> tower_layer::Layer<S> for GrpcServerLayer {
+    type Service = GrpcServerService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        GrpcServerService {
+            inner,
+            parent: self.parent.clone(),
+            name: self.name.clone(),
+        }
+    }
+}
+
+/// Service produced by [`GrpcServerLayer`]
+#[derive(Clone)]
+pub struct GrpcServerService<S> {
+    inner: S,
+    parent: std::sync::Arc<crate::Context>,
+    name: String,
+}
+
+impl<S, B> tower_service::Service<http::Request<B>> for GrpcServerService<S>
+where
+    S: tower_service::Service<http::Request<B>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future =
+        std::pin::Pin<Box<dyn std::future::Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: http::Request<B>) -> Self::Future {
+        let headers = request
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)));
+        let mut child = self.parent.child(&self.name).from_headers(headers);
+        if let Some(deadline) = request
+            .headers()
+            .get(GRPC_TIMEOUT_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_timeout)
+            .and_then(|timeout| std::time::Instant::now().checked_add(timeout))
+        {
+            child = child.deadline(deadline);
+        }
+
+        let context = child.build();
+        let guard = context.drop_guard();
+        request.extensions_mut().insert(context.clone());
+
+        let response = crate::with_current(context, self.inner.call(request));
+        Box::pin(async move {
+            let _guard = guard;
+            response.await
+        })
+    }
+}
+
+/// Encode a timeout as `grpc-timeout` (at most 8 digits, most precise unit that fits)
+fn encode_timeout(timeout: std::time::Duration) -> String {
+    const MAX: u128 = 99_999_999;
+    let nanos = timeout.as_nanos();
+    for (unit, per_unit) in [
+        ("n", 1),
+        ("u", 1_000),
+        ("m", 1_000_000),
+        ("S", 1_000_000_000),
+        ("M", 60_000_000_000),
+        ("H", 3_600_000_000_000),
+    ] {
+        // Round up so the remote deadline never ends before ours
+        let value = nanos.div_ceil(per_unit);
+        if value <= MAX {
+            return format!("{value}{unit}");
+        }
+    }
+    format!("{MAX}H")
+}
+
+/// Parse a `grpc-timeout` value
+fn parse_timeout(value: &str) -> Option<std::time::Duration> {
+    let unit = value.get(value.len().checked_sub(1)?..)?;
+    let amount: u64 = value[..value.len() - 1].parse().ok()?;
+    Some(match unit {
+        "H" => std::time::Duration::from_secs(amount.checked_mul(3600)?),
+        "M" => std::time::Duration::from_secs(amount.checked_mul(60)?),
+        "S" => std::time::Duration::from_secs(amount),
+        "m" => std::time::Duration::from_millis(amount),
+        "u" => std::time::Duration::from_micros(amount),
+        "n" => std::time::Duration::from_nanos(amount),
+        _ => return None,
+    })
+}
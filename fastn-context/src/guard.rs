@@ -0,0 +1,100 @@
+type Cleanup<T> =
+    Box<dyn FnOnce(T) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>> + Send>;
+
+/// Resource with async cleanup, run on the context unless the resource is released
+///
+/// Created by [`crate::Context::guard`]. Derefs to the resource. If the guard is
+/// dropped while still holding it (e.g. the task returned early on cancellation,
+/// hit an error, or was aborted), the cleanup is spawned as a task of the context,
+/// so [`crate::Context::shutdown`] waits for it. Call [`ContextGuard::release`] to
+/// take the resource back once it was used successfully.
+#[must_use = "the cleanup runs as soon as the guard is dropped"]
+pub struct ContextGuard<T: Send + 'static> {
+    resource: Option<T>,
+    cleanup: Option<Cleanup<T>>,
+    context: crate::Context,
+}
+
+impl<T: Send + 'static> ContextGuard<T> {
+    /// Take the resource back without running the cleanup
+    pub fn release(mut self) -> T {
+        self.cleanup = None;
+        self.resource
+            .take()
+            .expect("resource is only taken on release or drop")
+    }
+}
+
+impl<T: Send + 'static> std::ops::Deref for ContextGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.resource
+            .as_ref()
+            .expect("resource is only taken on release or drop")
+    }
+}
+
+impl<T: Send + 'static> std::ops::DerefMut for ContextGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.resource
+            .as_mut()
+            .expect("resource is only taken on release or drop")
+    }
+}
+
+impl<T: Send + 'static> Drop for ContextGuard<T> {
+    fn drop(&mut self) {
+        let (Some(resource), Some(cleanup)) = (self.resource.take(), self.cleanup.take()) else {
+            return;
+        };
+        // Without a runtime (e.g. while it shuts down) the resource's own drop has to do
+        if tokio::runtime::Handle::try_current().is_ok() {
+            self.context.spawn_cleanup(cleanup(resource));
+        }
+    }
+}
+
+impl crate::Context {
+    /// Tie `resource` to this context: `cleanup` runs with it unless it is released
+    ///
+    /// For connections, locks, leases, temporary files and the like that need async
+    /// cleanup, which plain `Drop` can't do. See [`ContextGuard`].
+    pub fn guard<T, F, Fut>(&self, resource: T, cleanup: F) -> ContextGuard<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(T) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        ContextGuard {
+            resource: Some(resource),
+            cleanup: Some(Box::new(move |resource| Box::pin(cleanup(resource)))),
+            context: self.clone(),
+        }
+    }
+
+    /// [`crate::Context::guard`] for database transactions: rolled back unless
+    /// released (to commit)
+    ///
+    /// A failed rollback is recorded as an event of this context.
+    ///
+    /// ```rust,ignore
+    /// let mut tx = ctx.rollback_guard(pool.begin().await?, |tx| tx.rollback());
+    /// sqlx::query("UPDATE accounts SET ..").execute(&mut **tx).await?;
+    /// tx.release().commit().await?; // cancelled or failed before this: rolled back
+    /// ```
+    pub fn rollback_guard<T, F, Fut, E>(&self, transaction: T, rollback: F) -> ContextGuard<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(T) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<(), E>> + Send + 'static,
+        E: std::fmt::Display,
+    {
+        let context = self.clone();
+        self.guard(transaction, move |transaction| async move {
+            if let Err(e) = rollback(transaction).await {
+                context.event(format!("rollback failed: {e}"));
+            }
+        })
+    }
+}
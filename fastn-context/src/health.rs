@@ -0,0 +1,147 @@
+/// Health of a context, ordered from best to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Health {
+    #[default]
+    Healthy,
+    /// Working, but with reduced capacity or quality (e.g. a replica is lagging)
+    Degraded,
+    /// Not doing its job (e.g. the database is unreachable)
+    Unhealthy,
+}
+
+impl std::fmt::Display for Health {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Health::Healthy => write!(f, "healthy"),
+            Health::Degraded => write!(f, "degraded"),
+            Health::Unhealthy => write!(f, "unhealthy"),
+        }
+    }
+}
+
+/// Health a context reported about itself via [`crate::Context::set_health`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HealthStatus {
+    pub health: Health,
+    pub reason: Option<String>,
+}
+
+/// A context dragging down an aggregate [`HealthReport`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HealthCause {
+    pub path: String,
+    pub health: Health,
+    pub reason: Option<String>,
+}
+
+/// Aggregate health of a context and its required children, see
+/// [`crate::Context::health`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HealthReport {
+    pub health: Health,
+    /// Contexts that are not healthy, worst first
+    pub causes: Vec<HealthCause>,
+}
+
+impl HealthReport {
+    /// Fit for a liveness probe: anything but [`Health::Unhealthy`]
+    pub fn is_live(&self) -> bool {
+        self.health != Health::Unhealthy
+    }
+
+    /// HTTP status code for a probe endpoint: `200` while live, `503` otherwise
+    pub fn http_status(&self) -> u16 {
+        if self.is_live() { 200 } else { 503 }
+    }
+}
+
+impl std::fmt::Display for HealthReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.health)?;
+        for (i, cause) in self.causes.iter().enumerate() {
+            let separator = if i == 0 { " (" } else { "; " };
+            match &cause.reason {
+                Some(reason) => write!(f, "{separator}{} {}: {reason}", cause.path, cause.health)?,
+                None => write!(f, "{separator}{} {}", cause.path, cause.health)?,
+            }
+        }
+        if !self.causes.is_empty() {
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+/// Marks a context whose health counts towards its parent's, stored as a typed value
+struct Required;
+
+impl crate::Context {
+    /// Report the health of this context, with an optional `reason` (empty for none)
+    ///
+    /// Replaces the previously reported health. It counts towards the parent's
+    /// aggregate health only if the context was built with
+    /// [`crate::ContextBuilder::required`].
+    pub fn set_health(&self, health: Health, reason: &str) {
+        self.set(HealthStatus {
+            health,
+            reason: (!reason.is_empty()).then(|| reason.to_string()),
+        });
+        crate::status_stream::tree_changed();
+    }
+
+    /// Health reported by this context itself, None if it never reported any
+    pub fn own_health(&self) -> Option<HealthStatus> {
+        self.get_local::<HealthStatus>()
+            .map(|status| (*status).clone())
+    }
+
+    /// Aggregate health: the worst of this context's own health and the aggregate
+    /// health of its required children
+    pub fn health(&self) -> HealthReport {
+        let mut report = HealthReport::default();
+        self.collect_health(&mut report);
+        report
+            .causes
+            .sort_by(|a, b| b.health.cmp(&a.health).then_with(|| a.path.cmp(&b.path)));
+        report
+    }
+
+    fn collect_health(&self, report: &mut HealthReport) {
+        if let Some(status) = self.get_local::<HealthStatus>() {
+            if status.health != Health::Healthy {
+                report.health = report.health.max(status.health);
+                report.causes.push(HealthCause {
+                    path: self.path().to_string(),
+                    health: status.health,
+                    reason: status.reason.clone(),
+                });
+            }
+        }
+
+        for child in self.listed_children() {
+            if child.get_local::<Required>().is_some() {
+                child.collect_health(report);
+            }
+        }
+    }
+}
+
+impl crate::ContextBuilder {
+    /// Count the health of the context towards its parent's aggregate health
+    ///
+    /// A parent is at most as healthy as its worst required child, so marking
+    /// e.g. the `db` and `cache` contexts as required makes [`crate::health`]
+    /// reflect them.
+    pub fn required(self) -> Self {
+        self.configure(|context| context.set(Required))
+    }
+}
+
+/// Aggregate health of the global context, for liveness / readiness probes
+pub fn health() -> HealthReport {
+    crate::context::global().health()
+}
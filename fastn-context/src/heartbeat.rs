@@ -0,0 +1,144 @@
+/// Staleness threshold used when `heartbeat()` is called without `stale_after()`
+pub const DEFAULT_STALE_AFTER: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Heartbeat state of a context, see [`crate::Context::heartbeat`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeartbeatStatus {
+    /// Time since the last heartbeat (or since monitoring started)
+    pub since_last: std::time::Duration,
+    pub stale_after: std::time::Duration,
+    /// The context is still running but its heartbeat is overdue, it may be hung
+    pub stale: bool,
+}
+
+/// Heartbeat bookkeeping, stored as a typed value on the monitored context
+pub(crate) struct Heartbeat {
+    last: std::sync::Mutex<std::time::Instant>,
+    stale_after: std::time::Duration,
+}
+
+impl Heartbeat {
+    fn new(stale_after: std::time::Duration) -> Self {
+        Heartbeat {
+            last: std::sync::Mutex::new(std::time::Instant::now()),
+            stale_after,
+        }
+    }
+
+    fn beat(&self) {
+        if let Ok(mut last) = self.last.lock() {
+            *last = std::time::Instant::now();
+        }
+    }
+
+    pub(crate) fn since_last(&self) -> std::time::Duration {
+        self.last
+            .lock()
+            .map(|last| last.elapsed())
+            .unwrap_or_default()
+    }
+
+    /// Heartbeat status; only a running context can be stale
+    pub(crate) fn status(&self, running: bool) -> HeartbeatStatus {
+        let since_last = self.since_last();
+        HeartbeatStatus {
+            since_last,
+            stale_after: self.stale_after,
+            stale: running && since_last > self.stale_after,
+        }
+    }
+}
+
+impl crate::Context {
+    /// Signal that this context is alive and making progress
+    ///
+    /// Once a context heartbeats, its status is flagged as stale whenever the next
+    /// heartbeat is overdue (after [`DEFAULT_STALE_AFTER`] unless configured with
+    /// [`crate::ContextBuilder::stale_after`]), see [`crate::Status::stale_contexts`].
+    pub fn heartbeat(&self) {
+        match self.get_local::<Heartbeat>() {
+            Some(heartbeat) => heartbeat.beat(),
+            None => self.set(Heartbeat::new(DEFAULT_STALE_AFTER)),
+        }
+    }
+}
+
+impl crate::ContextBuilder {
+    /// Expect a `heartbeat()` at least every `threshold`, flagging the context as
+    /// stale otherwise
+    ///
+    /// Monitoring starts right away, the first heartbeat is due `threshold` after
+    /// the context is created.
+    pub fn stale_after(self, threshold: std::time::Duration) -> Self {
+        self.configure(move |context| context.set(Heartbeat::new(threshold)))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl crate::ContextBuilder {
+    /// Cancel the context (and its subtree) when no `heartbeat()` arrives within
+    /// `timeout`
+    ///
+    /// A per-subtree watchdog: the context is cancelled with
+    /// [`crate::CancelReason::Watchdog`]. The first heartbeat is due `timeout` after
+    /// the context is created; once the context finished (see
+    /// [`crate::Context::complete`]) it is no longer watched. Implies [`crate::ContextBuilder::stale_after`] with the
+    /// same threshold.
+    pub fn watchdog(self, timeout: std::time::Duration) -> Self {
+        self.configure(move |context| context.set(Heartbeat::new(timeout)))
+            .on_start(move |context| watch(context, timeout))
+    }
+}
+
+/// Cancel `context` once its heartbeat is more than `timeout` late
+#[cfg(feature = "tokio")]
+fn watch(context: &std::sync::Arc<crate::Context>, timeout: std::time::Duration) {
+    let Some(heartbeat) = context.get_local::<Heartbeat>() else {
+        return;
+    };
+
+    let token = context.cancellation_token();
+    let context = std::sync::Arc::downgrade(context);
+    tokio::spawn(async move {
+        loop {
+            // Nothing left to watch once the context finished or was dropped
+            let Some(watched) = context
+                .upgrade()
+                .filter(|watched| watched.total_duration().is_none())
+            else {
+                break;
+            };
+
+            let remaining = timeout.saturating_sub(heartbeat.since_last());
+            if remaining.is_zero() {
+                watched.cancel_with_reason(crate::CancelReason::Watchdog(timeout));
+                break;
+            }
+            drop(watched);
+
+            tokio::select! {
+                _ = token.cancelled() => break,
+                _ = tokio::time::sleep(remaining) => {}
+            }
+        }
+    });
+}
+
+impl crate::Status {
+    /// Contexts whose heartbeat is overdue, e.g. hung workers
+    pub fn stale_contexts(&self) -> Vec<&crate::ContextStatus> {
+        fn collect<'a>(ctx: &'a crate::ContextStatus, stale: &mut Vec<&'a crate::ContextStatus>) {
+            if ctx.heartbeat.is_some_and(|heartbeat| heartbeat.stale) {
+                stale.push(ctx);
+            }
+            for child in &ctx.children {
+                collect(child, stale);
+            }
+        }
+
+        let mut stale = Vec::new();
+        collect(&self.global_context, &mut stale);
+        stale
+    }
+}
@@ -0,0 +1,148 @@
+/// Upper bounds of the [`LatencyHistogram`] buckets
+pub const LATENCY_BUCKETS: [std::time::Duration; 12] = [
+    std::time::Duration::from_millis(1),
+    std::time::Duration::from_millis(5),
+    std::time::Duration::from_millis(10),
+    std::time::Duration::from_millis(25),
+    std::time::Duration::from_millis(50),
+    std::time::Duration::from_millis(100),
+    std::time::Duration::from_millis(250),
+    std::time::Duration::from_millis(500),
+    std::time::Duration::from_secs(1),
+    std::time::Duration::from_millis(2500),
+    std::time::Duration::from_secs(5),
+    std::time::Duration::from_secs(10),
+];
+
+/// Lifetimes of finished contexts, by context name
+static LIFETIMES: std::sync::LazyLock<
+    std::sync::Mutex<std::collections::BTreeMap<String, LatencyHistogram>>,
+> = std::sync::LazyLock::new(Default::default);
+
+/// Latency distribution over the fixed [`LATENCY_BUCKETS`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LatencyHistogram {
+    /// Samples per bucket: `buckets[i]` counts samples in `(LATENCY_BUCKETS[i - 1],
+    /// LATENCY_BUCKETS[i]]`, the extra last bucket counts slower samples
+    pub buckets: Vec<u64>,
+    pub count: u64,
+    pub sum: std::time::Duration,
+    pub max: std::time::Duration,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        LatencyHistogram {
+            buckets: vec![0; LATENCY_BUCKETS.len() + 1],
+            count: 0,
+            sum: std::time::Duration::ZERO,
+            max: std::time::Duration::ZERO,
+        }
+    }
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, latency: std::time::Duration) {
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| latency <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.sum = self.sum.saturating_add(latency);
+        self.max = self.max.max(latency);
+    }
+
+    /// Average latency, None without samples
+    pub fn mean(&self) -> Option<std::time::Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let mean = self.sum.as_nanos() / u128::from(self.count);
+        Some(std::time::Duration::new(
+            (mean / 1_000_000_000) as u64,
+            (mean % 1_000_000_000) as u32,
+        ))
+    }
+
+    /// Estimated latency at quantile `q` (e.g. `0.99`), None without samples
+    ///
+    /// Interpolates linearly within the bucket holding the quantile, and never
+    /// exceeds the slowest sample.
+    pub fn percentile(&self, q: f64) -> Option<std::time::Duration> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let rank = (q.clamp(0.0, 1.0) * self.count as f64).max(1.0);
+        let mut seen = 0;
+        for (bucket, &count) in self.buckets.iter().enumerate() {
+            if count > 0 && (seen + count) as f64 >= rank {
+                let lower = match bucket {
+                    0 => std::time::Duration::ZERO,
+                    _ => LATENCY_BUCKETS[bucket - 1],
+                };
+                let upper = LATENCY_BUCKETS.get(bucket).copied().unwrap_or(self.max);
+                let within = (rank - seen as f64) / count as f64;
+                let estimate = lower + upper.saturating_sub(lower).mul_f64(within);
+                return Some(estimate.min(self.max));
+            }
+            seen += count;
+        }
+        Some(self.max)
+    }
+}
+
+impl std::fmt::Display for LatencyHistogram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (
+            self.percentile(0.5),
+            self.percentile(0.95),
+            self.percentile(0.99),
+        ) {
+            (Some(p50), Some(p95), Some(p99)) => write!(
+                f,
+                "p50 {}, p95 {}, p99 {}",
+                format_latency(p50),
+                format_latency(p95),
+                format_latency(p99)
+            ),
+            _ => write!(f, "no samples"),
+        }
+    }
+}
+
+/// Format a latency as `12ms` below a second, like `format_duration()` otherwise
+fn format_latency(latency: std::time::Duration) -> String {
+    if latency < std::time::Duration::from_secs(1) {
+        format!("{}ms", latency.as_millis())
+    } else {
+        crate::status::format_duration(latency)
+    }
+}
+
+/// Record how long a finished context lived
+pub(crate) fn record_lifetime(name: &str, lifetime: std::time::Duration) {
+    if let Ok(mut lifetimes) = LIFETIMES.lock() {
+        match lifetimes.get_mut(name) {
+            Some(histogram) => histogram.record(lifetime),
+            None => {
+                let mut histogram = LatencyHistogram::default();
+                histogram.record(lifetime);
+                lifetimes.insert(name.to_string(), histogram);
+            }
+        }
+    }
+}
+
+/// Lifetime histograms of finished contexts by context name
+///
+/// Span-latency style metrics for request/job contexts: every context that
+/// finished (see `Context::total_duration()`) is counted under its name.
+pub fn lifetimes() -> std::collections::BTreeMap<String, LatencyHistogram> {
+    LIFETIMES
+        .lock()
+        .map(|lifetimes| lifetimes.clone())
+        .unwrap_or_default()
+}
@@ -0,0 +1,292 @@
+//! HTTP endpoints serving the status tree
+//!
+//! - `GET /status`: the [`crate::Status`] tree as JSON
+//! - `GET /status/tree`: the tree rendered as plain text, see
+//!   [`crate::Status::render_tree`]
+//! - `GET /status/stream`: Server-Sent Events pushing status changes, see
+//!   [`status_events`]
+//! - `GET /status/dashboard`: a self-contained HTML page polling `/status` and
+//!   rendering the live tree (with the `dashboard` feature)
+//! - `GET /health`: the aggregate [`crate::HealthReport`] of the tree as JSON, with
+//!   status `503` while unhealthy (for liveness probes)
+//! - `GET /ready`: `ready`, or `503` listing the contexts that reported they are
+//!   not ready (for readiness probes, see [`crate::not_ready_contexts`])
+//!
+//! Any other path gets a `404`.
+
+/// Page served at `/status/dashboard`
+#[cfg(feature = "dashboard")]
+const DASHBOARD: &str = include_str!("dashboard.html");
+
+/// Interval of SSE keep-alive comments, also bounds how long an abandoned stream
+/// lingers
+const KEEP_ALIVE: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// `tower::Service` serving the status endpoints, see [`status_handler`]
+#[derive(Debug, Clone, Default)]
+pub struct StatusHandler;
+
+/// Service answering `/status`, `/status/tree`, `/status/stream`,
+/// `/status/dashboard`, `/health` and `/ready`
+///
+/// Response bodies implement `http_body::Body`, so the service plugs into axum and
+/// into hyper (e.g. via `hyper_util::service::TowerToHyperService`) as is:
+///
+/// ```rust,ignore
+/// let app = axum::Router::new()
+///     .route_service("/status", fastn_context::http::status_handler())
+///     .route_service("/status/{*rest}", fastn_context::http::status_handler())
+///     .route_service("/health", fastn_context::http::status_handler())
+///     .route_service("/ready", fastn_context::http::status_handler());
+/// ```
+pub fn status_handler() -> StatusHandler {
+    StatusHandler
+}
+
+/// Body of the status endpoints: a complete document or an event stream
+pub struct StatusBody {
+    inner: BodyInner,
+}
+
+enum BodyInner {
+    Full(Option<bytes::Bytes>),
+    Events(tokio::sync::mpsc::Receiver<bytes::Bytes>),
+}
+
+impl StatusBody {
+    fn full(body: impl Into<bytes::Bytes>) -> Self {
+        StatusBody {
+            inner: BodyInner::Full(Some(body.into())),
+        }
+    }
+}
+
+impl http_body::Body for StatusBody {
+    type Data = bytes::Bytes;
+    type Error = std::convert::Infallible;
+
+    fn poll_frame(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        let data = match &mut self.get_mut().inner {
+            BodyInner::Full(body) => std::task::Poll::Ready(body.take()),
+            BodyInner::Events(events) => events.poll_recv(cx),
+        };
+        data.map(|data| data.map(|data| Ok(http_body::Frame::data(data))))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        matches!(self.inner, BodyInner::Full(None))
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        match &self.inner {
+            BodyInner::Full(Some(body)) => http_body::SizeHint::with_exact(body.len() as u64),
+            BodyInner::Full(None) => http_body::SizeHint::with_exact(0),
+            BodyInner::Events(_) => http_body::SizeHint::default(),
+        }
+    }
+}
+
+/// Build the response for a request to `path`, for hand-written handlers
+///
+/// Must be called inside a tokio runtime (`/status/stream` spawns a task).
+pub fn status_response(path: &str) -> ::http::Response<StatusBody> {
+    let (content_type, body) = match path.trim_end_matches('/') {
+        "/status" => match serde_json::to_string(&crate::status()) {
+            Ok(json) => ("application/json", StatusBody::full(json)),
+            Err(e) => return response(::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        },
+        "/status/tree" => (
+            "text/plain; charset=utf-8",
+            StatusBody::full(crate::status().render_tree()),
+        ),
+        "/status/stream" => ("text/event-stream", status_events()),
+        #[cfg(feature = "dashboard")]
+        "/status/dashboard" => ("text/html; charset=utf-8", StatusBody::full(DASHBOARD)),
+        "/health" => return health_response(),
+        "/ready" => return ready_response(),
+        _ => return response(::http::StatusCode::NOT_FOUND, "not found".to_string()),
+    };
+
+    let mut response = ::http::Response::new(body);
+    response.headers_mut().insert(
+        ::http::header::CONTENT_TYPE,
+        ::http::HeaderValue::from_static(content_type),
+    );
+    if content_type == "text/event-stream" {
+        response.headers_mut().insert(
+            ::http::header::CACHE_CONTROL,
+            ::http::HeaderValue::from_static("no-cache"),
+        );
+    }
+    response
+}
+
+fn health_response() -> ::http::Response<StatusBody> {
+    let report = crate::health();
+    let status = ::http::StatusCode::from_u16(report.http_status())
+        .unwrap_or(::http::StatusCode::SERVICE_UNAVAILABLE);
+    let mut response = match serde_json::to_string(&report) {
+        Ok(json) => response(status, json),
+        Err(e) => return response(::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    response.headers_mut().insert(
+        ::http::header::CONTENT_TYPE,
+        ::http::HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+fn ready_response() -> ::http::Response<StatusBody> {
+    let not_ready = crate::not_ready_contexts();
+    if not_ready.is_empty() {
+        return response(::http::StatusCode::OK, "ready\n".to_string());
+    }
+
+    let mut body = String::new();
+    for (path, reason) in not_ready {
+        match reason {
+            Some(reason) => body.push_str(&format!("{path}: {reason}\n")),
+            None => body.push_str(&format!("{path}\n")),
+        }
+    }
+    response(::http::StatusCode::SERVICE_UNAVAILABLE, body)
+}
+
+fn response(status: ::http::StatusCode, body: String) -> ::http::Response<StatusBody> {
+    let mut response = ::http::Response::new(StatusBody::full(body));
+    *response.status_mut() = status;
+    response
+}
+
+/// Server-Sent Events body pushing status changes (see [`crate::status_stream`])
+///
+/// The first event, `snapshot`, carries the full [`crate::Status`]. Each later
+/// `delta` event carries `{"changed": [...], "removed": [...]}`: the contexts (as
+/// [`crate::ContextStatus`] without `children`) that were added or changed, and
+/// the IDs of contexts no longer in the tree. Changes in `elapsed` and
+/// `deadline_remaining` alone don't count. Must be called inside a tokio runtime.
+pub fn status_events() -> StatusBody {
+    let (sender, receiver) = tokio::sync::mpsc::channel(16);
+    tokio::spawn(async move {
+        let mut snapshots = crate::status_stream();
+        snapshots.mark_unchanged();
+        // Taken now: the last published snapshot may miss recent changes
+        let status = crate::status();
+        let mut previous = flatten(&status);
+
+        let snapshot = serde_json::to_string(&status).unwrap_or_default();
+        if sender.send(event("snapshot", &snapshot)).await.is_err() {
+            return;
+        }
+
+        loop {
+            let message = tokio::select! {
+                changed = snapshots.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                    let current = flatten(&snapshots.borrow_and_update());
+                    let delta = delta(&previous, &current);
+                    previous = current;
+                    match delta {
+                        Some(delta) => event("delta", &delta.to_string()),
+                        None => continue,
+                    }
+                }
+                _ = tokio::time::sleep(KEEP_ALIVE) => bytes::Bytes::from_static(b": keep-alive\n\n"),
+            };
+
+            if sender.send(message).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    StatusBody {
+        inner: BodyInner::Events(receiver),
+    }
+}
+
+fn event(name: &str, data: &str) -> bytes::Bytes {
+    format!("event: {name}\ndata: {data}\n\n").into()
+}
+
+/// Every context of the snapshot by ID, without children and time-derived fields
+fn flatten(
+    status: &crate::Status,
+) -> std::collections::BTreeMap<u64, serde_json::Map<String, serde_json::Value>> {
+    fn collect(
+        ctx: &crate::ContextStatus,
+        out: &mut std::collections::BTreeMap<u64, serde_json::Map<String, serde_json::Value>>,
+    ) {
+        if let Ok(serde_json::Value::Object(mut node)) = serde_json::to_value(ctx) {
+            node.remove("children");
+            out.insert(ctx.id, node);
+        }
+        for child in &ctx.children {
+            collect(child, out);
+        }
+    }
+
+    let mut out = std::collections::BTreeMap::new();
+    collect(&status.global_context, &mut out);
+    out
+}
+
+/// Added/changed and removed contexts between two flattened snapshots, None if
+/// nothing changed
+fn delta(
+    previous: &std::collections::BTreeMap<u64, serde_json::Map<String, serde_json::Value>>,
+    current: &std::collections::BTreeMap<u64, serde_json::Map<String, serde_json::Value>>,
+) -> Option<serde_json::Value> {
+    let without_time = |node: &serde_json::Map<String, serde_json::Value>| {
+        let mut node = node.clone();
+        node.remove("elapsed");
+        node.remove("duration");
+        node.remove("deadline_remaining");
+        // Still changes when the heartbeat goes stale
+        if let Some(serde_json::Value::Object(heartbeat)) = node.get_mut("heartbeat") {
+            heartbeat.remove("since_last");
+        }
+        node
+    };
+
+    let changed: Vec<_> = current
+        .iter()
+        .filter(|(id, node)| {
+            previous
+                .get(id)
+                .map_or(true, |old| without_time(old) != without_time(node))
+        })
+        .map(|(_, node)| serde_json::Value::Object(node.clone()))
+        .collect();
+    let removed: Vec<_> = previous
+        .keys()
+        .filter(|id| !current.contains_key(id))
+        .collect();
+
+    if changed.is_empty() && removed.is_empty() {
+        return None;
+    }
+    Some(serde_json::json!({ "changed": changed, "removed": removed }))
+}
+
+impl<B> tower_service::Service<::http::Request<B>> for StatusHandler {
+    type Response = ::http::Response<StatusBody>;
+    type Error = std::convert::Infallible;
+    type Future = std::future::Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: ::http::Request<B>) -> Self::Future {
+        std::future::ready(Ok(status_response(request.uri().path())))
+    }
+}
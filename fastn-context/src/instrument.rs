@@ -0,0 +1,22 @@
+/// Run one call of an `#[fastn_context::instrument_context]` function
+///
+/// The call runs under a new child of the current context, named `name`. Once the
+/// call returns its duration is recorded as the child's `duration_ms` gauge and the
+/// child is completed; the child is cancelled when the call returns or is dropped,
+/// stopping anything it spawned.
+pub async fn instrument<F>(name: &str, call: F) -> F::Output
+where
+    F: std::future::Future,
+{
+    let context = crate::Context::current().child(name).build();
+    let _guard = context.drop_guard();
+    let started = std::time::Instant::now();
+
+    let output = crate::with_current(context.clone(), call).await;
+
+    context
+        .gauge("duration_ms")
+        .set(started.elapsed().as_millis() as i64);
+    context.complete();
+    output
+}
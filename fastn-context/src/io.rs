@@ -0,0 +1,130 @@
+pin_project_lite::pin_project! {
+    /// `AsyncRead` adapter failing pending and future reads once its context is cancelled
+    ///
+    /// Reads fail with an `std::io::ErrorKind::Other` error wrapping
+    /// [`crate::Cancelled`], see [`cancelled_error`].
+    pub struct CancellableReader<R> {
+        #[pin]
+        inner: R,
+        // Boxed so the adapter is `Unpin` whenever the wrapped stream is
+        cancelled: std::pin::Pin<Box<tokio_util::sync::WaitForCancellationFutureOwned>>,
+        context: crate::Context,
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// `AsyncWrite` adapter failing pending and future writes once its context is cancelled
+    ///
+    /// Writes and flushes fail with an `std::io::ErrorKind::Other` error wrapping
+    /// [`crate::Cancelled`], see [`cancelled_error`]. `poll_shutdown` is passed
+    /// through so the stream can still be closed.
+    pub struct CancellableWriter<W> {
+        #[pin]
+        inner: W,
+        // Boxed so the adapter is `Unpin` whenever the wrapped stream is
+        cancelled: std::pin::Pin<Box<tokio_util::sync::WaitForCancellationFutureOwned>>,
+        context: crate::Context,
+    }
+}
+
+/// The [`crate::Cancelled`] error inside an IO error from a cancellable adapter, if any
+pub fn cancelled_error(error: &std::io::Error) -> Option<&crate::Cancelled> {
+    error.get_ref()?.downcast_ref::<crate::Cancelled>()
+}
+
+/// Poll the context's cancellation, returning the IO error to fail with
+fn poll_cancelled(
+    cancelled: &mut std::pin::Pin<Box<tokio_util::sync::WaitForCancellationFutureOwned>>,
+    context: &crate::Context,
+    cx: &mut std::task::Context<'_>,
+) -> Option<std::io::Error> {
+    if !context.is_cancelled() && std::future::Future::poll(cancelled.as_mut(), cx).is_pending() {
+        return None;
+    }
+
+    Some(std::io::Error::other(crate::Cancelled {
+        reason: context.cancel_reason(),
+    }))
+}
+
+impl<R> CancellableReader<R> {
+    pub fn new(inner: R, context: &crate::Context) -> Self {
+        CancellableReader {
+            inner,
+            cancelled: Box::pin(context.cancelled_owned()),
+            context: context.clone(),
+        }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<W> CancellableWriter<W> {
+    pub fn new(inner: W, context: &crate::Context) -> Self {
+        CancellableWriter {
+            inner,
+            cancelled: Box::pin(context.cancelled_owned()),
+            context: context.clone(),
+        }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<R: tokio::io::AsyncRead> tokio::io::AsyncRead for CancellableReader<R> {
+    fn poll_read(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let this = self.project();
+        if let Some(error) = poll_cancelled(this.cancelled, this.context, cx) {
+            return std::task::Poll::Ready(Err(error));
+        }
+        this.inner.poll_read(cx, buf)
+    }
+}
+
+impl<W: tokio::io::AsyncWrite> tokio::io::AsyncWrite for CancellableWriter<W> {
+    fn poll_write(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let this = self.project();
+        if let Some(error) = poll_cancelled(this.cancelled, this.context, cx) {
+            return std::task::Poll::Ready(Err(error));
+        }
+        this.inner.poll_write(cx, buf)
+    }
+
+    fn poll_flush(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let this = self.project();
+        if let Some(error) = poll_cancelled(this.cancelled, this.context, cx) {
+            return std::task::Poll::Ready(Err(error));
+        }
+        this.inner.poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        self.project().inner.poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,127 @@
+//! Status journal: periodic snapshots of the context tree appended as JSON lines
+//!
+//! Each line is a [`crate::Status`] of the journaled context's subtree. When the
+//! file grows past its size limit it is rotated (`status.jsonl` becomes
+//! `status.jsonl.1`, `.1` becomes `.2`, ...), keeping a bounded window of history
+//! to reconstruct what the tree looked like before an incident.
+
+/// Periodically appends status snapshots to a JSONL file, with rotation
+///
+/// ```rust,no_run
+/// #[fastn_context::main]
+/// async fn main() {
+///     fastn_context::journal::StatusJournal::new("/var/log/my-app/status.jsonl")
+///         .interval(std::time::Duration::from_secs(5))
+///         .max_bytes(50 * 1024 * 1024)
+///         .max_files(5)
+///         .spawn(&fastn_context::global());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct StatusJournal {
+    path: std::path::PathBuf,
+    interval: std::time::Duration,
+    max_bytes: u64,
+    max_files: usize,
+}
+
+impl StatusJournal {
+    /// Journal to `path` every 10s, rotating at 10 MiB and keeping 3 rotated files
+    pub fn new(path: impl Into<std::path::PathBuf>) -> Self {
+        StatusJournal {
+            path: path.into(),
+            interval: std::time::Duration::from_secs(10),
+            max_bytes: 10 * 1024 * 1024,
+            max_files: 3,
+        }
+    }
+
+    /// How often a snapshot is appended
+    pub fn interval(mut self, interval: std::time::Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Rotate the file once it grows past this size
+    pub fn max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Rotated files to keep besides the current one (0 truncates instead)
+    pub fn max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    /// Journal the subtree of `context` as a task of `context`, until it is
+    /// cancelled; a final snapshot is written on cancellation
+    ///
+    /// Fails if the journal file can't be written.
+    pub fn spawn(self, context: &crate::Context) -> tokio::task::JoinHandle<std::io::Result<()>> {
+        let journaled = context.clone();
+        context.spawn_named("status journal", async move { self.run(&journaled).await })
+    }
+
+    async fn run(self, context: &crate::Context) -> std::io::Result<()> {
+        let mut interval = tokio::time::interval(self.interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            let cancelled = tokio::select! {
+                _ = context.cancelled() => true,
+                _ = interval.tick() => false,
+            };
+
+            self.append(context)?;
+            if cancelled {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Append one snapshot, rotating first if the file is full
+    fn append(&self, context: &crate::Context) -> std::io::Result<()> {
+        let status = crate::Status {
+            global_context: context.status(),
+            persisted_contexts: None,
+            lifetimes: crate::lifetimes(),
+            runtime: crate::RuntimeStatus::current(),
+            timestamp: std::time::SystemTime::now(),
+        };
+        let mut line = serde_json::to_vec(&status).map_err(std::io::Error::other)?;
+        line.push(b'\n');
+
+        let size = std::fs::metadata(&self.path).map_or(0, |metadata| metadata.len());
+        if size > 0 && size + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        std::io::Write::write_all(&mut file, &line)
+    }
+
+    /// Shift `path.N` to `path.N+1` (dropping the oldest), then `path` to `path.1`
+    fn rotate(&self) -> std::io::Result<()> {
+        if self.max_files == 0 {
+            return std::fs::remove_file(&self.path);
+        }
+
+        let rotated = |n: usize| {
+            let mut path = self.path.clone().into_os_string();
+            path.push(format!(".{n}"));
+            std::path::PathBuf::from(path)
+        };
+
+        for n in (1..self.max_files).rev() {
+            match std::fs::rename(rotated(n), rotated(n + 1)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        std::fs::rename(&self.path, rotated(1))
+    }
+}
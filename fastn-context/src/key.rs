@@ -0,0 +1,87 @@
+/// A strongly-typed key for a value stored on a context, see [`crate::context_key!`]
+///
+/// Values are stored per key rather than per type, so two keys holding a `String`
+/// don't overwrite each other.
+pub trait ContextKey: 'static {
+    type Value: Send + Sync + 'static;
+}
+
+/// Storage slot for the value of key `K`
+struct KeyValue<K: ContextKey>(
+    std::sync::Arc<K::Value>,
+    std::marker::PhantomData<fn() -> K>,
+);
+
+impl crate::Context {
+    /// Attach a value for `key` to this context, replacing any previous value
+    pub fn set_key<K: ContextKey>(&self, _key: K, value: K::Value) {
+        self.set(KeyValue::<K>(
+            std::sync::Arc::new(value),
+            std::marker::PhantomData,
+        ));
+    }
+
+    /// Get the value for `key`, looking it up through the parent chain
+    pub fn get_key<K: ContextKey>(&self, _key: K) -> Option<std::sync::Arc<K::Value>> {
+        self.get::<KeyValue<K>>().map(|value| value.0.clone())
+    }
+
+    /// Remove the value for `key` from this context (parents are not affected)
+    pub fn remove_key<K: ContextKey>(&self, _key: K) -> Option<std::sync::Arc<K::Value>> {
+        self.remove::<KeyValue<K>>().map(|value| value.0.clone())
+    }
+}
+
+/// Define strongly-typed context keys
+///
+/// Each key is a unit struct implementing [`ContextKey`], with `get` / `set` /
+/// `remove` helpers taking the context to operate on.
+///
+/// ```rust
+/// fastn_context::context_key! {
+///     /// ID of the request being served
+///     pub REQUEST_ID: String;
+///     ATTEMPT: u32;
+/// }
+///
+/// let ctx = fastn_context::Context::new("request");
+/// REQUEST_ID.set(&ctx, "req-42".to_string());
+/// ATTEMPT.set(&ctx, 1);
+///
+/// let child = fastn_context::Context::linked("db", &[&ctx]);
+/// assert_eq!(REQUEST_ID.get(&child).as_deref(), Some(&"req-42".to_string()));
+/// assert_eq!(child.get_key(ATTEMPT).as_deref(), Some(&1));
+/// ```
+#[macro_export]
+macro_rules! context_key {
+    ($($(#[$meta:meta])* $vis:vis $name:ident : $value:ty);+ $(;)?) => {
+        $(
+            $(#[$meta])*
+            #[allow(non_camel_case_types)]
+            #[derive(Debug, Clone, Copy)]
+            $vis struct $name;
+
+            impl $crate::ContextKey for $name {
+                type Value = $value;
+            }
+
+            #[allow(dead_code)]
+            impl $name {
+                /// Attach a value for this key to `ctx`
+                $vis fn set(self, ctx: &$crate::Context, value: $value) {
+                    ctx.set_key(self, value)
+                }
+
+                /// Get the value for this key from `ctx` or its parents
+                $vis fn get(self, ctx: &$crate::Context) -> Option<std::sync::Arc<$value>> {
+                    ctx.get_key(self)
+                }
+
+                /// Remove the value for this key from `ctx`
+                $vis fn remove(self, ctx: &$crate::Context) -> Option<std::sync::Arc<$value>> {
+                    ctx.remove_key(self)
+                }
+            }
+        )+
+    };
+}
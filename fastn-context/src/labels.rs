@@ -0,0 +1,60 @@
+/// Labels attached to a context, stored as a typed value
+#[derive(Clone, Default)]
+struct Labels(std::collections::BTreeSet<String>);
+
+impl crate::Context {
+    /// Labels attached via [`crate::ContextBuilder::label`], sorted
+    pub fn labels(&self) -> Vec<String> {
+        self.get_local::<Labels>()
+            .map(|labels| labels.0.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// True if `label` was attached to this context itself
+    pub fn has_label(&self, label: &str) -> bool {
+        self.get_local::<Labels>()
+            .is_some_and(|labels| labels.0.contains(label))
+    }
+}
+
+impl crate::ContextBuilder {
+    /// Attach a label (e.g. `tenant:acme`) to the context
+    ///
+    /// Labels show up in status and let you find or cancel contexts across the tree,
+    /// see [`crate::find_by_label`] and [`crate::cancel_by_label`]. They are not
+    /// inherited by children.
+    pub fn label(self, label: &str) -> Self {
+        let label = label.to_string();
+        self.configure(move |context| {
+            let mut labels = context
+                .get_local::<Labels>()
+                .map(|labels| (*labels).clone())
+                .unwrap_or_default();
+            labels.0.insert(label);
+            context.set(labels);
+        })
+    }
+}
+
+/// Find live contexts carrying `label`, ordered by context ID (creation order)
+pub fn find_by_label(label: &str) -> Vec<std::sync::Arc<crate::Context>> {
+    crate::find("**")
+        .into_iter()
+        .filter(|context| context.has_label(label))
+        .collect()
+}
+
+/// Cancel live contexts carrying `label` (and so their subtrees)
+///
+/// E.g. `cancel_by_label("tenant:acme")` for tenant-scoped shutdown. Contexts that
+/// are already cancelled are skipped. Returns the paths of the contexts cancelled.
+pub fn cancel_by_label(label: &str) -> Vec<String> {
+    find_by_label(label)
+        .into_iter()
+        .filter(|context| !context.is_cancelled())
+        .map(|context| {
+            context.cancel();
+            context.path().to_string()
+        })
+        .collect()
+}
@@ -0,0 +1,76 @@
+/// `tower::Layer` giving every request its own child context
+///
+/// The child is inserted into the request extensions as `Arc<Context>` and is
+/// cancelled once the inner service's response future completes or is dropped
+/// (e.g. because the client disconnected).
+#[derive(Clone)]
+pub struct ContextLayer {
+    parent: std::sync::Arc<crate::Context>,
+    name: String,
+}
+
+impl ContextLayer {
+    /// Create request contexts as children of `parent`, named `request`
+    pub fn new(parent: std::sync::Arc<crate::Context>) -> Self {
+        ContextLayer {
+            parent,
+            name: "request".to_string(),
+        }
+    }
+
+    /// Name used for the per-request child contexts
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+}
+
+impl<S> tower_layer::Layer<S> for ContextLayer {
+    type Service = ContextService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ContextService {
+            inner,
+            parent: self.parent.clone(),
+            name: self.name.clone(),
+        }
+    }
+}
+
+/// Service produced by [`ContextLayer`]
+#[derive(Clone)]
+pub struct ContextService<S> {
+    inner: S,
+    parent: std::sync::Arc<crate::Context>,
+    name: String,
+}
+
+impl<S, B> tower_service::Service<http::Request<B>> for ContextService<S>
+where
+    S: tower_service::Service<http::Request<B>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future =
+        std::pin::Pin<Box<dyn std::future::Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: http::Request<B>) -> Self::Future {
+        let context = self.parent.child(&self.name).build();
+        let guard = context.drop_guard();
+        request.extensions_mut().insert(context.clone());
+
+        let response = crate::with_current(context, self.inner.call(request));
+        Box::pin(async move {
+            let _guard = guard;
+            response.await
+        })
+    }
+}
@@ -19,73 +19,60 @@
 //!
 //! ## Quick Start
 //!
-//! ### Basic Usage
+//! ### Using the `#[main]` Macro
 //!
 //! ```rust
-//! use fastn_context::Context;
-//! use tokio::time::{sleep, Duration};
-//!
-//! #[tokio::main]
-//! async fn main() {
-//!     let ctx = Context::builder("my-app").build();
-//!     
-//!     // Spawn a task with context
-//!     let child_ctx = ctx.child("worker");
-//!     tokio::spawn(async move {
-//!         // Your async work here
-//!         sleep(Duration::from_millis(100)).await;
-//!         println!("Work completed");
-//!     });
-//!
-//!     // Monitor status
-//!     let status = fastn_context::status().await;
-//!     println!("Active contexts: {}", status.contexts.len());
+//! #[fastn_context::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     // Global context is automatically available
+//!     let ctx = fastn_context::global();
+//!     println!("App: {}", ctx.name);
+//!     Ok(())
 //! }
 //! ```
 //!
-//! ### Using the `#[main]` Macro
-//!
-//! For applications that need automatic context setup:
+//! ### Spawning Child Contexts
 //!
 //! ```rust
-//! use fastn_context::main;
-//!
-//! #[main]
+//! #[tokio::main]
 //! async fn main() {
-//!     // Global context is automatically available
-//!     let ctx = fastn_context::global().await;
-//!     println!("App: {}", ctx.name());
+//!     let ctx = fastn_context::global();
+//!
+//!     ctx.spawn_child("worker", |task_ctx| async move {
+//!         tokio::select! {
+//!             _ = task_ctx.cancelled() => println!("Task was cancelled"),
+//!             _ = tokio::time::sleep(std::time::Duration::from_millis(100)) => {
+//!                 println!("Work completed");
+//!             }
+//!         }
+//!     })
+//!     .await
+//!     .unwrap();
+//!
+//!     // Monitor status
+//!     println!("{}", fastn_context::status());
 //! }
 //! ```
 //!
 //! ### Cancellation and Shutdown
 //!
 //! ```rust
-//! use fastn_context::Context;
-//! use tokio::select;
-//! use tokio::time::{sleep, Duration};
-//!
 //! #[tokio::main]
 //! async fn main() {
-//!     let ctx = Context::builder("my-app").build();
-//!     
-//!     let child_ctx = ctx.child("worker");
-//!     let token = child_ctx.cancellation_token();
-//!     
-//!     tokio::spawn(async move {
-//!         select! {
-//!             _ = token.cancelled() => {
-//!                 println!("Task was cancelled");
-//!             }
-//!             _ = sleep(Duration::from_secs(10)) => {
+//!     let ctx = fastn_context::Context::new("my-app");
+//!
+//!     ctx.spawn_child("worker", |task_ctx| async move {
+//!         tokio::select! {
+//!             _ = task_ctx.cancelled() => println!("Task was cancelled"),
+//!             _ = tokio::time::sleep(std::time::Duration::from_secs(10)) => {
 //!                 println!("Task completed normally");
 //!             }
 //!         }
 //!     });
-//!     
-//!     // Cancel after 1 second
-//!     sleep(Duration::from_secs(1)).await;
-//!     child_ctx.cancel();
+//!
+//!     // Cancel the whole tree, then force-abort anything still running after 1s
+//!     let report = ctx.shutdown(std::time::Duration::from_secs(1)).await;
+//!     assert!(report.is_clean());
 //! }
 //! ```
 //!
@@ -97,6 +84,30 @@
 //! - [`ContextStatus`]: Status information and monitoring capabilities  
 //! - [`Status`]: Global status snapshots of the entire context tree
 //!
+//! ## Feature Flags
+//!
+//! - `tokio` (default): spawning, deadlines, shutdown, signals and the rest of the
+//!   runtime support on tokio. Without it the context tree, cancellation, status and
+//!   [`Context::spawn_with`] on any executor (see [`Spawner`]) remain
+//! - `serde`: `Serialize`/`Deserialize` for [`Status`] and [`ContextStatus`]
+//! - `prometheus`: `Status::to_prometheus()` in Prometheus text exposition format
+//! - `tower`: `ContextLayer` middleware creating a cancellable child context per request
+//! - `axum`: `Ctx` extractor for the request context (enables `tower`)
+//! - `tracing`: a `tracing::Span` per context, entered by tasks spawned through it
+//! - `opentelemetry`: link contexts to OpenTelemetry span contexts and baggage
+//! - `cron`: `Context::spawn_cron()` for jobs scheduled with cron expressions
+//! - `control`: `control::serve()` Unix socket endpoint to query status and cancel contexts (enables `serde`)
+//! - `http`: `http::status_handler()` serving the status tree at `/status`, `/status/tree` and as Server-Sent Events at `/status/stream` (enables `serde`)
+//! - `dashboard`: live HTML view of the tree at `/status/dashboard` (enables `http`)
+//! - `statsd`: `statsd::StatsdExporter` pushing context metrics to statsd / DogStatsD
+//! - `otlp`: `otlp::OtlpExporter` pushing context metrics to an OpenTelemetry collector (OTLP/HTTP JSON)
+//! - `systemd`: `systemd::install()` notifying systemd of readiness and shutdown and feeding its watchdog (unix)
+//! - `crash-dump`: `set_crash_dump_path()` to write the final status tree as JSON on fatal errors and forced shutdowns (enables `serde`)
+//! - `journal`: `journal::StatusJournal` appending periodic status snapshots to a rotated JSONL file (enables `serde`)
+//! - `ffi`: `ffi` module with a C ABI to embed the context tree in native hosts and plugins (enables `serde`)
+//! - `client`: `ClientLayer` middleware aborting outgoing HTTP requests when their context cancels and adding propagation headers (enables `tower`)
+//! - `tonic`: `grpc` module with client and server layers propagating deadlines and baggage over gRPC metadata, for use with tonic (enables `client`)
+//!
 //! ## Integration with fastn Applications
 //!
 //! This crate was extracted from the [fastn](https://github.com/fastn-stack/fastn) web framework
@@ -107,13 +118,170 @@
 #![deny(unused_crate_dependencies)]
 
 use tokio as _; // used by main macro
+#[cfg(test)]
+use tokio_test as _;
 use tokio_util as _; // used for cancellation tokens
 
+mod actor;
+mod cancel_reason;
+mod child_names;
+#[cfg(feature = "tokio")]
+mod cleanup;
+#[cfg(feature = "client")]
+mod client;
+mod consumer;
 mod context;
+#[cfg(all(unix, feature = "control"))]
+pub mod control;
+#[cfg(feature = "crash-dump")]
+mod crash_dump;
+mod current;
+mod dot;
+mod drop_guard;
+mod entry;
+mod events;
+#[cfg(feature = "axum")]
+mod extract;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod future_ext;
+mod glob;
+#[cfg(feature = "tonic")]
+pub mod grpc;
+#[cfg(feature = "tokio")]
+mod guard;
+mod health;
+mod heartbeat;
+mod histogram;
+#[cfg(feature = "http")]
+pub mod http;
+mod instrument;
+mod io;
+#[cfg(feature = "journal")]
+pub mod journal;
+mod key;
+mod labels;
+#[cfg(feature = "tower")]
+mod layer;
+mod limit;
+mod metrics;
+mod observer;
+#[cfg(feature = "opentelemetry")]
+mod otel;
+#[cfg(feature = "otlp")]
+pub mod otlp;
+mod owner;
+mod panic;
+mod process;
+mod progress;
+#[cfg(feature = "prometheus")]
+mod prometheus;
+mod propagation;
+mod quota;
+mod rate_limit;
+mod readiness;
+mod registry;
+mod rollup;
+mod runtime;
+mod schedule;
+#[cfg(feature = "cron")]
+mod scheduler;
+mod scope;
+mod shutdown;
+#[cfg(feature = "tokio")]
+mod signal;
+mod spawner;
+mod state;
+#[cfg(feature = "statsd")]
+pub mod statsd;
 mod status;
+mod status_stream;
+#[cfg(feature = "tokio")]
+mod supervisor;
+mod sync;
+#[cfg(all(unix, feature = "systemd"))]
+pub mod systemd;
+mod task;
+#[cfg(feature = "tokio")]
+mod task_group;
+#[cfg(feature = "tokio")]
+mod termination;
+mod testing;
+mod type_map;
+
+pub use actor::{Address, MailboxPolicy, MailboxStatus};
+pub use cancel_reason::CancelReason;
+pub use child_names::{ChildError, ChildNamePolicy};
+#[cfg(feature = "client")]
+pub use client::{ClientError, ClientLayer, ClientService};
+pub use consumer::ConsumerStatus;
+pub use context::{Context, ContextBuilder, global, init_global};
+#[cfg(feature = "crash-dump")]
+pub use crash_dump::{set_crash_dump_path, write_crash_dump};
+pub use current::with_current;
+pub use drop_guard::ContextDropGuard;
+pub use events::ContextEvent;
+#[cfg(feature = "axum")]
+pub use extract::Ctx;
+pub use future_ext::{Cancelled, ContextFutureExt, TimeoutResult, WithContext};
+#[cfg(feature = "tokio")]
+pub use guard::ContextGuard;
+pub use health::{Health, HealthCause, HealthReport, HealthStatus, health};
+pub use heartbeat::{DEFAULT_STALE_AFTER, HeartbeatStatus};
+pub use histogram::{LATENCY_BUCKETS, LatencyHistogram, lifetimes};
+pub use io::{CancellableReader, CancellableWriter, cancelled_error};
+pub use key::ContextKey;
+pub use labels::{cancel_by_label, find_by_label};
+#[cfg(feature = "tower")]
+pub use layer::{ContextLayer, ContextService};
+pub use limit::{ConcurrencyLimitReached, ConcurrencyStatus};
+pub use metrics::{Counter, Gauge};
+pub use observer::{ContextObserver, set_observer};
+pub use owner::{ContextHandle, ContextOwner};
+pub use panic::{PanicPolicy, install_panic_hook};
+pub use process::{DEFAULT_PROCESS_GRACE, ProcessStatus};
+pub use progress::Progress;
+pub use propagation::{BAGGAGE_HEADER, RemoteParent, TRACEPARENT_HEADER, TraceParent};
+pub use quota::QuotaExceeded;
+pub use rate_limit::RateLimitStatus;
+pub use readiness::{ReadinessStatus, all_ready, is_ready, not_ready_contexts};
+pub use registry::{cancel_matching, find, find_by_id, leaked};
+pub use rollup::RollUpStatus;
+pub use runtime::RuntimeStatus;
+pub use schedule::ScheduleStatus;
+#[cfg(feature = "cron")]
+pub use scheduler::CronError;
+#[cfg(feature = "tokio")]
+pub use shutdown::shutdown;
+pub use shutdown::{AbortedContext, HungTask, PanickedContext, ShutdownReport};
+#[cfg(all(unix, feature = "tokio"))]
+pub use signal::install_status_dump_handler;
+#[cfg(feature = "tokio")]
+pub use signal::{
+    SignalPolicy, install_signal_handlers, install_signal_handlers_with, request_stop,
+};
+pub use spawner::{Spawner, TaskError, TaskHandle};
+pub use state::{ContextError, ContextState};
+pub use status::{ContextStatus, Status, StatusOptions, status, status_with, status_with_latest};
+#[cfg(feature = "tokio")]
+pub use status_stream::status_stream;
+#[cfg(feature = "tokio")]
+pub use supervisor::{RestartPolicy, Supervisor};
+pub use sync::{CancelFlag, SyncTask};
+#[cfg(feature = "tokio")]
+pub use task_group::TaskGroup;
+#[cfg(feature = "tokio")]
+pub use termination::Termination;
 
-pub use context::{Context, ContextBuilder, global};
-pub use status::{ContextStatus, Status, status, status_with_latest};
+// Re-export main, test and instrument_context macros
+pub use fastn_context_macros::{instrument_context, main, test};
 
-// Re-export main macro
-pub use fastn_context_macros::main;
+#[doc(hidden)]
+pub use entry::MainOutput as __MainOutput;
+#[cfg(feature = "tokio")]
+#[doc(hidden)]
+pub use entry::run_main as __run_main;
+#[doc(hidden)]
+pub use instrument::instrument as __instrument;
+#[doc(hidden)]
+pub use testing::run_test as __run_test;
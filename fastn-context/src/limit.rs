@@ -0,0 +1,121 @@
+/// Concurrency limit usage of a context, see [`crate::ContextBuilder::max_concurrent_tasks`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConcurrencyStatus {
+    pub limit: usize,
+    /// Tasks currently running
+    pub in_flight: usize,
+    /// Tasks spawned but waiting for a free slot
+    pub queued: usize,
+}
+
+/// Returned by [`crate::Context::try_spawn`] when every slot is taken
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConcurrencyLimitReached {
+    pub limit: usize,
+}
+
+impl std::fmt::Display for ConcurrencyLimitReached {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "concurrency limit of {} tasks reached", self.limit)
+    }
+}
+
+impl std::error::Error for ConcurrencyLimitReached {}
+
+/// Semaphore limiting a context's tasks, stored as a typed value on the context
+pub(crate) struct ConcurrencyLimit {
+    limit: usize,
+    semaphore: std::sync::Arc<tokio::sync::Semaphore>,
+    queued: std::sync::atomic::AtomicUsize,
+}
+
+impl ConcurrencyLimit {
+    /// Wait for a free slot, counted as queued meanwhile
+    pub(crate) async fn acquire(&self) -> Option<tokio::sync::OwnedSemaphorePermit> {
+        let _queued = Queued::new(&self.queued);
+        self.semaphore.clone().acquire_owned().await.ok()
+    }
+
+    pub(crate) fn status(&self) -> ConcurrencyStatus {
+        ConcurrencyStatus {
+            limit: self.limit,
+            in_flight: self.limit - self.semaphore.available_permits(),
+            queued: self.queued.load(std::sync::atomic::Ordering::Relaxed),
+        }
+    }
+}
+
+/// Counts a task as queued until dropped, also when its future is dropped while
+/// waiting (e.g. aborted by `shutdown()`)
+struct Queued<'a>(&'a std::sync::atomic::AtomicUsize);
+
+impl<'a> Queued<'a> {
+    fn new(queued: &'a std::sync::atomic::AtomicUsize) -> Self {
+        queued.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        crate::status_stream::tree_changed();
+        Queued(queued)
+    }
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, std::sync::atomic::Ordering::Relaxed);
+        crate::status_stream::tree_changed();
+    }
+}
+
+impl crate::Context {
+    /// Like [`crate::Context::spawn`], but fails instead of queueing when the
+    /// concurrency limit is reached
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    pub fn try_spawn<F>(
+        &self,
+        task: F,
+    ) -> Result<tokio::task::JoinHandle<F::Output>, ConcurrencyLimitReached>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let Some(limit) = self.get_local::<ConcurrencyLimit>() else {
+            return Ok(self.spawn_unlimited(task));
+        };
+
+        let permit = limit
+            .semaphore
+            .clone()
+            .try_acquire_owned()
+            .map_err(|_| ConcurrencyLimitReached { limit: limit.limit })?;
+
+        Ok(self.spawn_unlimited(async move {
+            let _permit = permit;
+            task.await
+        }))
+    }
+}
+
+impl crate::ContextBuilder {
+    /// Run at most `limit` tasks spawned through this context at a time
+    ///
+    /// Further `spawn()` calls queue until a running task finishes, `try_spawn()`
+    /// fails instead. Tasks of child contexts and the task started by this builder's
+    /// `spawn()` are not counted.
+    ///
+    /// # Panics
+    ///
+    /// If `limit` is 0: no task could ever start.
+    pub fn max_concurrent_tasks(self, limit: usize) -> Self {
+        assert!(
+            limit > 0,
+            "max_concurrent_tasks() needs a limit of at least 1"
+        );
+        self.configure(move |context| {
+            context.set(ConcurrencyLimit {
+                limit,
+                semaphore: std::sync::Arc::new(tokio::sync::Semaphore::new(limit)),
+                queued: Default::default(),
+            })
+        })
+    }
+}
@@ -0,0 +1,92 @@
+/// Named counters and gauges attached to a context
+#[derive(Default)]
+pub(crate) struct Metrics {
+    counters: std::collections::HashMap<String, Counter>,
+    gauges: std::collections::HashMap<String, Gauge>,
+}
+
+impl Metrics {
+    /// Get or create the counter with this name
+    pub(crate) fn counter(&mut self, name: &str) -> Counter {
+        self.counters.entry(name.to_string()).or_default().clone()
+    }
+
+    /// Get or create the gauge with this name
+    pub(crate) fn gauge(&mut self, name: &str) -> Gauge {
+        self.gauges.entry(name.to_string()).or_default().clone()
+    }
+
+    /// Current value of every counter
+    pub(crate) fn counter_values(&self) -> std::collections::BTreeMap<String, u64> {
+        self.counters
+            .iter()
+            .map(|(name, counter)| (name.clone(), counter.get()))
+            .collect()
+    }
+
+    /// Current value of every gauge
+    pub(crate) fn gauge_values(&self) -> std::collections::BTreeMap<String, i64> {
+        self.gauges
+            .iter()
+            .map(|(name, gauge)| (name.clone(), gauge.get()))
+            .collect()
+    }
+}
+
+/// Monotonically increasing counter (cheap to clone, clones share the value)
+#[derive(Debug, Clone, Default)]
+pub struct Counter {
+    value: std::sync::Arc<std::sync::atomic::AtomicU64>,
+}
+
+impl Counter {
+    /// Increment by one
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    /// Increment by `n`
+    pub fn add(&self, n: u64) {
+        self.value
+            .fetch_add(n, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Current value
+    pub fn get(&self) -> u64 {
+        self.value.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+/// Value that can go up and down (cheap to clone, clones share the value)
+#[derive(Debug, Clone, Default)]
+pub struct Gauge {
+    value: std::sync::Arc<std::sync::atomic::AtomicI64>,
+}
+
+impl Gauge {
+    /// Set to `n`
+    pub fn set(&self, n: i64) {
+        self.value.store(n, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Increment by one
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    /// Decrement by one
+    pub fn dec(&self) {
+        self.add(-1);
+    }
+
+    /// Add `n` (may be negative)
+    pub fn add(&self, n: i64) {
+        self.value
+            .fetch_add(n, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Current value
+    pub fn get(&self) -> i64 {
+        self.value.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
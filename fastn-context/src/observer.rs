@@ -0,0 +1,42 @@
+/// Receives lifecycle events of every context in the process, see [`set_observer`]
+///
+/// All methods default to doing nothing. They are called synchronously from the
+/// code that triggered the event, so they should be cheap and must not block.
+pub trait ContextObserver: Send + Sync + 'static {
+    /// A context was created (root or child)
+    fn on_created(&self, _context: &crate::Context) {}
+
+    /// A task was spawned under a freshly created child context
+    fn on_child_spawned(&self, _parent: &crate::Context, _child: &crate::Context) {}
+
+    /// `context` was cancelled (not called for descendants cancelled along with it)
+    fn on_cancelled(&self, _context: &crate::Context, _reason: &crate::CancelReason) {}
+
+    /// `complete()` or `fail()` recorded the outcome of `context`
+    fn on_completed(&self, _context: &crate::Context, _state: &crate::ContextState) {}
+}
+
+/// Observer installed by `set_observer()`
+static OBSERVER: std::sync::RwLock<Option<std::sync::Arc<dyn ContextObserver>>> =
+    std::sync::RwLock::new(None);
+
+/// Install the process-wide context observer, replacing any previous one
+///
+/// Lets logging, metrics and audit systems follow the whole context tree without
+/// wrapping every call site.
+pub fn set_observer(observer: impl ContextObserver) {
+    if let Ok(mut current) = OBSERVER.write() {
+        *current = Some(std::sync::Arc::new(observer));
+    }
+}
+
+/// Call the installed observer, if any
+pub(crate) fn notify(event: impl FnOnce(&dyn ContextObserver)) {
+    let observer = match OBSERVER.read() {
+        Ok(observer) => observer.clone(),
+        Err(_) => return,
+    };
+    if let Some(observer) = observer {
+        event(observer.as_ref());
+    }
+}
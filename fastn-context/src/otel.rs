@@ -0,0 +1,112 @@
+//! Bridging between fastn contexts and OpenTelemetry contexts
+
+/// Remote/parent span context linked to a fastn context (stored as a typed value)
+struct OtelSpanContext(opentelemetry::trace::SpanContext);
+
+/// Baggage entries carried by a fastn context (stored as a typed value, since
+/// `Baggage` itself is not `Clone`)
+struct OtelBaggage(
+    Vec<(
+        opentelemetry::Key,
+        (
+            opentelemetry::StringValue,
+            opentelemetry::baggage::BaggageMetadata,
+        ),
+    )>,
+);
+
+impl crate::Context {
+    /// Link this context (and its descendants) to an OpenTelemetry context
+    ///
+    /// The active span context and baggage of `otel_cx` are stored on this context
+    /// and inherited by children, so trace IDs flow through the context tree.
+    pub fn set_otel_context(&self, otel_cx: &opentelemetry::Context) {
+        use opentelemetry::baggage::BaggageExt;
+        use opentelemetry::trace::TraceContextExt;
+
+        let span_context = otel_cx.span().span_context().clone();
+        if span_context.is_valid() {
+            self.set(OtelSpanContext(span_context));
+        }
+
+        let baggage = otel_cx.baggage();
+        if !baggage.is_empty() {
+            self.set(OtelBaggage(
+                baggage
+                    .iter()
+                    .map(|(key, entry)| (key.clone(), entry.clone()))
+                    .collect(),
+            ));
+        }
+    }
+
+    /// OpenTelemetry span context linked to this context or its nearest ancestor
+    pub fn otel_span_context(&self) -> Option<opentelemetry::trace::SpanContext> {
+        self.get::<OtelSpanContext>().map(|span| span.0.clone())
+    }
+
+    /// OpenTelemetry baggage linked to this context or its nearest ancestor
+    pub fn otel_baggage(&self) -> Option<opentelemetry::baggage::Baggage> {
+        self.get::<OtelBaggage>()
+            .map(|baggage| baggage.0.iter().cloned().collect())
+    }
+
+    /// Build an OpenTelemetry context carrying this context's span context and baggage
+    ///
+    /// Use it as parent when starting OTEL spans for work done under this context.
+    pub fn to_otel(&self) -> opentelemetry::Context {
+        use opentelemetry::baggage::BaggageExt;
+        use opentelemetry::trace::TraceContextExt;
+
+        let mut otel_cx = opentelemetry::Context::new();
+        if let Some(span_context) = self.otel_span_context() {
+            otel_cx = otel_cx.with_remote_span_context(span_context);
+        }
+        if let Some(baggage) = self.otel_baggage() {
+            otel_cx = otel_cx.with_baggage(baggage);
+        }
+        otel_cx
+    }
+
+    /// Annotate an OpenTelemetry span with this context's identity and cancellation state
+    pub fn record_otel_status<S: opentelemetry::trace::Span>(&self, span: &mut S) {
+        span.set_attribute(opentelemetry::KeyValue::new(
+            "fastn.context.path",
+            self.path().to_string(),
+        ));
+        span.set_attribute(opentelemetry::KeyValue::new(
+            "fastn.context.id",
+            self.id() as i64,
+        ));
+        span.set_attribute(opentelemetry::KeyValue::new(
+            "fastn.context.cancelled",
+            self.is_cancelled(),
+        ));
+
+        if let Some(reason) = self.cancel_reason() {
+            span.add_event(
+                "fastn.context.cancelled",
+                vec![opentelemetry::KeyValue::new(
+                    "fastn.context.cancel_reason",
+                    reason.to_string(),
+                )],
+            );
+        }
+    }
+}
+
+impl crate::ContextBuilder {
+    /// Link the child context to an OpenTelemetry context, see
+    /// [`crate::Context::set_otel_context`]
+    pub fn otel_parent(self, otel_cx: &opentelemetry::Context) -> Self {
+        let otel_cx = otel_cx.clone();
+        self.configure(move |context| context.set_otel_context(&otel_cx))
+    }
+}
+
+/// Trace ID (hex) of the span context linked to `context`, for status snapshots
+pub(crate) fn trace_id(context: &crate::Context) -> Option<String> {
+    context
+        .otel_span_context()
+        .map(|span_context| span_context.trace_id().to_string())
+}
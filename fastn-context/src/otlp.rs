@@ -0,0 +1,316 @@
+//! OTLP exporter for context metrics
+//!
+//! Every interval the exporter converts a status snapshot of its context's subtree
+//! into OTLP metrics and posts them, JSON encoded, to the `/v1/metrics` endpoint of
+//! an OpenTelemetry collector over plain HTTP:
+//!
+//! - `{prefix}.context.tasks` gauge of live tasks per context
+//! - `{prefix}.{name}` cumulative sum / gauge for each user counter / gauge
+//! - `{prefix}.context.lifetime` histogram (ms) of finished contexts by name
+//!
+//! Per-context points carry `context.path` and `context.name` attributes.
+
+/// Periodically pushes context metrics to an OpenTelemetry collector
+///
+/// ```rust,no_run
+/// #[fastn_context::main]
+/// async fn main() {
+///     fastn_context::otlp::OtlpExporter::new("http://localhost:4318")
+///         .service_name("my-app")
+///         .interval(std::time::Duration::from_secs(15))
+///         .spawn(&fastn_context::global());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OtlpExporter {
+    endpoint: String,
+    service_name: String,
+    prefix: String,
+    interval: std::time::Duration,
+    headers: Vec<(String, String)>,
+}
+
+impl OtlpExporter {
+    /// Export to the collector at `endpoint` (`http://host:port`, optionally with a
+    /// base path), every 10s
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        OtlpExporter {
+            endpoint: endpoint.into(),
+            service_name: "fastn".to_string(),
+            prefix: "fastn".to_string(),
+            interval: std::time::Duration::from_secs(10),
+            headers: Vec::new(),
+        }
+    }
+
+    /// `service.name` resource attribute, `fastn` by default
+    pub fn service_name(mut self, name: &str) -> Self {
+        self.service_name = name.to_string();
+        self
+    }
+
+    /// Prefix of every metric name
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// How often metrics are pushed
+    pub fn interval(mut self, interval: std::time::Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Extra HTTP header sent with every request (e.g. an API key)
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Export the subtree of `context` as a task of `context`, until it is cancelled
+    ///
+    /// Fails right away if the endpoint is not a valid `http://` URL. Failed pushes
+    /// are reported on stderr and retried at the next interval.
+    pub fn spawn(self, context: &crate::Context) -> tokio::task::JoinHandle<std::io::Result<()>> {
+        let exporter = context.clone();
+        context.spawn_named("otlp exporter", async move { self.run(&exporter).await })
+    }
+
+    async fn run(self, context: &crate::Context) -> std::io::Result<()> {
+        let endpoint = Endpoint::parse(&self.endpoint)?;
+        let started = std::time::SystemTime::now();
+
+        let mut interval = tokio::time::interval(self.interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            tokio::select! {
+                _ = context.cancelled() => return Ok(()),
+                _ = interval.tick() => {}
+            }
+
+            let body = self.metrics(&context.status(), started).to_string();
+            let push = endpoint.post("/v1/metrics", &self.headers, &body);
+            match crate::ContextFutureExt::with_context(push, context).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => eprintln!("failed to push metrics to {}: {e}", self.endpoint),
+                Err(_cancelled) => return Ok(()),
+            }
+        }
+    }
+
+    /// `ExportMetricsServiceRequest` in the OTLP JSON encoding
+    fn metrics(
+        &self,
+        root: &crate::ContextStatus,
+        started: std::time::SystemTime,
+    ) -> serde_json::Value {
+        let now = unix_nanos(std::time::SystemTime::now());
+        let mut points = Points::default();
+        collect(root, &now, &mut points);
+
+        let mut metrics = vec![serde_json::json!({
+            "name": format!("{}.context.tasks", self.prefix),
+            "unit": "{task}",
+            "gauge": { "dataPoints": points.tasks },
+        })];
+        for (name, data_points) in points.sums {
+            metrics.push(serde_json::json!({
+                "name": format!("{}.{name}", self.prefix),
+                "sum": {
+                    "aggregationTemporality": CUMULATIVE,
+                    "isMonotonic": true,
+                    "dataPoints": data_points,
+                },
+            }));
+        }
+        for (name, data_points) in points.gauges {
+            metrics.push(serde_json::json!({
+                "name": format!("{}.{name}", self.prefix),
+                "gauge": { "dataPoints": data_points },
+            }));
+        }
+
+        let lifetimes = crate::lifetimes();
+        if !lifetimes.is_empty() {
+            let bounds: Vec<f64> = crate::LATENCY_BUCKETS
+                .iter()
+                .map(|bound| bound.as_secs_f64() * 1000.0)
+                .collect();
+            let data_points: Vec<_> = lifetimes
+                .into_iter()
+                .map(|(name, histogram)| {
+                    serde_json::json!({
+                        "attributes": [attribute("context.name", &name)],
+                        "startTimeUnixNano": unix_nanos(started),
+                        "timeUnixNano": now,
+                        "count": histogram.count.to_string(),
+                        "sum": histogram.sum.as_secs_f64() * 1000.0,
+                        "max": histogram.max.as_secs_f64() * 1000.0,
+                        "bucketCounts": histogram.buckets.iter().map(u64::to_string).collect::<Vec<_>>(),
+                        "explicitBounds": bounds,
+                    })
+                })
+                .collect();
+            metrics.push(serde_json::json!({
+                "name": format!("{}.context.lifetime", self.prefix),
+                "unit": "ms",
+                "histogram": {
+                    "aggregationTemporality": CUMULATIVE,
+                    "dataPoints": data_points,
+                },
+            }));
+        }
+
+        serde_json::json!({
+            "resourceMetrics": [{
+                "resource": { "attributes": [attribute("service.name", &self.service_name)] },
+                "scopeMetrics": [{
+                    "scope": { "name": "fastn-context", "version": env!("CARGO_PKG_VERSION") },
+                    "metrics": metrics,
+                }],
+            }],
+        })
+    }
+}
+
+/// `AGGREGATION_TEMPORALITY_CUMULATIVE`
+const CUMULATIVE: u8 = 2;
+
+/// Data points collected from the tree, by metric
+#[derive(Default)]
+struct Points {
+    tasks: Vec<serde_json::Value>,
+    sums: std::collections::BTreeMap<String, Vec<serde_json::Value>>,
+    gauges: std::collections::BTreeMap<String, Vec<serde_json::Value>>,
+}
+
+fn collect(ctx: &crate::ContextStatus, now: &str, points: &mut Points) {
+    let attributes = [
+        attribute("context.path", &ctx.path),
+        attribute("context.name", &ctx.name),
+    ];
+
+    points.tasks.push(serde_json::json!({
+        "attributes": attributes,
+        "timeUnixNano": now,
+        "asInt": ctx.task_count.to_string(),
+    }));
+    for (name, value) in &ctx.counters {
+        points
+            .sums
+            .entry(name.clone())
+            .or_default()
+            .push(serde_json::json!({
+                "attributes": attributes,
+                "startTimeUnixNano": unix_nanos(ctx.created_at),
+                "timeUnixNano": now,
+                "asInt": value.to_string(),
+            }));
+    }
+    for (name, value) in &ctx.gauges {
+        points
+            .gauges
+            .entry(name.clone())
+            .or_default()
+            .push(serde_json::json!({
+                "attributes": attributes,
+                "timeUnixNano": now,
+                "asInt": value.to_string(),
+            }));
+    }
+
+    for child in &ctx.children {
+        collect(child, now, points);
+    }
+}
+
+fn attribute(key: &str, value: &str) -> serde_json::Value {
+    serde_json::json!({ "key": key, "value": { "stringValue": value } })
+}
+
+/// Nanoseconds since the Unix epoch, as a string (OTLP JSON encodes 64-bit integers
+/// as strings)
+fn unix_nanos(time: std::time::SystemTime) -> String {
+    time.duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string()
+}
+
+/// `http://host[:port][/base]` collector endpoint
+#[derive(Debug)]
+struct Endpoint {
+    host: String,
+    port: u16,
+    base: String,
+}
+
+impl Endpoint {
+    fn parse(endpoint: &str) -> std::io::Result<Self> {
+        let invalid = |message: &str| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("invalid OTLP endpoint {endpoint:?}: {message}"),
+            )
+        };
+
+        let rest = endpoint
+            .strip_prefix("http://")
+            .ok_or_else(|| invalid("only http:// is supported"))?;
+        let (authority, base) = match rest.find('/') {
+            Some(slash) => (&rest[..slash], rest[slash..].trim_end_matches('/')),
+            None => (rest, ""),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().map_err(|_| invalid("bad port"))?),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(invalid("missing host"));
+        }
+
+        Ok(Endpoint {
+            host: host.to_string(),
+            port,
+            base: base.to_string(),
+        })
+    }
+
+    /// POST a JSON `body` to `path`, failing unless the response status is 2xx
+    async fn post(
+        &self,
+        path: &str,
+        headers: &[(String, String)],
+        body: &str,
+    ) -> std::io::Result<()> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let mut stream = tokio::net::TcpStream::connect((self.host.as_str(), self.port)).await?;
+
+        let mut request = format!(
+            "POST {}{path} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.base,
+            self.host,
+            self.port,
+            body.len()
+        );
+        for (name, value) in headers {
+            request.push_str(&format!("{name}: {value}\r\n"));
+        }
+        request.push_str("\r\n");
+        request.push_str(body);
+        stream.write_all(request.as_bytes()).await?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await?;
+        let response = String::from_utf8_lossy(&response);
+        let status_line = response.lines().next().unwrap_or_default();
+        match status_line.split(' ').nth(1) {
+            Some(status) if status.starts_with('2') => Ok(()),
+            _ => Err(std::io::Error::other(format!(
+                "collector responded {status_line:?}"
+            ))),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+/// Sole owner of a context: cancels it (running its `on_cancel()` cleanup) when
+/// dropped
+///
+/// Makes ownership explicit: the owner decides the context's lifetime, everyone
+/// else gets a [`ContextHandle`], which does not keep the context alive. Derefs to
+/// [`crate::Context`].
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// let app = fastn_context::Context::new("app");
+/// let session = app.child("session").owned();
+/// let handle = session.handle();
+///
+/// handle.spawn(async { /* work */ });
+/// drop(session);
+/// assert!(handle.is_cancelled());
+/// # }
+/// ```
+#[must_use = "the context is cancelled as soon as its owner is dropped"]
+pub struct ContextOwner {
+    context: std::sync::Arc<crate::Context>,
+}
+
+impl ContextOwner {
+    /// Create a root context owned by the caller
+    pub fn new(name: &str) -> Self {
+        ContextOwner {
+            context: crate::Context::new(name),
+        }
+    }
+
+    /// Handle for code that uses the context without owning it
+    pub fn handle(&self) -> ContextHandle {
+        ContextHandle {
+            context: std::sync::Arc::downgrade(&self.context),
+            token: self.context.cancellation_token(),
+        }
+    }
+}
+
+impl std::ops::Deref for ContextOwner {
+    type Target = crate::Context;
+
+    fn deref(&self) -> &crate::Context {
+        &self.context
+    }
+}
+
+impl Drop for ContextOwner {
+    fn drop(&mut self) {
+        self.context
+            .cancel_with_reason(crate::CancelReason::Dropped);
+    }
+}
+
+impl crate::ContextBuilder {
+    /// Finish configuring and return the child context with its owner, see
+    /// [`ContextOwner`]
+    pub fn owned(self) -> ContextOwner {
+        ContextOwner {
+            context: self.build(),
+        }
+    }
+}
+
+/// Cheap clonable handle to a context, that doesn't keep it alive
+///
+/// Can observe the context and spawn onto it as long as it is still in the tree.
+/// Holding handles (e.g. in long-lived structs) never leaks a subtree: once the
+/// owner and the context's tasks are gone, the context is dropped and
+/// [`ContextHandle::get`] returns None.
+#[derive(Clone)]
+pub struct ContextHandle {
+    context: std::sync::Weak<crate::Context>,
+    token: tokio_util::sync::CancellationToken,
+}
+
+impl ContextHandle {
+    /// The context, None once it was dropped from the tree
+    pub fn get(&self) -> Option<std::sync::Arc<crate::Context>> {
+        self.context.upgrade()
+    }
+
+    /// Check if the context is cancelled (works after it was dropped too)
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Wait for the context to be cancelled
+    pub fn cancelled(&self) -> tokio_util::sync::WaitForCancellationFuture<'_> {
+        self.token.cancelled()
+    }
+
+    /// Spawn a task tracked by the context, None if it was dropped
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    pub fn spawn<F>(&self, task: F) -> Option<tokio::task::JoinHandle<F::Output>>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.get().map(|context| context.spawn(task))
+    }
+
+    /// Status of the context and its subtree, None if it was dropped
+    pub fn status(&self) -> Option<crate::ContextStatus> {
+        self.get().map(|context| context.status())
+    }
+}
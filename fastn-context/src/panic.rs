@@ -0,0 +1,213 @@
+/// What happens to a context when one of its tasks panics
+///
+/// The panic is always recorded in the context's status and still propagates to
+/// the task's `JoinHandle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PanicPolicy {
+    /// Only record the panic
+    #[default]
+    Record,
+    /// Cancel the context and its subtree
+    Cancel,
+    /// Mark the context failed via `fail()` (honours fail-fast ancestors)
+    Fail,
+}
+
+/// Keep at most this many panic messages per context
+const MAX_RECORDED_PANICS: usize = 10;
+
+pin_project_lite::pin_project! {
+    /// Records panics of the wrapped task future on its context
+    pub(crate) struct CatchPanic<F> {
+        #[pin]
+        future: F,
+        context: std::sync::Arc<crate::Context>,
+    }
+}
+
+impl<F> CatchPanic<F> {
+    pub(crate) fn new(future: F, context: std::sync::Arc<crate::Context>) -> Self {
+        CatchPanic { future, context }
+    }
+}
+
+thread_local! {
+    /// Depth of context tasks being polled on this thread, see [`HandledPanics`]
+    static HANDLED_PANICS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Marks the current thread as running a context task while alive: its panics are
+/// handled by the context's [`PanicPolicy`] (and supervisors), so the panic hook
+/// leaves them alone
+struct HandledPanics;
+
+impl HandledPanics {
+    fn enter() -> Self {
+        HANDLED_PANICS.with(|depth| depth.set(depth.get() + 1));
+        HandledPanics
+    }
+
+    /// True while a context task is running on this thread
+    fn active() -> bool {
+        HANDLED_PANICS.with(|depth| depth.get() > 0)
+    }
+}
+
+impl Drop for HandledPanics {
+    fn drop(&mut self) {
+        HANDLED_PANICS.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+impl<F: std::future::Future> std::future::Future for CatchPanic<F> {
+    type Output = F::Output;
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        let this = self.project();
+        let future = this.future;
+
+        let _handled = HandledPanics::enter();
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| future.poll(cx))) {
+            Ok(poll) => poll,
+            Err(payload) => {
+                this.context.record_panic(payload_message(payload.as_ref()));
+                std::panic::resume_unwind(payload)
+            }
+        }
+    }
+}
+
+/// Blocking counterpart of [`CatchPanic`]: records a panic of `f` on `context`
+#[cfg(feature = "tokio")]
+pub(crate) fn catch_panic_blocking<R>(context: &crate::Context, f: impl FnOnce() -> R) -> R {
+    let _handled = HandledPanics::enter();
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(payload) => {
+            context.record_panic(payload_message(payload.as_ref()));
+            std::panic::resume_unwind(payload)
+        }
+    }
+}
+
+/// Best effort message of a panic payload
+pub(crate) fn payload_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+/// Contexts listed in the outline printed by the panic hook
+const PANIC_OUTLINE_LIMIT: usize = 50;
+
+/// Cancel the global context on any unhandled panic, on any thread
+///
+/// The panic is reported by the previously installed hook as usual, then (for the
+/// first panic) an outline of the context tree is printed to stderr and the global
+/// context is cancelled with [`crate::CancelReason::Panicked`]. This way the
+/// application shuts down instead of leaving other tasks running against a broken
+/// invariant. Panics of context tasks are left to their context's [`PanicPolicy`]
+/// (and to supervisors), so only panics outside of them (plain threads, tasks not
+/// spawned through a context, `main`) take everything down. Installing it more
+/// than once has no effect.
+///
+/// The hook runs on the panicking thread, which may hold any lock, so it never
+/// blocks on one: contexts locked at the time show up as busy in the outline.
+pub fn install_panic_hook() {
+    static INSTALLED: std::sync::Once = std::sync::Once::new();
+    INSTALLED.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            previous(info);
+            if HandledPanics::active() {
+                return;
+            }
+
+            static DUMPED: std::sync::atomic::AtomicBool =
+                std::sync::atomic::AtomicBool::new(false);
+            let mut message = payload_message(info.payload());
+            if let Some(location) = info.location() {
+                message = format!("{message} at {location}");
+            }
+            let global = crate::global();
+            if !DUMPED.swap(true, std::sync::atomic::Ordering::Relaxed) {
+                eprint!(
+                    "context tree at the time of the panic:\n{}",
+                    global.try_outline(PANIC_OUTLINE_LIMIT)
+                );
+            }
+
+            #[cfg(feature = "crash-dump")]
+            crate::crash_dump::fatal_from_panic_hook(&format!("panic: {message}"));
+
+            global.try_cancel_with_reason(crate::CancelReason::Panicked(message));
+        }));
+    });
+}
+
+impl crate::Context {
+    /// Messages of the most recent task panics in this context
+    pub fn panics(&self) -> Vec<String> {
+        self.lifecycle
+            .lock()
+            .map(|lifecycle| lifecycle.panics.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Total number of task panics in this context
+    #[cfg(feature = "tokio")]
+    pub(crate) fn panic_count(&self) -> u64 {
+        self.lifecycle
+            .lock()
+            .map(|lifecycle| lifecycle.panic_count)
+            .unwrap_or_default()
+    }
+
+    /// Policy applied when a task of this context panics (inherited from ancestors)
+    pub fn panic_policy(&self) -> PanicPolicy {
+        let own = self
+            .lifecycle
+            .lock()
+            .ok()
+            .and_then(|lifecycle| lifecycle.panic_policy);
+        match (own, self.parent()) {
+            (Some(policy), _) => policy,
+            (None, Some(parent)) => parent.panic_policy(),
+            (None, None) => PanicPolicy::default(),
+        }
+    }
+
+    fn record_panic(&self, message: String) {
+        if let Ok(mut lifecycle) = self.lifecycle.lock() {
+            if lifecycle.panics.len() == MAX_RECORDED_PANICS {
+                lifecycle.panics.pop_front();
+            }
+            lifecycle.panics.push_back(message.clone());
+            lifecycle.panic_count += 1;
+        }
+
+        match self.panic_policy() {
+            PanicPolicy::Record => crate::status_stream::tree_changed(),
+            PanicPolicy::Cancel => self.cancel_with_reason(crate::CancelReason::Panicked(message)),
+            PanicPolicy::Fail => self.fail(format!("task panicked: {message}")),
+        }
+    }
+}
+
+impl crate::ContextBuilder {
+    /// Set what happens when a task in this context (or a descendant) panics
+    pub fn on_panic(self, policy: PanicPolicy) -> Self {
+        self.configure(move |context| {
+            if let Ok(mut lifecycle) = context.lifecycle.lock() {
+                lifecycle.panic_policy = Some(policy);
+            }
+        })
+    }
+}
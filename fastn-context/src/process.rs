@@ -0,0 +1,163 @@
+/// Grace period between SIGTERM and SIGKILL used by `Context::spawn_process`
+pub const DEFAULT_PROCESS_GRACE: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// OS processes owned by a context
+#[derive(Default)]
+pub(crate) struct ProcessRegistry {
+    processes: std::sync::Mutex<std::collections::BTreeMap<u32, TrackedProcess>>,
+}
+
+struct TrackedProcess {
+    command: String,
+    started_at: std::time::Instant,
+    terminating: bool,
+}
+
+/// Status of a child process owned by a context
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProcessStatus {
+    pub pid: u32,
+    pub command: String,
+    pub duration: std::time::Duration,
+    /// SIGTERM was sent and the process has not exited yet
+    pub terminating: bool,
+}
+
+/// Removes the process from its registry once it has exited
+#[cfg(feature = "tokio")]
+struct ProcessGuard {
+    registry: std::sync::Arc<ProcessRegistry>,
+    pid: u32,
+}
+
+#[cfg(feature = "tokio")]
+impl Drop for ProcessGuard {
+    fn drop(&mut self) {
+        if let Ok(mut processes) = self.registry.processes.lock() {
+            processes.remove(&self.pid);
+        }
+        crate::status_stream::tree_changed();
+    }
+}
+
+impl ProcessRegistry {
+    pub(crate) fn statuses(&self) -> Vec<ProcessStatus> {
+        let Ok(processes) = self.processes.lock() else {
+            return Vec::new();
+        };
+
+        processes
+            .iter()
+            .map(|(pid, process)| ProcessStatus {
+                pid: *pid,
+                command: process.command.clone(),
+                duration: process.started_at.elapsed(),
+                terminating: process.terminating,
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "tokio")]
+    fn set_terminating(&self, pid: u32) {
+        if let Ok(mut processes) = self.processes.lock() {
+            if let Some(process) = processes.get_mut(&pid) {
+                process.terminating = true;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl crate::Context {
+    /// Spawn an OS process owned by this context
+    ///
+    /// The process shows up in this context's status. When the context is cancelled
+    /// it receives SIGTERM, followed by SIGKILL if it is still running after
+    /// [`DEFAULT_PROCESS_GRACE`]. The returned handle resolves to the exit status.
+    #[track_caller]
+    pub fn spawn_process(
+        &self,
+        command: tokio::process::Command,
+    ) -> std::io::Result<tokio::task::JoinHandle<std::io::Result<std::process::ExitStatus>>> {
+        self.spawn_process_with_grace(command, DEFAULT_PROCESS_GRACE)
+    }
+
+    /// Like [`crate::Context::spawn_process`] with a custom SIGTERM to SIGKILL grace period
+    #[track_caller]
+    pub fn spawn_process_with_grace(
+        &self,
+        mut command: tokio::process::Command,
+        grace: std::time::Duration,
+    ) -> std::io::Result<tokio::task::JoinHandle<std::io::Result<std::process::ExitStatus>>> {
+        let description = describe(command.as_std());
+        let mut child = command.kill_on_drop(true).spawn()?;
+        let pid = child.id().unwrap_or_default();
+
+        let registry = self.processes();
+        if let Ok(mut processes) = registry.processes.lock() {
+            processes.insert(
+                pid,
+                TrackedProcess {
+                    command: description.clone(),
+                    started_at: std::time::Instant::now(),
+                    terminating: false,
+                },
+            );
+        }
+        crate::status_stream::tree_changed();
+
+        let context = self.clone();
+        Ok(self.spawn_named(&description, async move {
+            let guard = ProcessGuard { registry, pid };
+
+            tokio::select! {
+                status = child.wait() => status,
+                _ = context.cancelled() => {
+                    terminate(&mut child)?;
+                    guard.registry.set_terminating(pid);
+
+                    match tokio::time::timeout(grace, child.wait()).await {
+                        Ok(status) => status,
+                        Err(_) => {
+                            child.kill().await?;
+                            child.wait().await
+                        }
+                    }
+                }
+            }
+        }))
+    }
+}
+
+/// Human readable command line
+#[cfg(feature = "tokio")]
+fn describe(command: &std::process::Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|part| part.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Ask the process to exit (SIGTERM on unix, kill elsewhere)
+#[cfg(feature = "tokio")]
+#[cfg(unix)]
+fn terminate(child: &mut tokio::process::Child) -> std::io::Result<()> {
+    let Some(pid) = child.id() else {
+        return Ok(()); // already exited
+    };
+
+    // SAFETY: kill(2) has no memory safety requirements; `pid` is our own child
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Ask the process to exit (SIGTERM on unix, kill elsewhere)
+#[cfg(feature = "tokio")]
+#[cfg(not(unix))]
+fn terminate(child: &mut tokio::process::Child) -> std::io::Result<()> {
+    child.start_kill()
+}
@@ -0,0 +1,67 @@
+/// Progress of a long-running context, see [`crate::Context::set_progress`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Progress {
+    pub current: u64,
+    pub total: u64,
+}
+
+impl Progress {
+    /// Completed fraction in `0.0..=1.0` (0 when total is 0)
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        (self.current as f64 / self.total as f64).min(1.0)
+    }
+}
+
+impl std::fmt::Display for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{} ({:.0}%)",
+            self.current,
+            self.total,
+            self.fraction() * 100.0
+        )
+    }
+}
+
+/// Progress and status message reported by a context
+#[derive(Default)]
+pub(crate) struct ProgressState {
+    pub(crate) progress: Option<Progress>,
+    pub(crate) message: Option<String>,
+}
+
+impl crate::Context {
+    /// Report how far along this context's work is, shown in its status
+    pub fn set_progress(&self, current: u64, total: u64) {
+        if let Ok(mut state) = self.progress.lock() {
+            state.progress = Some(Progress { current, total });
+        }
+        crate::status_stream::tree_changed();
+    }
+
+    /// Report what this context is currently doing, shown in its status
+    pub fn set_message(&self, message: &str) {
+        if let Ok(mut state) = self.progress.lock() {
+            state.message = Some(message.to_string());
+        }
+        crate::status_stream::tree_changed();
+    }
+
+    /// Last progress reported via `set_progress()`
+    pub fn progress(&self) -> Option<Progress> {
+        self.progress.lock().ok().and_then(|state| state.progress)
+    }
+
+    /// Last message reported via `set_message()`
+    pub fn message(&self) -> Option<String> {
+        self.progress
+            .lock()
+            .ok()
+            .and_then(|state| state.message.clone())
+    }
+}
@@ -0,0 +1,117 @@
+impl crate::Status {
+    /// Render the context tree in Prometheus text exposition format
+    ///
+    /// Every context becomes a `context` label holding its slash separated path
+    /// (e.g. `global/http/worker`); user counters and gauges get an extra `name` label.
+    pub fn to_prometheus(&self) -> String {
+        let mut families = Families::default();
+        families.collect(&self.global_context);
+
+        let mut out = String::new();
+        families.write(&mut out);
+        out
+    }
+}
+
+/// Samples grouped by metric family, in output order
+#[derive(Default)]
+struct Families {
+    tasks: Vec<String>,
+    children: Vec<String>,
+    age: Vec<String>,
+    cancelled: Vec<String>,
+    counters: Vec<String>,
+    gauges: Vec<String>,
+}
+
+impl Families {
+    fn collect(&mut self, ctx: &crate::ContextStatus) {
+        let label = format!("context=\"{}\"", escape_label(&ctx.path));
+
+        self.tasks.push(format!("{{{label}}} {}", ctx.task_count));
+        self.children
+            .push(format!("{{{label}}} {}", ctx.children.len()));
+        self.age
+            .push(format!("{{{label}}} {}", ctx.elapsed.as_secs_f64()));
+        self.cancelled
+            .push(format!("{{{label}}} {}", u8::from(ctx.is_cancelled)));
+
+        for (name, value) in &ctx.counters {
+            self.counters.push(format!(
+                "{{{label},name=\"{}\"}} {value}",
+                escape_label(name)
+            ));
+        }
+        for (name, value) in &ctx.gauges {
+            self.gauges.push(format!(
+                "{{{label},name=\"{}\"}} {value}",
+                escape_label(name)
+            ));
+        }
+
+        for child in &ctx.children {
+            self.collect(child);
+        }
+    }
+
+    fn write(&self, out: &mut String) {
+        let families = [
+            (
+                "fastn_context_tasks",
+                "gauge",
+                "Live tasks spawned through the context",
+                &self.tasks,
+            ),
+            (
+                "fastn_context_children",
+                "gauge",
+                "Child contexts of the context",
+                &self.children,
+            ),
+            (
+                "fastn_context_age_seconds",
+                "gauge",
+                "Time since the context was created",
+                &self.age,
+            ),
+            (
+                "fastn_context_cancelled",
+                "gauge",
+                "Whether the context is cancelled (1) or not (0)",
+                &self.cancelled,
+            ),
+            (
+                "fastn_context_counter_total",
+                "counter",
+                "User defined context counters",
+                &self.counters,
+            ),
+            (
+                "fastn_context_gauge",
+                "gauge",
+                "User defined context gauges",
+                &self.gauges,
+            ),
+        ];
+
+        for (name, kind, help, samples) in families {
+            if samples.is_empty() {
+                continue;
+            }
+
+            out.push_str(&format!("# HELP {name} {help}\n"));
+            out.push_str(&format!("# TYPE {name} {kind}\n"));
+            for sample in samples {
+                out.push_str(&format!("{name}{sample}\n"));
+            }
+        }
+    }
+}
+
+/// Escape a label value per the exposition format
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
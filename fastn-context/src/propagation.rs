@@ -0,0 +1,280 @@
+//! W3C `traceparent` / `baggage` header propagation
+
+/// Name of the W3C trace context header
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// Name of the W3C baggage header
+pub const BAGGAGE_HEADER: &str = "baggage";
+
+/// Baggage key carrying the sender's context path
+const PATH_KEY: &str = "fastn-path";
+
+/// Baggage key carrying the sender's remaining deadline in milliseconds
+const DEADLINE_KEY: &str = "fastn-deadline-ms";
+
+/// Parsed W3C `traceparent` header (version `00`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceParent {
+    pub trace_id: u128,
+    /// ID of the sending context (fastn context ID when sent by fastn-context)
+    pub parent_id: u64,
+    pub sampled: bool,
+}
+
+impl TraceParent {
+    /// Parse a `traceparent` header value, None if malformed
+    pub fn parse(value: &str) -> Option<TraceParent> {
+        let mut parts = value.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = parts.next()?;
+        let parent_id = parts.next()?;
+        let flags = parts.next()?;
+
+        if version.len() != 2
+            || version == "ff"
+            || trace_id.len() != 32
+            || parent_id.len() != 16
+            || flags.len() != 2
+            || ![version, trace_id, parent_id, flags]
+                .iter()
+                .all(|field| is_lower_hex(field))
+        {
+            return None;
+        }
+        // Version 00 has exactly four fields, later versions may append more
+        if version == "00" && parts.next().is_some() {
+            return None;
+        }
+
+        let trace_id = u128::from_str_radix(trace_id, 16).ok()?;
+        let parent_id = u64::from_str_radix(parent_id, 16).ok()?;
+        let flags = u8::from_str_radix(flags, 16).ok()?;
+        if trace_id == 0 || parent_id == 0 {
+            return None;
+        }
+
+        Some(TraceParent {
+            trace_id,
+            parent_id,
+            sampled: flags & 1 == 1,
+        })
+    }
+}
+
+/// Only `0-9a-f`: W3C trace context forbids uppercase hex, and `from_str_radix`
+/// alone would accept a leading `+`
+fn is_lower_hex(field: &str) -> bool {
+    field
+        .bytes()
+        .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+impl std::fmt::Display for TraceParent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "00-{:032x}-{:016x}-{:02x}",
+            self.trace_id,
+            self.parent_id,
+            u8::from(self.sampled)
+        )
+    }
+}
+
+/// Remote context a context was reconstructed from, see [`crate::ContextBuilder::from_headers`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteParent {
+    pub trace_parent: TraceParent,
+    /// Path of the remote context, if it was sent by fastn-context
+    pub path: Option<String>,
+}
+
+/// Trace this context belongs to (stored as a typed value, inherited by children)
+struct Trace {
+    trace_id: u128,
+    sampled: bool,
+}
+
+/// Baggage entries set directly on a context (stored as a typed value)
+#[derive(Clone, Default)]
+struct Baggage(std::collections::BTreeMap<String, String>);
+
+impl crate::Context {
+    /// Attach a baggage entry, propagated to children and over the `baggage` header
+    pub fn set_baggage(&self, key: &str, value: &str) {
+        let mut baggage = self
+            .get_local::<Baggage>()
+            .map(|baggage| (*baggage).clone())
+            .unwrap_or_default();
+        baggage.0.insert(key.to_string(), value.to_string());
+        self.set(baggage);
+    }
+
+    /// Baggage of this context merged with its ancestors' (closest wins)
+    pub fn baggage(&self) -> std::collections::BTreeMap<String, String> {
+        let mut baggage = match self.parent() {
+            Some(parent) => parent.baggage(),
+            None => Default::default(),
+        };
+        if let Some(own) = self.get_local::<Baggage>() {
+            baggage.extend(own.0.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        baggage
+    }
+
+    /// Trace ID this context belongs to, if it joined or started a trace
+    pub fn trace_id(&self) -> Option<u128> {
+        self.get::<Trace>().map(|trace| trace.trace_id)
+    }
+
+    /// Remote context this context was reconstructed from, if any
+    pub fn remote_parent(&self) -> Option<RemoteParent> {
+        self.get::<RemoteParent>().map(|remote| (*remote).clone())
+    }
+
+    /// Headers identifying this context to a remote service
+    ///
+    /// Returns `traceparent` (trace ID plus this context's ID) and `baggage` (user
+    /// baggage plus this context's path and remaining deadline). Starts a new trace
+    /// on this context if it is not part of one yet.
+    pub fn propagation_headers(&self) -> Vec<(&'static str, String)> {
+        let trace = match self.get::<Trace>() {
+            Some(trace) => trace,
+            None => {
+                self.set(Trace {
+                    trace_id: new_trace_id(self.id()),
+                    sampled: true,
+                });
+                self.get::<Trace>().expect("trace was just set")
+            }
+        };
+
+        let trace_parent = TraceParent {
+            trace_id: trace.trace_id,
+            parent_id: self.id(),
+            sampled: trace.sampled,
+        };
+
+        let mut baggage = self.baggage();
+        baggage.insert(PATH_KEY.to_string(), self.path().to_string());
+        if let Some(deadline) = self.deadline() {
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            baggage.insert(DEADLINE_KEY.to_string(), remaining.as_millis().to_string());
+        }
+
+        let baggage = baggage
+            .iter()
+            .map(|(key, value)| format!("{key}={}", percent_encode(value)))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        vec![
+            (TRACEPARENT_HEADER, trace_parent.to_string()),
+            (BAGGAGE_HEADER, baggage),
+        ]
+    }
+}
+
+impl crate::ContextBuilder {
+    /// Link this child to the remote context described by incoming headers
+    ///
+    /// Header names are matched case-insensitively. The child joins the remote trace,
+    /// inherits its baggage and adopts its deadline (never extending the local one).
+    pub fn from_headers<'a>(self, headers: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut trace_parent = None;
+        let mut baggage = std::collections::BTreeMap::new();
+
+        for (name, value) in headers {
+            if name.eq_ignore_ascii_case(TRACEPARENT_HEADER) {
+                trace_parent = TraceParent::parse(value);
+            } else if name.eq_ignore_ascii_case(BAGGAGE_HEADER) {
+                baggage.extend(parse_baggage(value));
+            }
+        }
+
+        let path = baggage.remove(PATH_KEY);
+        let mut builder = self;
+        if let Some(remaining) = baggage
+            .remove(DEADLINE_KEY)
+            .and_then(|ms| ms.parse::<u64>().ok())
+        {
+            builder = builder.timeout(std::time::Duration::from_millis(remaining));
+        }
+
+        builder.configure(move |context| {
+            if let Some(trace_parent) = trace_parent {
+                context.set(Trace {
+                    trace_id: trace_parent.trace_id,
+                    sampled: trace_parent.sampled,
+                });
+                context.set(RemoteParent { trace_parent, path });
+            }
+
+            if !baggage.is_empty() {
+                context.set(Baggage(baggage));
+            }
+        })
+    }
+}
+
+/// Parse a `baggage` header into key/value pairs (properties are ignored)
+fn parse_baggage(value: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    value.split(',').filter_map(|member| {
+        let member = member.split(';').next()?;
+        let (key, value) = member.split_once('=')?;
+        let key = key.trim();
+        if key.is_empty() {
+            return None;
+        }
+        Some((key.to_string(), percent_decode(value.trim())))
+    })
+}
+
+/// Percent-encode characters not allowed in a baggage value
+fn percent_encode(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'!' | b'#'..=b'+' | b'-'..=b':' | b'<'..=b'[' | b']'..=b'~' if byte != b'%' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+/// Decode `%XX` escapes, leaving malformed escapes untouched
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && i + 2 < bytes.len()
+            && bytes[i + 1..i + 3].iter().all(u8::is_ascii_hexdigit)
+        {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Generate a new (non-cryptographic) random trace ID
+fn new_trace_id(seed: u64) -> u128 {
+    let hash = |salt: u64| {
+        std::hash::BuildHasher::hash_one(
+            &std::collections::hash_map::RandomState::new(),
+            (seed, salt, std::time::SystemTime::now()),
+        )
+    };
+    let trace_id = (u128::from(hash(1)) << 64) | u128::from(hash(2));
+    trace_id.max(1)
+}
@@ -0,0 +1,187 @@
+/// Quotas of a context, stored as a typed value, see `ContextBuilder::max_children()`
+#[derive(Clone, Default)]
+struct Quota {
+    max_children: Option<usize>,
+    max_tracked_tasks: Option<usize>,
+    max_depth: Option<usize>,
+}
+
+/// Returned by [`crate::Context::try_spawn_tracked`] (and by
+/// [`crate::ContextBuilder::try_build`] as [`crate::ChildError::Quota`]) when a quota would
+/// be exceeded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuotaExceeded {
+    /// The context at `path` already has `limit` active children
+    Children { path: String, limit: usize },
+    /// The context at `path` already tracks `limit` tasks
+    TrackedTasks { path: String, limit: usize },
+    /// The new child would be more than `limit` levels below the context at `path`
+    Depth { path: String, limit: usize },
+}
+
+impl std::fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuotaExceeded::Children { path, limit } => {
+                write!(f, "{path} reached its quota of {limit} children")
+            }
+            QuotaExceeded::TrackedTasks { path, limit } => {
+                write!(f, "{path} reached its quota of {limit} tasks")
+            }
+            QuotaExceeded::Depth { path, limit } => {
+                write!(f, "{path} allows at most {limit} levels of children")
+            }
+        }
+    }
+}
+
+impl std::error::Error for QuotaExceeded {}
+
+impl crate::ContextBuilder {
+    /// Allow at most `limit` active children
+    ///
+    /// Children count as active until they finish (or are cancelled with no tasks
+    /// left), so a server can't fan out without bound.
+    /// [`crate::ContextBuilder::build`] panics over the quota,
+    /// [`crate::ContextBuilder::try_build`] fails instead.
+    pub fn max_children(self, limit: usize) -> Self {
+        self.update_quota(move |quota| quota.max_children = Some(limit))
+    }
+
+    /// Allow at most `limit` live tasks
+    ///
+    /// Every way of spawning through this context counts and panics over the quota,
+    /// [`crate::Context::try_spawn_tracked`] fails instead. Tasks waiting for a
+    /// `max_concurrent_tasks()` slot count as live. Cleanup (`on_cancel()` hooks,
+    /// guards) always runs.
+    pub fn max_tracked_tasks(self, limit: usize) -> Self {
+        self.update_quota(move |quota| quota.max_tracked_tasks = Some(limit))
+    }
+
+    /// Allow descendants at most `limit` levels below this context (1 for children
+    /// only), enforced like `max_children()` anywhere in the subtree
+    pub fn max_depth(self, limit: usize) -> Self {
+        self.update_quota(move |quota| quota.max_depth = Some(limit))
+    }
+
+    fn update_quota(self, update: impl FnOnce(&mut Quota) + Send + 'static) -> Self {
+        self.configure(|context| {
+            let mut quota = context
+                .get_local::<Quota>()
+                .map(|quota| (*quota).clone())
+                .unwrap_or_default();
+            update(&mut quota);
+            context.set(quota);
+        })
+    }
+}
+
+/// Refuse a child of `parent` that would exceed a `max_depth()` quota of `parent`
+/// or an ancestor
+pub(crate) fn check_depth(parent: &crate::Context) -> Result<(), QuotaExceeded> {
+    // The new child is `depth` levels below `ancestor`
+    let mut ancestor = Some(parent.arc());
+    let mut depth = 1;
+    while let Some(context) = ancestor {
+        if let Some(limit) = context
+            .get_local::<Quota>()
+            .and_then(|quota| quota.max_depth)
+        {
+            if depth > limit {
+                return Err(QuotaExceeded::Depth {
+                    path: context.path().to_string(),
+                    limit,
+                });
+            }
+        }
+        ancestor = context.parent();
+        depth += 1;
+    }
+    Ok(())
+}
+
+/// Refuse another child of `parent` beyond its `max_children()` quota, called with
+/// the parent's `children` locked
+///
+/// `replacing` active children are about to be replaced by the new one, so they
+/// don't count.
+pub(crate) fn check_children(
+    parent: &crate::Context,
+    children: &[std::sync::Arc<crate::Context>],
+    replacing: usize,
+) -> Result<(), QuotaExceeded> {
+    if let Some(limit) = parent
+        .get_local::<Quota>()
+        .and_then(|quota| quota.max_children)
+    {
+        let active = children
+            .iter()
+            .filter(|child| child.is_active() && !child.is_replaced())
+            .count()
+            - replacing;
+        if active >= limit {
+            return Err(QuotaExceeded::Children {
+                path: parent.path().to_string(),
+                limit,
+            });
+        }
+    }
+    Ok(())
+}
+
+impl crate::Context {
+    /// Like [`crate::Context::child`], but fails right away if this context reached
+    /// its `max_children()` quota or the child would exceed a `max_depth()` quota
+    /// of this context or an ancestor
+    ///
+    /// Also fails under [`crate::ChildNamePolicy::Error`] if an active child `name`
+    /// exists. The child is only created by the builder, which checks again: where
+    /// callers race for the last slot, use [`crate::ContextBuilder::try_build`].
+    pub fn try_child(&self, name: &str) -> Result<crate::ContextBuilder, crate::ChildError> {
+        self.check_child(name)?;
+        Ok(self.child(name))
+    }
+
+    /// Like [`crate::Context::spawn`], but fails instead of panicking if this
+    /// context already tracks its `max_tracked_tasks()` quota of tasks
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    pub fn try_spawn_tracked<F>(
+        &self,
+        task: F,
+    ) -> Result<tokio::task::JoinHandle<F::Output>, QuotaExceeded>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        Ok(self.spawn_limited(self.reserve_task(None)?, task))
+    }
+
+    /// Reserve a slot for a task about to be spawned, unless that would exceed the
+    /// `max_tracked_tasks()` quota
+    #[track_caller]
+    pub(crate) fn reserve_task(
+        &self,
+        name: Option<String>,
+    ) -> Result<crate::task::TaskGuard, QuotaExceeded> {
+        let limit = self
+            .get_local::<Quota>()
+            .and_then(|quota| quota.max_tracked_tasks);
+        match self.tasks.try_register(name, limit) {
+            Some(slot) => Ok(slot),
+            None => Err(QuotaExceeded::TrackedTasks {
+                path: self.path().to_string(),
+                limit: limit.unwrap_or_default(),
+            }),
+        }
+    }
+
+    /// Like [`crate::Context::reserve_task`], panicking over the quota
+    #[track_caller]
+    pub(crate) fn task_slot(&self, name: Option<String>) -> crate::task::TaskGuard {
+        match self.reserve_task(name) {
+            Ok(slot) => slot,
+            Err(quota) => panic!("task refused: {quota}"),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+/// Rate limiter usage of a context, see [`crate::Context::rate_limit`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RateLimitStatus {
+    pub permits_per_sec: f64,
+    /// Permits handed out during the last full second
+    pub current_rate: f64,
+    /// Permits that can be acquired right now without waiting
+    pub available: f64,
+}
+
+/// Token bucket, stored as a typed value on the limiting context
+///
+/// Timed with tokio's clock, which the waiting side sleeps on (and tests pause).
+#[cfg(feature = "tokio")]
+pub(crate) struct RateLimiter {
+    permits_per_sec: f64,
+    state: std::sync::Mutex<Bucket>,
+}
+
+#[cfg(feature = "tokio")]
+struct Bucket {
+    tokens: f64,
+    refilled_at: tokio::time::Instant,
+    /// Start of the current one second measuring window
+    window_start: tokio::time::Instant,
+    window_count: u64,
+    last_window_count: u64,
+}
+
+#[cfg(feature = "tokio")]
+impl RateLimiter {
+    fn new(permits_per_sec: f64) -> Self {
+        let now = tokio::time::Instant::now();
+        RateLimiter {
+            permits_per_sec,
+            state: std::sync::Mutex::new(Bucket {
+                tokens: permits_per_sec.max(1.0),
+                refilled_at: now,
+                window_start: now,
+                window_count: 0,
+                last_window_count: 0,
+            }),
+        }
+    }
+
+    /// Burst size: one second worth of permits
+    fn capacity(&self) -> f64 {
+        self.permits_per_sec.max(1.0)
+    }
+
+    /// Take a token, or return how long to wait for the next one
+    fn try_take(&self) -> Result<(), std::time::Duration> {
+        let Ok(mut bucket) = self.state.lock() else {
+            return Ok(());
+        };
+        let now = tokio::time::Instant::now();
+        self.refill(&mut bucket, now);
+
+        if bucket.tokens < 1.0 {
+            return Err(std::time::Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / self.permits_per_sec,
+            ));
+        }
+
+        bucket.tokens -= 1.0;
+        bucket.window_count += 1;
+        Ok(())
+    }
+
+    fn refill(&self, bucket: &mut Bucket, now: tokio::time::Instant) {
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.permits_per_sec).min(self.capacity());
+        bucket.refilled_at = now;
+
+        let window = now.duration_since(bucket.window_start);
+        if window >= std::time::Duration::from_secs(2) {
+            bucket.last_window_count = 0;
+            bucket.window_count = 0;
+            bucket.window_start = now;
+        } else if window >= std::time::Duration::from_secs(1) {
+            bucket.last_window_count = bucket.window_count;
+            bucket.window_count = 0;
+            bucket.window_start += std::time::Duration::from_secs(1);
+        }
+    }
+
+    pub(crate) fn status(&self) -> RateLimitStatus {
+        let (current_rate, available) = match self.state.lock() {
+            Ok(mut bucket) => {
+                self.refill(&mut bucket, tokio::time::Instant::now());
+                (bucket.last_window_count as f64, bucket.tokens)
+            }
+            Err(_) => (0.0, 0.0),
+        };
+
+        RateLimitStatus {
+            permits_per_sec: self.permits_per_sec,
+            current_rate,
+            available,
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl crate::Context {
+    /// Make this context a token bucket rate limiter for the work it owns
+    ///
+    /// Allows bursts of up to one second worth of permits. Replaces any limit set
+    /// before. Children share the bucket, see [`crate::Context::acquire`].
+    pub fn rate_limit(&self, permits_per_sec: f64) {
+        assert!(
+            permits_per_sec > 0.0 && permits_per_sec.is_finite(),
+            "rate_limit() expects a positive permits_per_sec"
+        );
+        self.set(RateLimiter::new(permits_per_sec));
+    }
+
+    /// Wait for a permit from the closest rate limited context (this one or an ancestor)
+    ///
+    /// Returns immediately if no rate limit applies, or `Err(Cancelled)` if this
+    /// context is cancelled while waiting.
+    pub async fn acquire(&self) -> Result<(), crate::Cancelled> {
+        let Some(limiter) = self.get::<RateLimiter>() else {
+            return Ok(());
+        };
+
+        self.run(async move {
+            while let Err(wait) = limiter.try_take() {
+                tokio::time::sleep(wait).await;
+            }
+        })
+        .await
+    }
+}
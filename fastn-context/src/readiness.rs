@@ -0,0 +1,119 @@
+/// Readiness a context reported via [`crate::Context::ready`] /
+/// [`crate::Context::not_ready`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReadinessStatus {
+    pub ready: bool,
+    /// Why the context is not ready yet
+    pub reason: Option<String>,
+}
+
+/// Bumped whenever some context reports its readiness, wakes `all_ready()` waiters
+static READINESS_CHANGED: std::sync::LazyLock<tokio::sync::watch::Sender<u64>> =
+    std::sync::LazyLock::new(|| tokio::sync::watch::Sender::new(0));
+
+impl crate::Context {
+    /// Report that this context is ready (e.g. its connection pool is up)
+    pub fn ready(&self) {
+        self.set_readiness(ReadinessStatus {
+            ready: true,
+            reason: None,
+        });
+    }
+
+    /// Report that this context is not ready, e.g. `not_ready("connecting to db")`
+    pub fn not_ready(&self, reason: &str) {
+        self.set_readiness(ReadinessStatus {
+            ready: false,
+            reason: Some(reason.to_string()),
+        });
+    }
+
+    /// True once the context called [`crate::Context::ready`] (and not
+    /// `not_ready()` since)
+    pub fn is_ready(&self) -> bool {
+        self.get_local::<ReadinessStatus>()
+            .is_some_and(|status| status.ready)
+    }
+
+    /// Readiness reported by this context, None if it never reported any
+    pub fn readiness(&self) -> Option<ReadinessStatus> {
+        self.get_local::<ReadinessStatus>()
+            .map(|status| (*status).clone())
+    }
+
+    fn set_readiness(&self, status: ReadinessStatus) {
+        self.set(status);
+        READINESS_CHANGED.send_modify(|generation| *generation += 1);
+        crate::status_stream::tree_changed();
+    }
+}
+
+/// Wait until every one of `contexts` is ready
+///
+/// Each entry is a context name (e.g. `db`), or a path glob if it contains a `/`
+/// (e.g. `global/storage/*`, see [`crate::find`]). An entry counts as ready once
+/// at least one live context matches it and all live matches are ready; contexts
+/// that don't exist yet are waited for. Serve traffic only once the subsystems are
+/// up:
+///
+/// ```rust,no_run
+/// # async fn serve() {}
+/// #[fastn_context::main]
+/// async fn main() {
+///     let ctx = fastn_context::global();
+///     ctx.spawn_child("db", |db| async move {
+///         // ... connect ...
+///         db.ready();
+///         db.cancelled().await;
+///     });
+///
+///     fastn_context::all_ready(&["db"]).await;
+///     serve().await;
+/// }
+/// ```
+///
+/// Combine with [`crate::Context::timeout`] to bound the wait.
+pub async fn all_ready(contexts: &[&str]) {
+    wait_for_readiness(|| contexts.iter().all(|entry| is_entry_ready(entry))).await
+}
+
+/// Wait until `condition` holds, re-checking whenever some context reports readiness
+pub(crate) async fn wait_for_readiness(condition: impl Fn() -> bool) {
+    let mut changed = READINESS_CHANGED.subscribe();
+    loop {
+        changed.borrow_and_update();
+        if condition() {
+            return;
+        }
+        // The sender lives in a static and is never dropped
+        let _ = changed.changed().await;
+    }
+}
+
+/// True if every live context that reported its readiness is ready, for
+/// readiness probes
+pub fn is_ready() -> bool {
+    not_ready_contexts().is_empty()
+}
+
+/// Live contexts that reported they are not ready, with their reasons, ordered by
+/// context ID
+pub fn not_ready_contexts() -> Vec<(String, Option<String>)> {
+    crate::find("**")
+        .into_iter()
+        .filter_map(|context| match context.readiness() {
+            Some(status) if !status.ready => Some((context.path().to_string(), status.reason)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn is_entry_ready(entry: &str) -> bool {
+    let matches = if entry.contains('/') {
+        crate::find(entry)
+    } else {
+        crate::find(&format!("**/{entry}"))
+    };
+    !matches.is_empty() && matches.iter().all(|context| context.is_ready())
+}
@@ -0,0 +1,126 @@
+/// Next context ID to hand out (IDs start at 1)
+static NEXT_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Number of independently locked registry shards
+const SHARDS: usize = 16;
+
+/// Live contexts by ID, used for lookups from logs/traces
+///
+/// Sharded by ID so creating contexts on many threads, and walking the registry in
+/// `find()`, don't all contend on a single lock.
+static CONTEXTS: std::sync::LazyLock<[std::sync::Mutex<Shard>; SHARDS]> =
+    std::sync::LazyLock::new(|| std::array::from_fn(|_| Default::default()));
+
+#[derive(Default)]
+struct Shard {
+    contexts: std::collections::HashMap<u64, std::sync::Weak<crate::Context>>,
+    /// Drop dead entries once the map grows past this size
+    prune_at: usize,
+}
+
+fn shard(id: u64) -> &'static std::sync::Mutex<Shard> {
+    &CONTEXTS[id as usize % SHARDS]
+}
+
+/// Allocate a new unique context ID
+pub(crate) fn next_id() -> u64 {
+    NEXT_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+}
+
+/// Make a freshly created context discoverable via `find_by_id()`
+pub(crate) fn register(context: &std::sync::Arc<crate::Context>) {
+    let Ok(mut shard) = shard(context.id()).lock() else {
+        return;
+    };
+
+    shard
+        .contexts
+        .insert(context.id(), std::sync::Arc::downgrade(context));
+
+    if shard.contexts.len() > shard.prune_at {
+        shard
+            .contexts
+            .retain(|_, context| context.strong_count() > 0);
+        shard.prune_at = (shard.contexts.len() * 2).max(64);
+    }
+}
+
+/// Find a live context by its ID
+pub fn find_by_id(id: u64) -> Option<std::sync::Arc<crate::Context>> {
+    shard(id)
+        .lock()
+        .ok()?
+        .contexts
+        .get(&id)
+        .and_then(std::sync::Weak::upgrade)
+}
+
+/// Find live contexts whose path matches a glob (e.g. `global/http/*`)
+///
+/// `*` matches within a single path segment, `**` matches any number of segments.
+/// Results are ordered by context ID (creation order).
+pub fn find(pattern: &str) -> Vec<std::sync::Arc<crate::Context>> {
+    let mut found = Vec::new();
+    for shard in CONTEXTS.iter() {
+        // Only hold the shard lock while upgrading, match outside of it
+        let live: Vec<_> = match shard.lock() {
+            Ok(shard) => shard
+                .contexts
+                .values()
+                .filter_map(std::sync::Weak::upgrade)
+                .collect(),
+            Err(_) => continue,
+        };
+        found.extend(
+            live.into_iter()
+                .filter(|context| crate::glob::matches(pattern, context.path())),
+        );
+    }
+    found.sort_by_key(|context| context.id());
+    found
+}
+
+/// Cancel live contexts whose path matches a glob (e.g. `app/jobs/*`)
+///
+/// Lets admin endpoints stop a class of work without holding handles. Contexts
+/// that are already cancelled are skipped. Returns the paths of the contexts
+/// cancelled, see [`find`] for the pattern syntax.
+pub fn cancel_matching(pattern: &str) -> Vec<String> {
+    find(pattern)
+        .into_iter()
+        .filter(|context| !context.is_cancelled())
+        .map(|context| {
+            context.cancel();
+            context.path().to_string()
+        })
+        .collect()
+}
+
+/// Contexts that outlived their owners: cancelled or finished, yet still running
+/// tasks or still referenced from somewhere
+///
+/// Finished children are dropped from the tree once their last handle is gone, so
+/// anything listed here is kept alive by a task that ignores cancellation or by a
+/// stray handle (e.g. stored in a long-lived struct). Contexts that are still
+/// winding down show up briefly too.
+pub fn leaked() -> Vec<String> {
+    find("**")
+        .into_iter()
+        .filter(|context| context.is_cancelled() || context.total_duration().is_some())
+        .filter_map(|context| {
+            let task_count = context.task_count();
+            // Tasks hold the context too, but they are reported above. Apart from
+            // that, the only references not held by some caller are the one returned
+            // by `find()` and the parent's children list.
+            let handles = std::sync::Arc::strong_count(&context)
+                .saturating_sub(1 + context.tree_references());
+            if task_count > 0 {
+                Some(format!("{} ({task_count} task(s))", context.path()))
+            } else if !context.is_root() && handles > 0 {
+                Some(format!("{} (still referenced)", context.path()))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
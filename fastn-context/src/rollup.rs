@@ -0,0 +1,96 @@
+/// Aggregate stats of a context's completed children, see [`crate::ContextBuilder::roll_up`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RollUpStatus {
+    /// Children whose task finished
+    pub completed: u64,
+    /// Completed children that failed or panicked
+    pub errors: u64,
+    /// How long the children lived
+    pub latency: crate::LatencyHistogram,
+}
+
+/// Roll-up stats, stored as a typed value on the rolling-up context
+#[derive(Default)]
+pub(crate) struct RollUp {
+    status: std::sync::Mutex<RollUpStatus>,
+}
+
+impl RollUp {
+    pub(crate) fn status(&self) -> RollUpStatus {
+        self.status
+            .lock()
+            .map(|status| status.clone())
+            .unwrap_or_default()
+    }
+
+    fn record(&self, latency: std::time::Duration, failed: bool) {
+        if let Ok(mut status) = self.status.lock() {
+            status.completed += 1;
+            if failed {
+                status.errors += 1;
+            }
+            status.latency.record(latency);
+        }
+    }
+}
+
+/// Marks a finished child whose roll-up waits for its remaining work, see
+/// [`roll_up`]; whether it failed is decided when it finished
+struct Pending {
+    failed: bool,
+}
+
+/// Fold a child into its parent's roll-up, if the parent rolls up its children
+///
+/// Called once the task started by the child's `ContextBuilder::spawn()` is done,
+/// including when it panicked or was aborted. A child that still has tasks or
+/// active children stays in the tree, so status and shutdown keep seeing that
+/// work; it is rolled up once it is pruned.
+#[cfg(feature = "tokio")]
+pub(crate) fn roll_up(context: &crate::Context) {
+    let Some(parent) = context.parent() else {
+        return;
+    };
+    if parent.get_local::<RollUp>().is_none() {
+        return;
+    }
+
+    let failed =
+        std::thread::panicking() || matches!(context.state(), crate::ContextState::Failed(_));
+    if context.task_count() > 0 || !context.children().is_empty() {
+        context.set(Pending { failed });
+        return;
+    }
+    record(&parent, context, failed);
+    parent.remove_child(context.id());
+}
+
+/// Roll up a child pruned from `parent`'s children, if its roll-up was deferred
+pub(crate) fn pruned(parent: &crate::Context, child: &crate::Context) {
+    if let Some(pending) = child.get_local::<Pending>() {
+        record(parent, child, pending.failed);
+    }
+}
+
+fn record(parent: &crate::Context, context: &crate::Context, failed: bool) {
+    let Some(roll_up) = parent.get_local::<RollUp>() else {
+        return;
+    };
+    let lifetime = context
+        .total_duration()
+        .unwrap_or_else(|| context.created_at.elapsed());
+    roll_up.record(lifetime, failed);
+}
+
+impl crate::ContextBuilder {
+    /// Collapse completed children into aggregate stats instead of listing them
+    ///
+    /// For high-cardinality children like per-request contexts: once the task
+    /// started by a child's `spawn()` finishes (and any tasks and children it left
+    /// behind are done), the child is removed from this context's status and
+    /// counted in its [`RollUpStatus`] (count, errors, latency histogram) instead.
+    pub fn roll_up(self) -> Self {
+        self.configure(|context| context.set(RollUp::default()))
+    }
+}
@@ -0,0 +1,116 @@
+/// Metrics of the tokio runtime the status snapshot was taken on
+///
+/// Fields marked optional need tokio's unstable metrics, i.e. building with
+/// `RUSTFLAGS="--cfg tokio_unstable"`, and are None otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuntimeStatus {
+    pub workers: usize,
+    /// Tasks currently alive on the runtime, tracked by contexts or not
+    pub alive_tasks: usize,
+    /// Tasks waiting in the runtime's shared injection queue
+    pub global_queue_depth: usize,
+    /// Tasks waiting in each worker's local run queue
+    pub worker_queue_depths: Option<Vec<usize>>,
+    /// Blocking tasks waiting for a free blocking thread
+    pub blocking_queue_depth: Option<usize>,
+    pub blocking_threads: Option<usize>,
+    pub idle_blocking_threads: Option<usize>,
+    /// Tasks spawned since the runtime was created
+    pub spawned_tasks: Option<u64>,
+}
+
+impl RuntimeStatus {
+    /// Metrics of the current runtime, None outside of a tokio runtime
+    #[cfg(feature = "tokio")]
+    pub(crate) fn current() -> Option<Self> {
+        let metrics = tokio::runtime::Handle::try_current().ok()?.metrics();
+
+        #[allow(unused_mut)]
+        let mut status = RuntimeStatus {
+            workers: metrics.num_workers(),
+            alive_tasks: metrics.num_alive_tasks(),
+            global_queue_depth: metrics.global_queue_depth(),
+            worker_queue_depths: None,
+            blocking_queue_depth: None,
+            blocking_threads: None,
+            idle_blocking_threads: None,
+            spawned_tasks: None,
+        };
+
+        #[cfg(tokio_unstable)]
+        {
+            status.worker_queue_depths = Some(
+                (0..status.workers)
+                    .map(|worker| metrics.worker_local_queue_depth(worker))
+                    .collect(),
+            );
+            status.blocking_queue_depth = Some(metrics.blocking_queue_depth());
+            status.blocking_threads = Some(metrics.num_blocking_threads());
+            status.idle_blocking_threads = Some(metrics.num_idle_blocking_threads());
+            #[cfg(target_has_atomic = "64")]
+            {
+                status.spawned_tasks = Some(metrics.spawned_tasks_count());
+            }
+        }
+
+        Some(status)
+    }
+
+    /// No runtime metrics without the `tokio` feature
+    #[cfg(not(feature = "tokio"))]
+    pub(crate) fn current() -> Option<Self> {
+        None
+    }
+}
+
+impl std::fmt::Display for RuntimeStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} workers, {} alive tasks, {} queued",
+            self.workers, self.alive_tasks, self.global_queue_depth
+        )?;
+        if let Some(depths) = &self.worker_queue_depths {
+            let local: usize = depths.iter().sum();
+            write!(f, " + {local} in worker queues")?;
+        }
+        if let (Some(threads), Some(idle)) = (self.blocking_threads, self.idle_blocking_threads) {
+            write!(f, ", {threads} blocking threads ({idle} idle)")?;
+        }
+        if let Some(queued) = self.blocking_queue_depth {
+            if queued > 0 {
+                write!(f, ", {queued} blocking queued")?;
+            }
+        }
+        if let Some(spawned) = self.spawned_tasks {
+            write!(f, ", {spawned} spawned")?;
+        }
+        Ok(())
+    }
+}
+
+/// Runtime the tasks of a context (and its descendants) are spawned on, see
+/// `ContextBuilder::runtime()`
+#[cfg(feature = "tokio")]
+struct Runtime(tokio::runtime::Handle);
+
+#[cfg(feature = "tokio")]
+impl crate::ContextBuilder {
+    /// Spawn this context's tasks, and those of its descendants, on `runtime`
+    /// instead of the current runtime
+    ///
+    /// For applications running several tokio runtimes (e.g. separate IO and
+    /// compute runtimes) that still want all tasks owned by one context tree.
+    pub fn runtime(self, runtime: tokio::runtime::Handle) -> Self {
+        self.configure(move |context| context.set(Runtime(runtime)))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl crate::Context {
+    /// Runtime set with `ContextBuilder::runtime()` on this context or an ancestor
+    pub(crate) fn runtime(&self) -> Option<tokio::runtime::Handle> {
+        self.get::<Runtime>().map(|runtime| runtime.0.clone())
+    }
+}
@@ -0,0 +1,124 @@
+/// Run history of a periodic context (`spawn_interval()` / `spawn_cron()`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScheduleStatus {
+    /// Number of finished runs
+    pub runs: u64,
+    /// Time since the last run finished
+    pub since_last_run: Option<std::time::Duration>,
+    /// Error returned by the last run, None if it succeeded
+    pub last_error: Option<String>,
+    /// Time until the next run is due
+    pub next_run_in: Option<std::time::Duration>,
+}
+
+/// Schedule bookkeeping, stored as a typed value on the periodic context
+#[derive(Default)]
+pub(crate) struct Schedule {
+    state: std::sync::Mutex<ScheduleState>,
+}
+
+#[derive(Default)]
+struct ScheduleState {
+    runs: u64,
+    last_run: Option<std::time::Instant>,
+    last_error: Option<String>,
+    next_run: Option<std::time::Instant>,
+}
+
+impl Schedule {
+    pub(crate) fn status(&self) -> ScheduleStatus {
+        let now = std::time::Instant::now();
+        let Ok(state) = self.state.lock() else {
+            return ScheduleStatus {
+                runs: 0,
+                since_last_run: None,
+                last_error: None,
+                next_run_in: None,
+            };
+        };
+
+        ScheduleStatus {
+            runs: state.runs,
+            since_last_run: state.last_run.map(|last_run| now - last_run),
+            last_error: state.last_error.clone(),
+            next_run_in: state
+                .next_run
+                .map(|next_run| next_run.saturating_duration_since(now)),
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    pub(crate) fn set_next_run(&self, next_run: Option<std::time::Instant>) {
+        if let Ok(mut state) = self.state.lock() {
+            state.next_run = next_run;
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    pub(crate) fn record_run(&self, error: Option<String>) {
+        if let Ok(mut state) = self.state.lock() {
+            state.runs += 1;
+            state.last_run = Some(std::time::Instant::now());
+            state.last_error = error;
+        }
+        crate::status_stream::tree_changed();
+    }
+}
+
+impl crate::Context {
+    /// Run `task` every `period` in a named child context until it is cancelled
+    ///
+    /// The first run starts immediately. Runs never overlap: ticks missed while a
+    /// run is still going are skipped. The child's status reports the run count,
+    /// the last run and its error, and when the next run is due.
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    pub fn spawn_interval<F, Fut, E>(
+        &self,
+        name: &str,
+        period: std::time::Duration,
+        task: F,
+    ) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn(std::sync::Arc<crate::Context>) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<(), E>> + Send + 'static,
+        E: std::fmt::Display,
+    {
+        assert!(
+            !period.is_zero(),
+            "spawn_interval() period must be non-zero"
+        );
+
+        let context = self.child(name).build();
+        context.set(Schedule::default());
+
+        context.clone().spawn(async move {
+            let Some(schedule) = context.get_local::<Schedule>() else {
+                return;
+            };
+            let mut next_run = std::time::Instant::now();
+
+            loop {
+                schedule.set_next_run(Some(next_run));
+                tokio::select! {
+                    _ = context.cancelled() => break,
+                    _ = tokio::time::sleep_until(next_run.into()) => {}
+                }
+
+                schedule.set_next_run(None);
+                let error = task(context.clone()).await.err().map(|e| e.to_string());
+                schedule.record_run(error);
+
+                // Skip ticks missed while the run was going
+                next_run += period;
+                let now = std::time::Instant::now();
+                while next_run <= now {
+                    next_run += period;
+                }
+            }
+
+            schedule.set_next_run(None);
+        })
+    }
+}
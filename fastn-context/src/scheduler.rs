@@ -0,0 +1,74 @@
+/// Invalid cron expression passed to [`crate::Context::spawn_cron`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronError {
+    pub expression: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for CronError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid cron expression {:?}: {}",
+            self.expression, self.message
+        )
+    }
+}
+
+impl std::error::Error for CronError {}
+
+impl crate::Context {
+    /// Run `task` in a named child context whenever the cron expression fires (UTC)
+    ///
+    /// Expressions have a leading seconds field, e.g. `0 */5 * * * *` fires every
+    /// five minutes. Runs never overlap: fire times missed while a run is still going
+    /// are skipped. Firing stops once the context is cancelled. The child's status
+    /// reports the run history and when the job fires next.
+    #[track_caller]
+    pub fn spawn_cron<F, Fut, E>(
+        &self,
+        name: &str,
+        expression: &str,
+        task: F,
+    ) -> Result<tokio::task::JoinHandle<()>, CronError>
+    where
+        F: Fn(std::sync::Arc<crate::Context>) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<(), E>> + Send + 'static,
+        E: std::fmt::Display,
+    {
+        let cron: ::cron::Schedule =
+            expression
+                .parse()
+                .map_err(|e: ::cron::error::Error| CronError {
+                    expression: expression.to_string(),
+                    message: e.to_string(),
+                })?;
+
+        let context = self.child(name).build();
+        context.set(crate::schedule::Schedule::default());
+
+        Ok(context.clone().spawn(async move {
+            let Some(schedule) = context.get_local::<crate::schedule::Schedule>() else {
+                return;
+            };
+
+            // `upcoming()` starts after now, so fire times missed by a long run are skipped
+            while let Some(fire_at) = cron.upcoming(chrono::Utc).next() {
+                let delay = (fire_at - chrono::Utc::now()).to_std().unwrap_or_default();
+                let next_run = std::time::Instant::now() + delay;
+
+                schedule.set_next_run(Some(next_run));
+                tokio::select! {
+                    _ = context.cancelled() => break,
+                    _ = tokio::time::sleep_until(next_run.into()) => {}
+                }
+
+                schedule.set_next_run(None);
+                let error = task(context.clone()).await.err().map(|e| e.to_string());
+                schedule.record_run(error);
+            }
+
+            schedule.set_next_run(None);
+        }))
+    }
+}
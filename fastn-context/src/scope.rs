@@ -0,0 +1,66 @@
+impl crate::Context {
+    /// Run `block` in a child context named `name`, then wait for every task it
+    /// spawned (structured concurrency)
+    ///
+    /// Once `block` succeeds, the tasks spawned directly on the child context are
+    /// waited for. Then the child is cancelled, so its `on_cancel()` hooks run and
+    /// long-lived work under it (e.g. a listener waiting for
+    /// [`crate::Context::cancelled`] in a grandchild) stops, and drained: the scope
+    /// only returns once all tasks of the child context and its descendants have
+    /// finished. A task spawned directly on the child that only waits for
+    /// cancellation keeps the scope open, run such work in a child of the scope.
+    ///
+    /// If `block` returns an error, the child is cancelled with
+    /// [`crate::CancelReason::Failure`] right away and drained before the error is
+    /// returned. If the scope future is dropped early, the child is cancelled.
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let ctx = fastn_context::Context::new("app");
+    /// let total = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    ///
+    /// ctx.scope("batch", |scope| {
+    ///     let total = total.clone();
+    ///     async move {
+    ///         for i in 0..3 {
+    ///             let total = total.clone();
+    ///             scope.spawn(async move {
+    ///                 total.fetch_add(i, std::sync::atomic::Ordering::Relaxed);
+    ///             });
+    ///         }
+    ///         Ok::<_, std::io::Error>(())
+    ///     }
+    /// })
+    /// .await
+    /// .unwrap();
+    ///
+    /// assert_eq!(total.load(std::sync::atomic::Ordering::Relaxed), 3);
+    /// # }
+    /// ```
+    pub async fn scope<F, Fut, T, E>(&self, name: &str, block: F) -> Result<T, E>
+    where
+        F: FnOnce(std::sync::Arc<crate::Context>) -> Fut,
+        Fut: std::future::Future<Output = Result<T, E>>,
+        E: std::fmt::Display,
+    {
+        let scope = self.child(name).build();
+        let guard = scope.drop_guard();
+
+        let result = block(scope.clone()).await;
+        match &result {
+            Ok(_) => {
+                scope.tasks.close_and_wait().await;
+                scope.cancel();
+            }
+            Err(e) => scope.cancel_with_reason(crate::CancelReason::Failure(format!(
+                "{}: {e}",
+                scope.path()
+            ))),
+        }
+        scope.close_and_wait().await;
+
+        guard.disarm();
+        result
+    }
+}
@@ -0,0 +1,153 @@
+/// Outcome of [`crate::Context::shutdown`]
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownReport {
+    /// How long the shutdown took, including the grace period
+    pub elapsed: std::time::Duration,
+    /// True if the grace period ran out before every task finished
+    pub timed_out: bool,
+    /// Paths of contexts whose tasks all finished within the grace period without panicking
+    pub finished: Vec<String>,
+    /// Contexts with tasks that panicked during shutdown
+    pub panicked: Vec<PanickedContext>,
+    /// Contexts whose tasks did not finish within the grace period
+    pub aborted: Vec<AbortedContext>,
+}
+
+/// A context whose tasks had to be force-aborted during shutdown
+#[derive(Debug, Clone)]
+pub struct AbortedContext {
+    pub name: String,
+    pub path: String,
+    pub aborted_tasks: usize,
+    /// The tasks that were still running, longest-running first
+    pub tasks: Vec<HungTask>,
+}
+
+/// A task that ignored cancellation and was force-aborted during shutdown
+#[derive(Debug, Clone)]
+pub struct HungTask {
+    /// Set for tasks spawned with `Context::spawn_named()`
+    pub name: Option<String>,
+    /// Source location (`file:line:column`) the task was spawned from
+    pub spawned_at: String,
+    pub running_for: std::time::Duration,
+}
+
+impl std::fmt::Display for HungTask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} spawned at {}, running for {}",
+            self.name.as_deref().unwrap_or("task"),
+            self.spawned_at,
+            crate::status::format_duration(self.running_for)
+        )
+    }
+}
+
+/// A context whose tasks panicked during shutdown
+#[derive(Debug, Clone)]
+pub struct PanickedContext {
+    pub path: String,
+    /// Messages of the panics (most recent last)
+    pub panics: Vec<String>,
+}
+
+/// Shutdown phase of a context, stored as a typed value
+#[cfg(feature = "tokio")]
+pub(crate) struct ShutdownPhase(pub(crate) u32);
+
+#[cfg(feature = "tokio")]
+impl crate::ContextBuilder {
+    /// Shut this context down in `phase` when its parent shuts down
+    ///
+    /// Lower phases go first, e.g. stop accepting requests before stopping workers
+    /// before closing storage:
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// const INGRESS: u32 = 0;
+    /// const WORKERS: u32 = 1;
+    /// const STORAGE: u32 = 2;
+    ///
+    /// let app = fastn_context::Context::new("app");
+    /// app.child("http").shutdown_phase(INGRESS).spawn(|ctx| async move { ctx.cancelled().await });
+    /// app.child("jobs").shutdown_phase(WORKERS).spawn(|ctx| async move { ctx.cancelled().await });
+    /// app.child("db").shutdown_phase(STORAGE).spawn(|ctx| async move { ctx.cancelled().await });
+    ///
+    /// app.shutdown(std::time::Duration::from_secs(5)).await;
+    /// # }
+    /// ```
+    pub fn shutdown_phase(self, phase: u32) -> Self {
+        self.configure(move |context| context.set(ShutdownPhase(phase)))
+    }
+}
+
+/// Shut the whole process down: cancel the global context, wait up to `grace` for
+/// the tree to drain (phase by phase, running `on_cancel()` cleanup hooks), then
+/// force-abort whatever is still running
+///
+/// Can be called from anywhere, e.g. an admin endpoint or a fatal error handler.
+/// With `#[fastn_context::main]` the body then returns as soon as it observes the
+/// cancellation. See [`crate::Context::shutdown`] for details.
+///
+/// ```rust,no_run
+/// # async fn run() {
+/// let report = fastn_context::shutdown(std::time::Duration::from_secs(10)).await;
+/// if !report.is_clean() {
+///     eprint!("{report}");
+/// }
+/// # }
+/// ```
+#[cfg(feature = "tokio")]
+pub async fn shutdown(grace: std::time::Duration) -> ShutdownReport {
+    crate::global().shutdown(grace).await
+}
+
+impl ShutdownReport {
+    /// True if every task finished within the grace period without panicking
+    pub fn is_clean(&self) -> bool {
+        !self.timed_out && self.aborted.is_empty() && self.panicked.is_empty()
+    }
+
+    /// Total number of tasks that had to be force-aborted
+    pub fn aborted_tasks(&self) -> usize {
+        self.aborted.iter().map(|ctx| ctx.aborted_tasks).sum()
+    }
+}
+
+impl std::fmt::Display for ShutdownReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_clean() {
+            return write!(
+                f,
+                "clean shutdown in {}",
+                crate::status::format_duration(self.elapsed)
+            );
+        }
+
+        writeln!(
+            f,
+            "shutdown in {}{}, {} context(s) finished in time",
+            crate::status::format_duration(self.elapsed),
+            if self.timed_out { " (timed out)" } else { "" },
+            self.finished.len()
+        )?;
+        for ctx in &self.panicked {
+            writeln!(f, "- {} panicked: {}", ctx.path, ctx.panics.join("; "))?;
+        }
+        for ctx in &self.aborted {
+            writeln!(
+                f,
+                "- {} force-aborted {} task(s)",
+                ctx.path, ctx.aborted_tasks
+            )?;
+            for task in &ctx.tasks {
+                writeln!(f, "  - {task}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+/// How repeated termination signals escalate, see [`install_signal_handlers_with`]
+///
+/// By default the first signal cancels the global context, a second one within 5s
+/// force-aborts all tracked tasks, and a third one within 5s of that exits the
+/// process with code 130.
+#[derive(Debug, Clone)]
+pub struct SignalPolicy {
+    /// A signal only escalates if it arrives within this window after the previous
+    /// one, otherwise it counts as a first signal again; None escalates regardless
+    /// of timing
+    pub escalation_window: Option<std::time::Duration>,
+    /// Force-abort all tracked tasks on the second signal; if false the second
+    /// signal exits the process right away
+    pub abort_on_second: bool,
+    /// Exit code of the process when a signal exits it
+    pub exit_code: i32,
+}
+
+impl Default for SignalPolicy {
+    fn default() -> Self {
+        SignalPolicy {
+            escalation_window: Some(std::time::Duration::from_secs(5)),
+            abort_on_second: true,
+            exit_code: 130,
+        }
+    }
+}
+
+/// Cancel the global context on SIGINT/SIGTERM (console control events such as
+/// Ctrl+C, Ctrl+Break and closing the console on Windows) or [`request_stop`]
+///
+/// The first signal cancels the global context so the application can shut down
+/// gracefully, repeated signals escalate as described by [`SignalPolicy`]. Must be
+/// called from within a tokio runtime.
+///
+/// Signals are handled once per process: this fails with
+/// [`std::io::ErrorKind::AlreadyExists`] if the handlers (or
+/// [`crate::Termination::install`], which escalates the same way) are already
+/// installed.
+pub fn install_signal_handlers() -> std::io::Result<()> {
+    install_signal_handlers_with(SignalPolicy::default())
+}
+
+/// [`install_signal_handlers`] with a custom escalation policy
+pub fn install_signal_handlers_with(policy: SignalPolicy) -> std::io::Result<()> {
+    handle_signals(policy, |signal| {
+        crate::global().cancel_with_reason(crate::CancelReason::Signal(signal.to_string()))
+    })
+}
+
+/// Set once termination signals are handled, see [`handle_signals`]
+static HANDLED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Call `first` on a first termination signal, and escalate repeated ones as
+/// described by `policy`
+///
+/// Only one caller per process succeeds, so two handlers can't race to escalate
+/// (or exit) differently.
+pub(crate) fn handle_signals(
+    policy: SignalPolicy,
+    first: impl Fn(&'static str) + Send + 'static,
+) -> std::io::Result<()> {
+    if HANDLED.swap(true, std::sync::atomic::Ordering::SeqCst) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            "termination signals are already handled (install_signal_handlers() and \
+             Termination::install() are exclusive)",
+        ));
+    }
+    let mut signals = match Signals::new() {
+        Ok(signals) => signals,
+        Err(e) => {
+            HANDLED.store(false, std::sync::atomic::Ordering::SeqCst);
+            return Err(e);
+        }
+    };
+
+    tokio::spawn(async move {
+        let mut stage = 0;
+        let mut previous: Option<std::time::Instant> = None;
+        loop {
+            let signal = signals.recv().await;
+
+            let now = std::time::Instant::now();
+            if let (Some(window), Some(previous)) = (policy.escalation_window, previous) {
+                if now - previous > window {
+                    stage = 0;
+                }
+            }
+            previous = Some(now);
+            stage += 1;
+
+            match stage {
+                1 => first(signal),
+                2 if policy.abort_on_second => {
+                    #[cfg(feature = "crash-dump")]
+                    crate::crash_dump::fatal(&format!("repeated {signal}, force-aborting tasks"));
+                    let aborted = crate::global().abort_subtree();
+                    eprintln!(
+                        "received {signal} during shutdown, force-aborted {aborted} task(s); \
+                         repeat to exit immediately"
+                    );
+                }
+                _ => {
+                    eprintln!("received {signal} during shutdown, exiting immediately");
+                    #[cfg(feature = "crash-dump")]
+                    crate::crash_dump::fatal(&format!("repeated {signal}, exiting immediately"));
+                    std::process::exit(policy.exit_code);
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Dump the rendered context tree on SIGUSR1 or SIGQUIT (like a JVM thread dump)
+///
+/// Dumps go to stderr, or are appended to `path` if given. The process keeps
+/// running. Must be called from within a tokio runtime.
+#[cfg(unix)]
+pub fn install_status_dump_handler(path: Option<std::path::PathBuf>) -> std::io::Result<()> {
+    let mut user1 = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::user_defined1())?;
+    let mut quit = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::quit())?;
+
+    tokio::spawn(async move {
+        loop {
+            let signal = tokio::select! {
+                _ = user1.recv() => "SIGUSR1",
+                _ = quit.recv() => "SIGQUIT",
+            };
+
+            let dump = format!(
+                "fastn-context status dump ({signal}, unix time {})\n{}\n",
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+                crate::status().render_tree()
+            );
+
+            match &path {
+                None => eprint!("{dump}"),
+                Some(path) => {
+                    let written = std::fs::OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(path)
+                        .and_then(|mut file| std::io::Write::write_all(&mut file, dump.as_bytes()));
+                    if let Err(e) = written {
+                        eprintln!("failed to write status dump to {}: {e}", path.display());
+                        eprint!("{dump}");
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Bumped by `request_stop()`, observed by every installed signal handler
+static STOP_REQUESTS: std::sync::LazyLock<tokio::sync::watch::Sender<u64>> =
+    std::sync::LazyLock::new(|| tokio::sync::watch::Sender::new(0));
+
+/// Deliver a stop request to the installed handlers, as if a termination signal
+/// arrived
+///
+/// Meant for stop requests that don't come as signals, e.g. `SERVICE_CONTROL_STOP`
+/// received by a Windows service control handler (such as the one registered with
+/// the `windows-service` crate). Requests made before the handlers are installed
+/// are not seen.
+pub fn request_stop() {
+    STOP_REQUESTS.send_modify(|requests| *requests += 1);
+}
+
+/// Termination signals, plus stop requests from `request_stop()`
+struct Signals {
+    platform: PlatformSignals,
+    stop: tokio::sync::watch::Receiver<u64>,
+}
+
+impl Signals {
+    fn new() -> std::io::Result<Self> {
+        Ok(Signals {
+            platform: PlatformSignals::new()?,
+            stop: STOP_REQUESTS.subscribe(),
+        })
+    }
+
+    /// Wait for the next termination signal or stop request, returning its name
+    async fn recv(&mut self) -> &'static str {
+        tokio::select! {
+            signal = self.platform.recv() => signal,
+            // The sender lives in a static and is never dropped
+            _ = self.stop.changed() => "stop request",
+        }
+    }
+}
+
+#[cfg(unix)]
+struct PlatformSignals {
+    interrupt: tokio::signal::unix::Signal,
+    terminate: tokio::signal::unix::Signal,
+}
+
+#[cfg(unix)]
+impl PlatformSignals {
+    fn new() -> std::io::Result<Self> {
+        Ok(PlatformSignals {
+            interrupt: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::interrupt())?,
+            terminate: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?,
+        })
+    }
+
+    async fn recv(&mut self) -> &'static str {
+        tokio::select! {
+            _ = self.interrupt.recv() => "SIGINT",
+            _ = self.terminate.recv() => "SIGTERM",
+        }
+    }
+}
+
+/// Console control events: Ctrl+C, Ctrl+Break, the console window being closed and
+/// system shutdown (the latter is only delivered to services)
+#[cfg(windows)]
+struct PlatformSignals {
+    ctrl_c: tokio::signal::windows::CtrlC,
+    ctrl_break: tokio::signal::windows::CtrlBreak,
+    ctrl_close: tokio::signal::windows::CtrlClose,
+    ctrl_shutdown: tokio::signal::windows::CtrlShutdown,
+}
+
+#[cfg(windows)]
+impl PlatformSignals {
+    fn new() -> std::io::Result<Self> {
+        Ok(PlatformSignals {
+            ctrl_c: tokio::signal::windows::ctrl_c()?,
+            ctrl_break: tokio::signal::windows::ctrl_break()?,
+            ctrl_close: tokio::signal::windows::ctrl_close()?,
+            ctrl_shutdown: tokio::signal::windows::ctrl_shutdown()?,
+        })
+    }
+
+    async fn recv(&mut self) -> &'static str {
+        tokio::select! {
+            _ = self.ctrl_c.recv() => "Ctrl+C",
+            _ = self.ctrl_break.recv() => "Ctrl+Break",
+            _ = self.ctrl_close.recv() => "console close",
+            _ = self.ctrl_shutdown.recv() => "system shutdown",
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+struct PlatformSignals;
+
+#[cfg(not(any(unix, windows)))]
+impl PlatformSignals {
+    fn new() -> std::io::Result<Self> {
+        Ok(PlatformSignals)
+    }
+
+    async fn recv(&mut self) -> &'static str {
+        let _ = tokio::signal::ctrl_c().await;
+        "Ctrl+C"
+    }
+}
@@ -0,0 +1,114 @@
+/// Executor that contexts can spawn tasks on, see [`crate::Context::spawn_with`]
+///
+/// Lets code running on other executors (async-std, smol, embassy, ...) keep its
+/// tasks in the context tree, with the same tracking, cancellation and shutdown
+/// as tasks spawned on tokio. Implemented for `tokio::runtime::Handle` (with the
+/// `tokio` feature) and for closures, e.g. `&|task| smol::spawn(task).detach()`.
+///
+/// Without the `tokio` feature this is the only way to spawn through a context.
+pub trait Spawner: Send + Sync {
+    /// Run `task` to completion in the background
+    fn spawn(&self, task: std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>);
+}
+
+#[cfg(feature = "tokio")]
+impl Spawner for tokio::runtime::Handle {
+    fn spawn(&self, task: std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>) {
+        drop(tokio::runtime::Handle::spawn(self, task));
+    }
+}
+
+impl<F> Spawner for F
+where
+    F: Fn(std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>) + Send + Sync,
+{
+    fn spawn(&self, task: std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>) {
+        self(task)
+    }
+}
+
+/// Why a task spawned with [`crate::Context::spawn_with`] has no result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskError {
+    /// The task was aborted (e.g. by `shutdown()`), or the executor dropped it
+    Aborted,
+    /// The task panicked with this message
+    Panicked(String),
+}
+
+impl std::fmt::Display for TaskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskError::Aborted => write!(f, "task aborted"),
+            TaskError::Panicked(message) => write!(f, "task panicked: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for TaskError {}
+
+/// Executor independent handle of a task spawned with [`crate::Context::spawn_with`]
+///
+/// Await it for the task's result. Dropping the handle detaches the task.
+pub struct TaskHandle<T> {
+    result: tokio::sync::oneshot::Receiver<Result<T, TaskError>>,
+    abort: tokio_util::sync::CancellationToken,
+}
+
+impl<T> TaskHandle<T> {
+    pub(crate) fn new(
+        result: tokio::sync::oneshot::Receiver<Result<T, TaskError>>,
+        abort: tokio_util::sync::CancellationToken,
+    ) -> Self {
+        TaskHandle { result, abort }
+    }
+
+    /// Abort the task, it stops at its next await point
+    pub fn abort(&self) {
+        self.abort.cancel();
+    }
+}
+
+impl<T> std::future::Future for TaskHandle<T> {
+    type Output = Result<T, TaskError>;
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        std::pin::Pin::new(&mut self.result)
+            .poll(cx)
+            .map(|result| result.unwrap_or(Err(TaskError::Aborted)))
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Turns a panic of the wrapped future into `TaskError::Panicked`
+    pub(crate) struct CatchUnwind<F> {
+        #[pin]
+        future: F,
+    }
+}
+
+impl<F> CatchUnwind<F> {
+    pub(crate) fn new(future: F) -> Self {
+        CatchUnwind { future }
+    }
+}
+
+impl<F: std::future::Future> std::future::Future for CatchUnwind<F> {
+    type Output = Result<F::Output, TaskError>;
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        let future = self.project().future;
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| future.poll(cx))) {
+            Ok(poll) => poll.map(Ok),
+            Err(payload) => std::task::Poll::Ready(Err(TaskError::Panicked(
+                crate::panic::payload_message(payload.as_ref()),
+            ))),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+/// Lifecycle state of a context
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ContextState {
+    Running,
+    /// `complete()` was called
+    Completed,
+    /// `fail()` was called with this error
+    Failed(String),
+    /// Cancelled before it completed or failed
+    Cancelled,
+}
+
+impl std::fmt::Display for ContextState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContextState::Running => write!(f, "running"),
+            ContextState::Completed => write!(f, "completed"),
+            ContextState::Failed(error) => write!(f, "failed: {error}"),
+            ContextState::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+/// Error recorded by `fail()`, along with the context it happened in
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContextError {
+    /// Path of the context that failed
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ContextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for ContextError {}
+
+/// Outcome and failure bookkeeping of a context
+#[derive(Default)]
+pub(crate) struct Lifecycle {
+    /// Explicit outcome recorded via `complete()` / `fail()` (first one wins)
+    outcome: Option<Outcome>,
+    /// First failure in this context's subtree
+    first_error: Option<ContextError>,
+    /// Cancel this context's subtree when anything in it fails
+    fail_fast: bool,
+    /// Most recent task panic messages
+    pub(crate) panics: std::collections::VecDeque<String>,
+    /// Total number of task panics
+    pub(crate) panic_count: u64,
+    /// Set via `ContextBuilder::on_panic()`, inherited when None
+    pub(crate) panic_policy: Option<crate::PanicPolicy>,
+    /// When the context's work finished (first of `complete()`, `fail()` or the end
+    /// of the task started by `ContextBuilder::spawn()`)
+    finished_at: Option<std::time::Instant>,
+}
+
+enum Outcome {
+    Completed,
+    Failed(String),
+}
+
+impl crate::Context {
+    /// Mark this context's work as successfully done
+    ///
+    /// Ignored if the context already completed or failed.
+    pub fn complete(&self) {
+        match self.lifecycle.lock() {
+            Ok(mut lifecycle) if lifecycle.outcome.is_none() => {
+                lifecycle.outcome = Some(Outcome::Completed);
+            }
+            _ => return,
+        }
+        self.mark_finished();
+        crate::status_stream::tree_changed();
+        crate::observer::notify(|observer| observer.on_completed(self, &ContextState::Completed));
+    }
+
+    /// Mark this context's work as failed
+    ///
+    /// The error is recorded as the first error of this context and every ancestor
+    /// that has none yet. The closest context created with
+    /// [`crate::ContextBuilder::fail_fast`] (this one included) and every fail-fast
+    /// context above it is cancelled, taking down the failing context's siblings and
+    /// descendants with it. Ignored if the context already completed or failed.
+    pub fn fail(&self, error: impl std::fmt::Display) {
+        let error = ContextError {
+            path: self.path().to_string(),
+            message: error.to_string(),
+        };
+
+        match self.lifecycle.lock() {
+            Ok(mut lifecycle) if lifecycle.outcome.is_none() => {
+                lifecycle.outcome = Some(Outcome::Failed(error.message.clone()));
+            }
+            _ => return,
+        }
+        self.mark_finished();
+
+        let mut current = Some(self.arc());
+        while let Some(context) = current {
+            let fail_fast = match context.lifecycle.lock() {
+                Ok(mut lifecycle) => {
+                    lifecycle.first_error.get_or_insert_with(|| error.clone());
+                    lifecycle.fail_fast
+                }
+                Err(_) => false,
+            };
+            if fail_fast {
+                context.cancel_with_reason(crate::CancelReason::Failure(error.to_string()));
+            }
+            current = context.parent();
+        }
+
+        crate::status_stream::tree_changed();
+        crate::observer::notify(|observer| {
+            observer.on_completed(self, &ContextState::Failed(error.message.clone()))
+        });
+    }
+
+    /// Record that this context's work finished (only the first call counts)
+    fn mark_finished(&self) {
+        let newly_finished = match self.lifecycle.lock() {
+            Ok(mut lifecycle) if lifecycle.finished_at.is_none() => {
+                lifecycle.finished_at = Some(std::time::Instant::now());
+                true
+            }
+            _ => false,
+        };
+
+        if newly_finished {
+            if let Some(lifetime) = self.total_duration() {
+                crate::histogram::record_lifetime(&self.name, lifetime);
+            }
+        }
+    }
+
+    /// How long this context ran, once its work finished
+    ///
+    /// Finished means `complete()` or `fail()` was called, or the task started by
+    /// [`crate::ContextBuilder::spawn`] returned.
+    pub fn total_duration(&self) -> Option<std::time::Duration> {
+        self.lifecycle
+            .lock()
+            .ok()
+            .and_then(|lifecycle| lifecycle.finished_at)
+            .map(|finished_at| finished_at - self.created_at)
+    }
+
+    /// First failure recorded in this context or any of its descendants
+    pub fn first_error(&self) -> Option<ContextError> {
+        self.lifecycle
+            .lock()
+            .ok()
+            .and_then(|lifecycle| lifecycle.first_error.clone())
+    }
+
+    /// Current lifecycle state (an explicit outcome takes precedence over cancellation)
+    pub fn state(&self) -> ContextState {
+        let outcome = match self.lifecycle.lock() {
+            Ok(lifecycle) => match &lifecycle.outcome {
+                Some(Outcome::Completed) => Some(ContextState::Completed),
+                Some(Outcome::Failed(error)) => Some(ContextState::Failed(error.clone())),
+                None => None,
+            },
+            Err(_) => None,
+        };
+
+        match outcome {
+            Some(state) => state,
+            None if self.is_cancelled() => ContextState::Cancelled,
+            None => ContextState::Running,
+        }
+    }
+}
+
+impl crate::ContextBuilder {
+    /// Cancel this context (and so all its children) as soon as anything in it fails
+    pub fn fail_fast(self) -> Self {
+        self.configure(|context| {
+            if let Ok(mut lifecycle) = context.lifecycle.lock() {
+                lifecycle.fail_fast = true;
+            }
+        })
+    }
+}
+
+/// Marks a child finished once the task started by `ContextBuilder::spawn()` ends
+///
+/// Dropped when the task completes, panics or is aborted alike.
+#[cfg(feature = "tokio")]
+pub(crate) struct FinishGuard {
+    pub(crate) context: std::sync::Arc<crate::Context>,
+}
+
+#[cfg(feature = "tokio")]
+impl Drop for FinishGuard {
+    fn drop(&mut self) {
+        self.context.mark_finished();
+        crate::rollup::roll_up(&self.context);
+        crate::status_stream::tree_changed();
+    }
+}
@@ -0,0 +1,186 @@
+//! statsd / DogStatsD exporter for context metrics
+//!
+//! Every interval the exporter walks the tree below its context and sends, per
+//! context, the live task count and the user counters and gauges, plus the
+//! lifetime percentiles of finished contexts by name (see [`crate::lifetimes`]).
+//! Counters are sent as deltas since the previous push.
+
+/// Largest UDP payload sent at once (metrics are batched up to this size)
+const MAX_PACKET: usize = 1400;
+
+/// Periodically pushes context metrics to a statsd endpoint
+///
+/// ```rust,no_run
+/// #[fastn_context::main]
+/// async fn main() {
+///     fastn_context::statsd::StatsdExporter::new("127.0.0.1:8125")
+///         .prefix("myapp")
+///         .interval(std::time::Duration::from_secs(10))
+///         .dogstatsd()
+///         .spawn(&fastn_context::global());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct StatsdExporter {
+    address: String,
+    prefix: String,
+    interval: std::time::Duration,
+    dogstatsd: bool,
+}
+
+impl StatsdExporter {
+    /// Export to the statsd server at `address` (e.g. `127.0.0.1:8125`), every 10s
+    /// with the `fastn` prefix
+    pub fn new(address: impl Into<String>) -> Self {
+        StatsdExporter {
+            address: address.into(),
+            prefix: "fastn".to_string(),
+            interval: std::time::Duration::from_secs(10),
+            dogstatsd: false,
+        }
+    }
+
+    /// Prefix of every metric name
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// How often metrics are pushed
+    pub fn interval(mut self, interval: std::time::Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Send the context path as a DogStatsD `context` tag instead of encoding it
+    /// in the metric name
+    pub fn dogstatsd(mut self) -> Self {
+        self.dogstatsd = true;
+        self
+    }
+
+    /// Export the subtree of `context` as a task of `context`, until it is cancelled
+    pub fn spawn(self, context: &crate::Context) -> tokio::task::JoinHandle<std::io::Result<()>> {
+        let exporter = context.clone();
+        context.spawn_named("statsd exporter", async move { self.run(&exporter).await })
+    }
+
+    async fn run(self, context: &crate::Context) -> std::io::Result<()> {
+        let socket = tokio::net::UdpSocket::bind("0.0.0.0:0").await?;
+        socket.connect(&self.address).await?;
+
+        let mut previous_counters = std::collections::HashMap::new();
+        let mut interval = tokio::time::interval(self.interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            tokio::select! {
+                _ = context.cancelled() => return Ok(()),
+                _ = interval.tick() => {}
+            }
+
+            let mut lines = Vec::new();
+            self.collect(&context.status(), &mut previous_counters, &mut lines);
+            self.collect_lifetimes(&mut previous_counters, &mut lines);
+
+            for packet in packets(&lines) {
+                // statsd is fire-and-forget, a dropped packet is not worth stopping for
+                let _ = socket.send(packet.as_bytes()).await;
+            }
+        }
+    }
+
+    fn collect(
+        &self,
+        ctx: &crate::ContextStatus,
+        previous_counters: &mut std::collections::HashMap<String, u64>,
+        lines: &mut Vec<String>,
+    ) {
+        lines.push(self.line(&ctx.path, "tasks", ctx.task_count as i64, "g"));
+        for (name, value) in &ctx.counters {
+            let key = format!("{}/{name}", ctx.path);
+            let delta = value.saturating_sub(previous_counters.insert(key, *value).unwrap_or(0));
+            if delta > 0 {
+                lines.push(self.line(&ctx.path, name, delta as i64, "c"));
+            }
+        }
+        for (name, value) in &ctx.gauges {
+            lines.push(self.line(&ctx.path, name, *value, "g"));
+        }
+
+        for child in &ctx.children {
+            self.collect(child, previous_counters, lines);
+        }
+    }
+
+    fn collect_lifetimes(
+        &self,
+        previous_counters: &mut std::collections::HashMap<String, u64>,
+        lines: &mut Vec<String>,
+    ) {
+        for (name, histogram) in crate::lifetimes() {
+            let finished = histogram.count;
+            let delta = finished.saturating_sub(
+                previous_counters
+                    .insert(name.clone(), finished)
+                    .unwrap_or(0),
+            );
+            let metric = format!("{}.lifetime.{}", self.prefix, sanitize(&name));
+            if delta > 0 {
+                lines.push(format!("{metric}.finished:{delta}|c"));
+            }
+            for (label, q) in [("p50", 0.5), ("p95", 0.95), ("p99", 0.99)] {
+                if let Some(latency) = histogram.percentile(q) {
+                    lines.push(format!("{metric}.{label}:{}|g", latency.as_millis()));
+                }
+            }
+        }
+    }
+
+    /// One metric line for `name` of the context at `path`
+    fn line(&self, path: &str, name: &str, value: i64, kind: &str) -> String {
+        if self.dogstatsd {
+            format!(
+                "{}.context.{}:{value}|{kind}|#context:{path}",
+                self.prefix,
+                sanitize(name)
+            )
+        } else {
+            format!(
+                "{}.context.{}.{}:{value}|{kind}",
+                self.prefix,
+                sanitize(&path.replace('/', ".")),
+                sanitize(name)
+            )
+        }
+    }
+}
+
+/// Replace characters statsd treats specially (`:`, `|`, `@`, `#`, whitespace)
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' | '.' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+/// Join metric lines into newline separated packets of at most `MAX_PACKET` bytes
+fn packets(lines: &[String]) -> Vec<String> {
+    let mut packets = Vec::new();
+    let mut packet = String::new();
+    for line in lines {
+        if !packet.is_empty() && packet.len() + 1 + line.len() > MAX_PACKET {
+            packets.push(std::mem::take(&mut packet));
+        }
+        if !packet.is_empty() {
+            packet.push('\n');
+        }
+        packet.push_str(line);
+    }
+    if !packet.is_empty() {
+        packets.push(packet);
+    }
+    packets
+}
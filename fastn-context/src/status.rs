@@ -1,18 +1,87 @@
 /// Status snapshot of the context tree
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Status {
     pub global_context: ContextStatus,
     pub persisted_contexts: Option<Vec<ContextStatus>>,
+    /// Lifetime histograms of finished contexts by name, see `lifetimes()`
+    pub lifetimes: std::collections::BTreeMap<String, crate::LatencyHistogram>,
+    /// The tokio runtime the snapshot was taken on, None outside of a runtime
+    pub runtime: Option<crate::RuntimeStatus>,
     pub timestamp: std::time::SystemTime,
 }
 
 /// Status information for a single context
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ContextStatus {
+    pub id: u64,
     pub name: String,
+    pub path: String,
+    pub state: crate::ContextState,
+    /// Labels attached via `ContextBuilder::label()`
+    pub labels: Vec<String>,
+    /// Not cancelled along with its parent, see `ContextBuilder::detach()`
+    pub detached: bool,
     pub is_cancelled: bool,
+    pub cancel_reason: Option<crate::CancelReason>,
+    /// Time since the context was created
+    #[deprecated(note = "use `elapsed`, or `total_duration` once the context finished")]
     pub duration: std::time::Duration,
+    pub created_at: std::time::SystemTime,
+    /// Time since the context was created
+    pub elapsed: std::time::Duration,
+    /// How long the context ran, once its work finished (see `Context::total_duration()`)
+    pub total_duration: Option<std::time::Duration>,
+    pub task_count: usize,
+    /// Time left until the effective deadline, if any
+    pub deadline_remaining: Option<std::time::Duration>,
+    pub counters: std::collections::BTreeMap<String, u64>,
+    pub gauges: std::collections::BTreeMap<String, i64>,
+    /// Run history, for contexts created by `spawn_interval()` / `spawn_cron()`
+    pub schedule: Option<crate::ScheduleStatus>,
+    /// Set if the context limits its concurrent tasks
+    pub concurrency: Option<crate::ConcurrencyStatus>,
+    /// Set if the context is a rate limiter
+    pub rate_limit: Option<crate::RateLimitStatus>,
+    /// Set if the context consumes a stream, see `Context::consume()`
+    pub consumer: Option<crate::ConsumerStatus>,
+    /// Set if the context runs an actor, see `Context::actor()`
+    pub mailbox: Option<crate::MailboxStatus>,
+    /// Stats of completed children, set if the context rolls them up
+    pub roll_up: Option<crate::RollUpStatus>,
+    /// Health the context reported about itself via `Context::set_health()`
+    pub health: Option<crate::HealthStatus>,
+    /// Set once the context reports readiness via `ready()` / `not_ready()`
+    pub readiness: Option<crate::ReadinessStatus>,
+    /// Set once the context heartbeats or expects heartbeats
+    pub heartbeat: Option<crate::HeartbeatStatus>,
+    /// Messages of the most recent task panics
+    pub panics: Vec<String>,
+    pub progress: Option<crate::Progress>,
+    pub message: Option<String>,
+    /// Most recent events recorded via `Context::event()`, oldest first
+    pub events: Vec<crate::ContextEvent>,
+    pub processes: Vec<crate::ProcessStatus>,
+    /// Trace ID linked to this context (e.g. via OpenTelemetry), if any
+    pub trace_id: Option<String>,
     pub children: Vec<ContextStatus>,
+    /// Children left out by the limits or filter of `status_with()`
+    pub omitted_children: usize,
+}
+
+/// Limits for [`status_with`], to get a cheap overview of a huge tree
+#[derive(Debug, Clone, Default)]
+pub struct StatusOptions {
+    /// Levels below the root to include (0 for the root only), None for all
+    pub max_depth: Option<usize>,
+    /// Children listed per context, None for all
+    pub max_children: Option<usize>,
+    /// Only include contexts whose name matches this glob (e.g. `import-*`), along
+    /// with their ancestors
+    pub name_filter: Option<String>,
+    /// Only include contexts carrying this label, along with their ancestors
+    pub label_filter: Option<String>,
 }
 
 /// Global storage for persisted contexts (circular buffer)
@@ -38,7 +107,10 @@ pub fn add_persisted_context(context_status: ContextStatus) {
     // Log as trace event
     println!(
         "TRACE: {} completed in {:?}",
-        context_status.name, context_status.duration
+        context_status.name,
+        context_status
+            .total_duration
+            .unwrap_or(context_status.elapsed)
     );
 }
 
@@ -47,6 +119,19 @@ pub fn status() -> Status {
     Status {
         global_context: crate::context::global().status(),
         persisted_contexts: None,
+        lifetimes: crate::lifetimes(),
+        runtime: crate::RuntimeStatus::current(),
+        timestamp: std::time::SystemTime::now(),
+    }
+}
+
+/// Get a status snapshot of the part of the context tree allowed by `options`
+pub fn status_with(options: &StatusOptions) -> Status {
+    Status {
+        global_context: crate::context::global().status_with(options),
+        persisted_contexts: None,
+        lifetimes: crate::lifetimes(),
+        runtime: crate::RuntimeStatus::current(),
         timestamp: std::time::SystemTime::now(),
     }
 }
@@ -62,6 +147,8 @@ pub fn status_with_latest() -> Status {
     Status {
         global_context: crate::context::global().status(),
         persisted_contexts: persisted,
+        lifetimes: crate::lifetimes(),
+        runtime: crate::RuntimeStatus::current(),
         timestamp: std::time::SystemTime::now(),
     }
 }
@@ -70,6 +157,9 @@ impl std::fmt::Display for Status {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "fastn Context Status")?;
         writeln!(f, "Snapshot: {:?}", self.timestamp)?;
+        if let Some(runtime) = &self.runtime {
+            writeln!(f, "Runtime: {runtime}")?;
+        }
         writeln!(f)?;
 
         Self::display_context(&self.global_context, f, 0)?;
@@ -77,25 +167,17 @@ impl std::fmt::Display for Status {
         // Show persisted contexts if included
         if let Some(persisted) = &self.persisted_contexts {
             if !persisted.is_empty() {
-            writeln!(f, "\nRecent completed contexts (last {}):", persisted.len())?;
-            for ctx in persisted {
-                let duration_str = if ctx.duration.as_secs() > 60 {
-                    format!(
-                        "{}m {}s",
-                        ctx.duration.as_secs() / 60,
-                        ctx.duration.as_secs() % 60
-                    )
-                } else {
-                    format!("{:.1}s", ctx.duration.as_secs_f64())
-                };
-
-                let status_str = if ctx.is_cancelled {
-                    "cancelled"
-                } else {
-                    "completed"
-                };
-                writeln!(f, "- {} ({}, {})", ctx.name, duration_str, status_str)?;
+                writeln!(f, "\nRecent completed contexts (last {}):", persisted.len())?;
+                for ctx in persisted {
+                    writeln!(f, "- {} ({})", ctx.name, ctx.details("completed"))?;
+                }
             }
+        }
+
+        if !self.lifetimes.is_empty() {
+            writeln!(f, "\nContext lifetimes:")?;
+            for (name, histogram) in &self.lifetimes {
+                writeln!(f, "- {name} ({} finished, {histogram})", histogram.count)?;
             }
         }
 
@@ -110,30 +192,18 @@ impl Status {
         depth: usize,
     ) -> std::fmt::Result {
         let indent = "  ".repeat(depth);
-        let status_icon = if ctx.is_cancelled { "❌" } else { "✅" };
-
-        let duration_str = if ctx.duration.as_secs() > 60 {
-            format!(
-                "{}m {}s",
-                ctx.duration.as_secs() / 60,
-                ctx.duration.as_secs() % 60
-            )
-        } else {
-            format!("{:.1}s", ctx.duration.as_secs_f64())
+        let status_icon = match ctx.state {
+            crate::ContextState::Running | crate::ContextState::Completed => "✅",
+            crate::ContextState::Failed(_) | crate::ContextState::Cancelled => "❌",
         };
 
         writeln!(
             f,
-            "{}{} {} ({}, {})",
+            "{}{} {} ({})",
             indent,
             status_icon,
             ctx.name,
-            duration_str,
-            if ctx.is_cancelled {
-                "cancelled"
-            } else {
-                "active"
-            }
+            ctx.details("active")
         )?;
 
         for child in &ctx.children {
@@ -142,4 +212,218 @@ impl Status {
 
         Ok(())
     }
+
+    /// Render the context tree as an indented ASCII tree
+    ///
+    /// ```text
+    /// global (2m 15s, active, 1 task)
+    /// ├── http (2m 14s, active)
+    /// │   └── request-42 (0.3s, active, 2 tasks)
+    /// └── worker (1.2s, cancelled: shutdown)
+    /// ```
+    pub fn render_tree(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!(
+            "{} ({})\n",
+            self.global_context.name,
+            self.global_context.details("active")
+        ));
+        Self::render_children(&self.global_context, "", &mut out);
+        out
+    }
+
+    fn render_children(ctx: &ContextStatus, prefix: &str, out: &mut String) {
+        for (i, child) in ctx.children.iter().enumerate() {
+            let is_last = i + 1 == ctx.children.len();
+            let (branch, continuation) = if is_last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+
+            out.push_str(&format!(
+                "{prefix}{branch}{} ({})\n",
+                child.name,
+                child.details("active")
+            ));
+            Self::render_children(child, &format!("{prefix}{continuation}"), out);
+        }
+    }
+}
+
+impl ContextStatus {
+    /// Comma separated summary: age (or run time once finished), state, labels, tasks, deadline, concurrency, rate,
+    /// consumer, mailbox, schedule, roll-up, health, readiness, staleness, panics, progress, last event, processes, omitted
+    /// children and metrics
+    pub(crate) fn details(&self, alive_state: &str) -> String {
+        let mut details = vec![match self.total_duration {
+            Some(total) => format!("took {}", format_duration(total)),
+            None => format_duration(self.elapsed),
+        }];
+
+        details.push(match (&self.state, &self.cancel_reason) {
+            (crate::ContextState::Running, _) if self.total_duration.is_some() => {
+                "finished".to_string()
+            }
+            (crate::ContextState::Running, _) => alive_state.to_string(),
+            (crate::ContextState::Cancelled, Some(reason)) => format!("cancelled: {reason}"),
+            (state, _) => state.to_string(),
+        });
+
+        if !self.labels.is_empty() {
+            details.push(format!("[{}]", self.labels.join(" ")));
+        }
+
+        if self.detached {
+            details.push("detached".to_string());
+        }
+
+        match self.task_count {
+            0 => {}
+            1 => details.push("1 task".to_string()),
+            n => details.push(format!("{n} tasks")),
+        }
+
+        if let Some(remaining) = self.deadline_remaining {
+            details.push(format!("deadline in {}", format_duration(remaining)));
+        }
+
+        if let Some(concurrency) = &self.concurrency {
+            details.push(format!(
+                "{}/{} in flight",
+                concurrency.in_flight, concurrency.limit
+            ));
+            if concurrency.queued > 0 {
+                details.push(format!("{} queued", concurrency.queued));
+            }
+        }
+
+        if let Some(rate_limit) = &self.rate_limit {
+            details.push(format!(
+                "{}/{} per sec",
+                rate_limit.current_rate, rate_limit.permits_per_sec
+            ));
+        }
+
+        if let Some(consumer) = &self.consumer {
+            details.push(format!(
+                "{} messages in flight, {} processed",
+                consumer.in_flight, consumer.processed
+            ));
+            if consumer.failed > 0 {
+                details.push(format!("{} failed", consumer.failed));
+            }
+            if let Some(lag) = consumer.lag {
+                details.push(format!("lag {lag}"));
+            }
+        }
+
+        if let Some(mailbox) = &self.mailbox {
+            details.push(format!(
+                "{} queued, {} handled",
+                mailbox.queued, mailbox.processed
+            ));
+            if mailbox.discarded > 0 {
+                details.push(format!("{} discarded", mailbox.discarded));
+            }
+            if mailbox.closed {
+                details.push("mailbox closed".to_string());
+            }
+        }
+
+        if let Some(schedule) = &self.schedule {
+            details.push(format!("{} runs", schedule.runs));
+            if let Some(since) = schedule.since_last_run {
+                details.push(format!("last run {} ago", format_duration(since)));
+            }
+            if let Some(error) = &schedule.last_error {
+                details.push(format!("last error: {error}"));
+            }
+            if let Some(next) = schedule.next_run_in {
+                details.push(format!("next run in {}", format_duration(next)));
+            }
+        }
+
+        if let Some(roll_up) = &self.roll_up {
+            details.push(format!("{} completed", roll_up.completed));
+            if roll_up.errors > 0 {
+                details.push(format!("{} errors", roll_up.errors));
+            }
+            if roll_up.completed > 0 {
+                details.push(roll_up.latency.to_string());
+            }
+        }
+
+        if let Some(health) = &self.health {
+            match (&health.health, &health.reason) {
+                (crate::Health::Healthy, _) => {}
+                (health, Some(reason)) => details.push(format!("{health}: {reason}")),
+                (health, None) => details.push(health.to_string()),
+            }
+        }
+
+        if let Some(readiness) = &self.readiness {
+            match &readiness.reason {
+                _ if readiness.ready => {}
+                Some(reason) => details.push(format!("not ready: {reason}")),
+                None => details.push("not ready".to_string()),
+            }
+        }
+
+        if let Some(heartbeat) = &self.heartbeat {
+            if heartbeat.stale {
+                details.push(format!(
+                    "stale: no heartbeat for {}",
+                    format_duration(heartbeat.since_last)
+                ));
+            }
+        }
+
+        match self.panics.last() {
+            None => {}
+            Some(last) if self.panics.len() == 1 => details.push(format!("panicked: {last}")),
+            Some(last) => details.push(format!("{} panics, last: {last}", self.panics.len())),
+        }
+
+        if let Some(progress) = &self.progress {
+            details.push(progress.to_string());
+        }
+        if let Some(message) = &self.message {
+            details.push(format!("{message:?}"));
+        }
+
+        if let Some(event) = self.events.last() {
+            details.push(format!("last event: {:?}", event.message));
+        }
+
+        match self.processes.len() {
+            0 => {}
+            1 => details.push("1 process".to_string()),
+            n => details.push(format!("{n} processes")),
+        }
+
+        match self.omitted_children {
+            0 => {}
+            1 => details.push("1 child not shown".to_string()),
+            n => details.push(format!("{n} children not shown")),
+        }
+
+        for (name, value) in &self.counters {
+            details.push(format!("{name}={value}"));
+        }
+        for (name, value) in &self.gauges {
+            details.push(format!("{name}={value}"));
+        }
+
+        details.join(", ")
+    }
+}
+
+/// Format a duration as `1m 5s` above a minute, `12.3s` otherwise
+pub(crate) fn format_duration(duration: std::time::Duration) -> String {
+    if duration.as_secs() > 60 {
+        format!("{}m {}s", duration.as_secs() / 60, duration.as_secs() % 60)
+    } else {
+        format!("{:.1}s", duration.as_secs_f64())
+    }
 }
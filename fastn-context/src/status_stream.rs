@@ -0,0 +1,56 @@
+/// Signalled whenever the context tree changes (stores a permit if nobody waits)
+static TREE_CHANGED: tokio::sync::Notify = tokio::sync::Notify::const_new();
+
+/// Sender behind `status_stream()` and the task publishing into it, created on
+/// first subscription
+#[cfg(feature = "tokio")]
+static PUBLISHER: std::sync::Mutex<Option<Publisher>> = std::sync::Mutex::new(None);
+
+#[cfg(feature = "tokio")]
+struct Publisher {
+    sender: tokio::sync::watch::Sender<crate::Status>,
+    /// Dies with the runtime it was spawned on, restarted by the next subscriber
+    task: tokio::task::JoinHandle<()>,
+}
+
+/// Minimum time between two published snapshots (changes in between are coalesced)
+#[cfg(feature = "tokio")]
+const MIN_SNAPSHOT_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Record that the context tree changed (contexts created/cancelled, tasks started/finished)
+pub(crate) fn tree_changed() {
+    TREE_CHANGED.notify_one();
+}
+
+/// Subscribe to status snapshots, published whenever the context tree changes
+///
+/// Must be called inside a tokio runtime: the background task publishing snapshots
+/// runs on the runtime of the first subscriber, and is restarted on the caller's
+/// runtime if that one has shut down since.
+#[cfg(feature = "tokio")]
+pub fn status_stream() -> tokio::sync::watch::Receiver<crate::Status> {
+    let mut publisher = PUBLISHER.lock().unwrap_or_else(|e| e.into_inner());
+    match &mut *publisher {
+        Some(publisher) if !publisher.task.is_finished() => publisher.sender.subscribe(),
+        Some(publisher) => {
+            publisher.sender.send_replace(crate::status());
+            publisher.task = tokio::spawn(publish_snapshots(publisher.sender.clone()));
+            publisher.sender.subscribe()
+        }
+        None => {
+            let (sender, receiver) = tokio::sync::watch::channel(crate::status());
+            let task = tokio::spawn(publish_snapshots(sender.clone()));
+            *publisher = Some(Publisher { sender, task });
+            receiver
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+async fn publish_snapshots(sender: tokio::sync::watch::Sender<crate::Status>) {
+    loop {
+        TREE_CHANGED.notified().await;
+        sender.send_replace(crate::status());
+        tokio::time::sleep(MIN_SNAPSHOT_INTERVAL).await;
+    }
+}
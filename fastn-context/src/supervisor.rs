@@ -0,0 +1,142 @@
+/// When a supervised task is restarted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Restart whenever the task exits, successfully or not
+    Always,
+    /// Restart only if the task returns an error or panics
+    OnFailure,
+    /// Run the task once
+    Never,
+}
+
+/// Owns a set of named child tasks and restarts them according to a [`RestartPolicy`]
+///
+/// Every task gets its own child context under the supervisor's context. The
+/// `restarts` counter of that context counts restarts, its message holds the last
+/// error. Once `max_restarts` is exhausted the task context is marked failed.
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// let ctx = fastn_context::Context::new("app");
+/// let supervisor = fastn_context::Supervisor::new(&ctx, "workers")
+///     .policy(fastn_context::RestartPolicy::OnFailure)
+///     .max_restarts(3);
+///
+/// supervisor.spawn("poller", |task_ctx| async move {
+///     task_ctx.cancelled().await;
+///     Ok::<_, std::io::Error>(())
+/// });
+/// # ctx.shutdown(std::time::Duration::from_secs(1)).await;
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Supervisor {
+    context: std::sync::Arc<crate::Context>,
+    policy: RestartPolicy,
+    initial_backoff: std::time::Duration,
+    max_backoff: std::time::Duration,
+    max_restarts: Option<u32>,
+}
+
+impl Supervisor {
+    /// Create a supervisor with its own child context of `parent`
+    ///
+    /// Defaults to [`RestartPolicy::OnFailure`], unlimited restarts and a backoff
+    /// doubling from 100ms up to 30s.
+    pub fn new(parent: &crate::Context, name: &str) -> Supervisor {
+        Supervisor {
+            context: parent.child(name).build(),
+            policy: RestartPolicy::OnFailure,
+            initial_backoff: std::time::Duration::from_millis(100),
+            max_backoff: std::time::Duration::from_secs(30),
+            max_restarts: None,
+        }
+    }
+
+    pub fn policy(mut self, policy: RestartPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Delay before the first restart, doubled for every further restart up to `max`
+    pub fn backoff(mut self, initial: std::time::Duration, max: std::time::Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Give up after this many restarts
+    pub fn max_restarts(mut self, max_restarts: u32) -> Self {
+        self.max_restarts = Some(max_restarts);
+        self
+    }
+
+    /// The supervisor's context, cancel it to stop every supervised task
+    pub fn context(&self) -> &std::sync::Arc<crate::Context> {
+        &self.context
+    }
+
+    /// Start supervising a task, `task` is called again for every restart
+    ///
+    /// The returned handle finishes once the task is no longer restarted.
+    #[track_caller]
+    pub fn spawn<F, Fut, E>(&self, name: &str, task: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn(std::sync::Arc<crate::Context>) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<(), E>> + Send + 'static,
+        E: std::fmt::Display + Send + 'static,
+    {
+        let supervisor = self.clone();
+        let task_ctx = self.context.child(name).build();
+
+        self.context.spawn(async move {
+            let restarts = task_ctx.counter("restarts");
+            let mut backoff = supervisor.initial_backoff;
+
+            loop {
+                let error = match task_ctx.spawn(task(task_ctx.clone())).await {
+                    Ok(Ok(())) => None,
+                    Ok(Err(e)) => Some(e.to_string()),
+                    Err(e) if e.is_panic() => Some(format!(
+                        "panicked: {}",
+                        crate::panic::payload_message(e.into_panic().as_ref())
+                    )),
+                    Err(_) => return, // aborted during shutdown
+                };
+
+                if task_ctx.is_cancelled() {
+                    return;
+                }
+
+                let restart = match (&error, supervisor.policy) {
+                    (_, RestartPolicy::Never) => false,
+                    (None, RestartPolicy::OnFailure) => false,
+                    (_, RestartPolicy::Always) | (Some(_), RestartPolicy::OnFailure) => true,
+                };
+                let exhausted = supervisor
+                    .max_restarts
+                    .is_some_and(|max| restarts.get() >= u64::from(max));
+
+                if !restart || exhausted {
+                    match error {
+                        Some(error) => task_ctx.fail(error),
+                        None => task_ctx.complete(),
+                    }
+                    return;
+                }
+
+                if let Some(error) = &error {
+                    task_ctx.set_message(&format!("last error: {error}"));
+                }
+
+                tokio::select! {
+                    _ = task_ctx.cancelled() => return,
+                    _ = tokio::time::sleep(backoff) => {}
+                }
+                backoff = (backoff * 2).min(supervisor.max_backoff);
+                restarts.inc();
+            }
+        })
+    }
+}
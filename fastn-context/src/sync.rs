@@ -0,0 +1,145 @@
+/// Cancellation of a context as seen from synchronous code
+///
+/// For legacy blocking code and threads not running on an async runtime: poll
+/// [`CancelFlag::is_cancelled`] or park the thread with
+/// [`CancelFlag::park_until_cancelled`]. Get one with
+/// [`crate::Context::cancel_flag`].
+#[derive(Clone)]
+pub struct CancelFlag {
+    token: tokio_util::sync::CancellationToken,
+}
+
+impl CancelFlag {
+    /// Check if the context is cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Block the current thread until the context is cancelled or `timeout`
+    /// passes, returns whether it is cancelled
+    pub fn park_until_cancelled(&self, timeout: std::time::Duration) -> bool {
+        let deadline = std::time::Instant::now().checked_add(timeout);
+        self.park(deadline)
+    }
+
+    /// Park until cancelled or `deadline` (None: no deadline)
+    fn park(&self, deadline: Option<std::time::Instant>) -> bool {
+        let waker =
+            std::task::Waker::from(std::sync::Arc::new(ThreadWaker(std::thread::current())));
+        let mut cx = std::task::Context::from_waker(&waker);
+        let mut cancelled = std::pin::pin!(self.token.cancelled());
+
+        loop {
+            if std::future::Future::poll(cancelled.as_mut(), &mut cx).is_ready() {
+                return true;
+            }
+            match deadline {
+                Some(deadline) => {
+                    let now = std::time::Instant::now();
+                    if now >= deadline {
+                        return self.is_cancelled();
+                    }
+                    std::thread::park_timeout(deadline - now);
+                }
+                None => std::thread::park(),
+            }
+        }
+    }
+}
+
+/// Unparks the waiting thread when the cancellation future is woken
+struct ThreadWaker(std::thread::Thread);
+
+impl std::task::Wake for ThreadWaker {
+    fn wake(self: std::sync::Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &std::sync::Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Work done outside of any async runtime (e.g. on a `std::thread`), tracked by a
+/// context until finished, see [`crate::Context::sync_task`]
+///
+/// Counts as a live task of the context, so status shows it and
+/// [`crate::Context::shutdown`] waits for it. Dropping it marks the work done, as
+/// does [`SyncTask::finish`].
+#[must_use = "the work counts as done as soon as the SyncTask is dropped"]
+pub struct SyncTask {
+    flag: CancelFlag,
+    _task: crate::task::ExternalTask,
+}
+
+impl SyncTask {
+    /// Check if the work should stop
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.is_cancelled()
+    }
+
+    /// Cancellation flag of the context, for code that only needs to observe it
+    pub fn cancel_flag(&self) -> CancelFlag {
+        self.flag.clone()
+    }
+
+    /// Mark the work done
+    pub fn finish(self) {}
+}
+
+impl crate::Context {
+    /// Synchronous handle to this context's cancellation
+    pub fn cancel_flag(&self) -> CancelFlag {
+        CancelFlag {
+            token: self.cancellation_token(),
+        }
+    }
+
+    /// Block the current thread until this context is cancelled
+    ///
+    /// For synchronous code only: calling it from async code blocks a runtime
+    /// worker, use [`crate::Context::cancelled`] there.
+    pub fn block_until_cancelled(&self) {
+        self.cancel_flag().park(None);
+    }
+
+    /// Track work running outside of the async runtime until the returned
+    /// [`SyncTask`] is dropped
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let ctx = fastn_context::Context::new("app");
+    /// let task = ctx.sync_task("legacy-worker");
+    ///
+    /// std::thread::spawn(move || {
+    ///     while !task.is_cancelled() {
+    ///         // blocking work in small steps
+    ///         std::thread::sleep(std::time::Duration::from_millis(10));
+    ///     }
+    ///     task.finish();
+    /// });
+    ///
+    /// let report = ctx.shutdown(std::time::Duration::from_secs(1)).await;
+    /// assert!(report.is_clean());
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn sync_task(&self, name: &str) -> SyncTask {
+        match self.try_sync_task(name) {
+            Ok(task) => task,
+            Err(quota) => panic!("task refused: {quota}"),
+        }
+    }
+
+    /// Like [`crate::Context::sync_task`], but fails instead of panicking over the
+    /// `max_tracked_tasks()` quota
+    pub(crate) fn try_sync_task(&self, name: &str) -> Result<SyncTask, crate::QuotaExceeded> {
+        Ok(SyncTask {
+            flag: self.cancel_flag(),
+            _task: self
+                .tasks
+                .track_external(self.reserve_task(Some(name.to_string()))?),
+        })
+    }
+}
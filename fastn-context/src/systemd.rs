@@ -0,0 +1,104 @@
+//! systemd service integration via the `sd_notify` protocol
+//!
+//! With `Type=notify` (and optionally `WatchdogSec=`) in the unit, [`install`]
+//! tells systemd when the service is ready and stopping, and keeps the watchdog fed
+//! while no context is stale (see [`crate::Context::heartbeat`]), so systemd
+//! restarts a service whose workers hang. Outside of systemd (no `NOTIFY_SOCKET`)
+//! everything here is a no-op.
+
+/// Send a raw `sd_notify` message (e.g. `STATUS=loading cache`) to systemd
+///
+/// Returns `Ok(false)` when not running under systemd.
+pub fn notify(state: &str) -> std::io::Result<bool> {
+    let Some(path) = std::env::var_os("NOTIFY_SOCKET") else {
+        return Ok(false);
+    };
+
+    let socket = std::os::unix::net::UnixDatagram::unbound()?;
+    let bytes = std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str());
+    match bytes.strip_prefix(b"@") {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        Some(name) => {
+            let address =
+                <std::os::unix::net::SocketAddr as std::os::linux::net::SocketAddrExt>::from_abstract_name(name)?;
+            socket.send_to_addr(state.as_bytes(), &address)?;
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        Some(_) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "abstract notify socket",
+            ));
+        }
+        None => {
+            socket.send_to(state.as_bytes(), &path)?;
+        }
+    }
+    Ok(true)
+}
+
+/// Watchdog interval requested by systemd for this process (`WATCHDOG_USEC`)
+pub fn watchdog_interval() -> Option<std::time::Duration> {
+    let usec: u64 = std::env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    match std::env::var("WATCHDOG_PID") {
+        Ok(pid) if pid.parse() != Ok(std::process::id()) => None,
+        _ => Some(std::time::Duration::from_micros(usec)),
+    }
+}
+
+/// Report the service lifecycle of the global context to systemd
+///
+/// - `READY=1` once the global context reports [`crate::Context::ready`]
+/// - `STOPPING=1` once the global context is cancelled (shutdown begins)
+/// - `WATCHDOG=1` every half `WatchdogSec=`, unless some context is stale
+///
+/// Does nothing when not running under systemd. Must be called from within a
+/// tokio runtime.
+pub fn install() {
+    if std::env::var_os("NOTIFY_SOCKET").is_none() {
+        return;
+    }
+
+    let global = crate::global();
+    if let Some(interval) = watchdog_interval() {
+        let context = global.clone();
+        global.spawn_named("systemd watchdog", async move {
+            feed_watchdog(&context, interval / 2).await
+        });
+    }
+
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = crate::readiness::wait_for_readiness(|| global.is_ready()) => {
+                send("READY=1");
+            }
+            _ = global.cancelled() => {}
+        }
+
+        global.cancelled().await;
+        send("STOPPING=1");
+    });
+}
+
+async fn feed_watchdog(context: &crate::Context, every: std::time::Duration) {
+    let mut interval = tokio::time::interval(every);
+    loop {
+        tokio::select! {
+            _ = context.cancelled() => return,
+            _ = interval.tick() => {}
+        }
+
+        let status = crate::status();
+        match status.stale_contexts().first() {
+            None => send("WATCHDOG=1"),
+            // Starve the watchdog so systemd restarts the hung service
+            Some(stale) => send(&format!("STATUS=stale context: {}", stale.path)),
+        }
+    }
+}
+
+fn send(state: &str) {
+    if let Err(e) = notify(state) {
+        eprintln!("failed to notify systemd ({state}): {e}");
+    }
+}
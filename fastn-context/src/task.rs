@@ -0,0 +1,316 @@
+/// Registry of tasks spawned through a context
+#[derive(Default)]
+pub(crate) struct TaskRegistry {
+    next_id: std::sync::atomic::AtomicU64,
+    tasks: std::sync::Mutex<std::collections::HashMap<u64, TrackedTask>>,
+
+    /// Notified when the last live task finished, lets callers wait for them
+    idle: tokio::sync::Notify,
+}
+
+/// Bookkeeping for a single live task
+// Only read when `shutdown()` force-aborts, which needs the `tokio` feature
+#[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+struct TrackedTask {
+    /// Set right after spawning (None only while the task is being registered)
+    abort_handle: Option<Abort>,
+    name: Option<String>,
+    spawned_at: &'static std::panic::Location<'static>,
+    started: std::time::Instant,
+}
+
+/// How a tracked task is force-aborted
+// Only read when `shutdown()` force-aborts, which needs the `tokio` feature
+#[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+enum Abort {
+    /// Task spawned on a tokio runtime
+    #[cfg(feature = "tokio")]
+    Task(tokio::task::AbortHandle),
+    /// Task spawned on another executor, see [`crate::Spawner`]
+    Token(tokio_util::sync::CancellationToken),
+}
+
+impl Abort {
+    #[cfg(feature = "tokio")]
+    fn abort(&self) {
+        match self {
+            Abort::Task(handle) => handle.abort(),
+            Abort::Token(token) => token.cancel(),
+        }
+    }
+}
+
+/// Work tracked by [`TaskRegistry::track_external`], done when dropped
+pub(crate) struct ExternalTask {
+    _guard: TaskGuard,
+}
+
+/// Removes the task from its registry when the task future is dropped
+///
+/// Created by [`TaskRegistry::register`] right before the task is spawned.
+pub(crate) struct TaskGuard {
+    registry: std::sync::Arc<TaskRegistry>,
+    id: u64,
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        if let Ok(mut tasks) = self.registry.tasks.lock() {
+            tasks.remove(&self.id);
+            if tasks.is_empty() {
+                self.registry.idle.notify_waiters();
+            }
+        }
+        crate::status_stream::tree_changed();
+    }
+}
+
+impl TaskRegistry {
+    /// Spawn a task that is tracked here (as `guard`, see [`TaskRegistry::register`])
+    /// until it finishes or is aborted, on `runtime` or the current runtime
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    pub(crate) fn spawn<F>(
+        self: &std::sync::Arc<Self>,
+        guard: TaskGuard,
+        runtime: Option<&tokio::runtime::Handle>,
+        task: F,
+    ) -> tokio::task::JoinHandle<F::Output>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let id = guard.id;
+
+        let task = async move {
+            let _guard = guard;
+            task.await
+        };
+        let handle = match runtime {
+            Some(runtime) => runtime.spawn(task),
+            None => tokio::spawn(task),
+        };
+
+        self.set_abort_handle(id, Abort::Task(handle.abort_handle()));
+        handle
+    }
+
+    /// Like [`TaskRegistry::spawn`], for a `!Send` task on the current `LocalSet`
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    pub(crate) fn spawn_local<F>(
+        self: &std::sync::Arc<Self>,
+        guard: TaskGuard,
+        task: F,
+    ) -> tokio::task::JoinHandle<F::Output>
+    where
+        F: std::future::Future + 'static,
+        F::Output: 'static,
+    {
+        let id = guard.id;
+
+        let handle = tokio::task::spawn_local(async move {
+            let _guard = guard;
+            task.await
+        });
+
+        self.set_abort_handle(id, Abort::Task(handle.abort_handle()));
+        handle
+    }
+
+    /// Like [`TaskRegistry::spawn`], running `f` on the blocking thread pool
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    pub(crate) fn spawn_blocking<F, R>(
+        self: &std::sync::Arc<Self>,
+        guard: TaskGuard,
+        runtime: Option<&tokio::runtime::Handle>,
+        f: F,
+    ) -> tokio::task::JoinHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let id = guard.id;
+
+        let f = move || {
+            let _guard = guard;
+            f()
+        };
+        let handle = match runtime {
+            Some(runtime) => runtime.spawn_blocking(f),
+            None => tokio::task::spawn_blocking(f),
+        };
+
+        self.set_abort_handle(id, Abort::Task(handle.abort_handle()));
+        handle
+    }
+
+    /// Like [`TaskRegistry::spawn`], spawning the task into `set`
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    pub(crate) fn spawn_in<F>(
+        self: &std::sync::Arc<Self>,
+        set: &mut tokio::task::JoinSet<F::Output>,
+        guard: TaskGuard,
+        runtime: Option<&tokio::runtime::Handle>,
+        task: F,
+    ) -> tokio::task::AbortHandle
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let id = guard.id;
+
+        let task = async move {
+            let _guard = guard;
+            task.await
+        };
+        let abort_handle = match runtime {
+            Some(runtime) => set.spawn_on(task, runtime),
+            None => set.spawn(task),
+        };
+
+        self.set_abort_handle(id, Abort::Task(abort_handle.clone()));
+        abort_handle
+    }
+
+    /// Like [`TaskRegistry::spawn`], on another executor; returns the token that
+    /// aborts the task
+    #[track_caller]
+    pub(crate) fn spawn_with<F>(
+        self: &std::sync::Arc<Self>,
+        guard: TaskGuard,
+        spawner: &dyn crate::Spawner,
+        task: F,
+    ) -> tokio_util::sync::CancellationToken
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let id = guard.id;
+        let abort = tokio_util::sync::CancellationToken::new();
+
+        let aborted = abort.clone();
+        spawner.spawn(Box::pin(async move {
+            let _guard = guard;
+            tokio::select! {
+                biased;
+                _ = aborted.cancelled() => {}
+                _ = task => {}
+            }
+        }));
+
+        self.set_abort_handle(id, Abort::Token(abort.clone()));
+        abort
+    }
+
+    /// Track work running outside of any executor (e.g. on a plain thread) until
+    /// the returned guard is dropped
+    #[track_caller]
+    pub(crate) fn track_external(&self, guard: TaskGuard) -> ExternalTask {
+        ExternalTask { _guard: guard }
+    }
+
+    /// Record a task about to be spawned, the guard removes it again
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    pub(crate) fn register(self: &std::sync::Arc<Self>, name: Option<String>) -> TaskGuard {
+        let Some(guard) = self.try_register(name, None) else {
+            unreachable!("tasks are only refused over a limit");
+        };
+        guard
+    }
+
+    /// Like [`TaskRegistry::register`], unless `limit` tasks are alive already
+    ///
+    /// Checked and recorded under one lock, so concurrent callers can't overshoot.
+    #[track_caller]
+    pub(crate) fn try_register(
+        self: &std::sync::Arc<Self>,
+        name: Option<String>,
+        limit: Option<usize>,
+    ) -> Option<TaskGuard> {
+        let id = self
+            .next_id
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        if let Ok(mut tasks) = self.tasks.lock() {
+            if limit.is_some_and(|limit| tasks.len() >= limit) {
+                return None;
+            }
+            tasks.insert(
+                id,
+                TrackedTask {
+                    abort_handle: None,
+                    name,
+                    spawned_at: std::panic::Location::caller(),
+                    started: std::time::Instant::now(),
+                },
+            );
+        }
+
+        crate::status_stream::tree_changed();
+
+        Some(TaskGuard {
+            registry: self.clone(),
+            id,
+        })
+    }
+
+    fn set_abort_handle(&self, id: u64, abort_handle: Abort) {
+        if let Ok(mut tasks) = self.tasks.lock() {
+            if let Some(tracked) = tasks.get_mut(&id) {
+                tracked.abort_handle = Some(abort_handle);
+            }
+        }
+    }
+
+    /// Number of tasks still alive
+    pub(crate) fn len(&self) -> usize {
+        self.tasks.lock().map(|tasks| tasks.len()).unwrap_or(0)
+    }
+
+    /// Like [`TaskRegistry::len`], None instead of blocking if the registry is locked
+    pub(crate) fn try_len(&self) -> Option<usize> {
+        self.tasks.try_lock().ok().map(|tasks| tasks.len())
+    }
+
+    /// Wait until every tracked task has finished
+    ///
+    /// Tasks spawned while waiting are waited for as well, the registry is meant to
+    /// be winding down.
+    pub(crate) async fn close_and_wait(&self) {
+        loop {
+            let mut idle = std::pin::pin!(self.idle.notified());
+            idle.as_mut().enable();
+            if self.len() == 0 {
+                return;
+            }
+            idle.await;
+        }
+    }
+
+    /// Force-abort every task still alive, returns the aborted tasks
+    #[cfg(feature = "tokio")]
+    pub(crate) fn abort_all(&self) -> Vec<crate::HungTask> {
+        let Ok(tasks) = self.tasks.lock() else {
+            return Vec::new();
+        };
+
+        let mut hung: Vec<_> = tasks
+            .values()
+            .map(|task| {
+                if let Some(abort_handle) = &task.abort_handle {
+                    abort_handle.abort();
+                }
+                crate::HungTask {
+                    name: task.name.clone(),
+                    spawned_at: task.spawned_at.to_string(),
+                    running_for: task.started.elapsed(),
+                }
+            })
+            .collect();
+        hung.sort_by_key(|task| std::cmp::Reverse(task.running_for));
+        hung
+    }
+}
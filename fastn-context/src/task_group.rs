@@ -0,0 +1,119 @@
+/// Set of tasks spawned through a context that can be joined together, built on
+/// `tokio::task::JoinSet`
+///
+/// Tasks are tracked by the context like [`crate::Context::spawn`]ed ones (task
+/// counts, shutdown, panic policy). When the context is cancelled, every task still
+/// in the group is aborted; dropping the group aborts them too.
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// let ctx = fastn_context::Context::new("app");
+/// let mut group = ctx.task_group();
+///
+/// for i in 0..3 {
+///     group.spawn(async move { i * 2 });
+/// }
+///
+/// let mut results: Vec<_> = group.join_all().await.into_iter().map(Result::unwrap).collect();
+/// results.sort();
+/// assert_eq!(results, vec![0, 2, 4]);
+/// # }
+/// ```
+pub struct TaskGroup<T> {
+    context: crate::Context,
+    set: tokio::task::JoinSet<T>,
+    abort_handles: std::sync::Arc<std::sync::Mutex<Vec<tokio::task::AbortHandle>>>,
+    /// Stops the task aborting the group on cancellation once the group is dropped
+    watcher: Option<tokio_util::sync::DropGuard>,
+}
+
+impl<T: Send + 'static> TaskGroup<T> {
+    /// Spawn a task into the group (aborted right away if the context is already
+    /// cancelled)
+    #[track_caller]
+    pub fn spawn<F>(&mut self, task: F) -> tokio::task::AbortHandle
+    where
+        F: std::future::Future<Output = T> + Send + 'static,
+    {
+        let abort_handle = self.context.spawn_in(&mut self.set, task);
+
+        if let Ok(mut abort_handles) = self.abort_handles.lock() {
+            abort_handles.retain(|handle| !handle.is_finished());
+            abort_handles.push(abort_handle.clone());
+        }
+        if self.watcher.is_none() {
+            self.watcher = Some(self.watch());
+        }
+        if self.context.is_cancelled() {
+            abort_handle.abort();
+        }
+
+        abort_handle
+    }
+
+    /// Wait for the next task to finish, None once the group is empty
+    pub async fn join_next(&mut self) -> Option<Result<T, tokio::task::JoinError>> {
+        self.set.join_next().await
+    }
+
+    /// Wait for every task in the group, returning their results in completion order
+    ///
+    /// Panicked and aborted tasks show up as `Err`, unlike
+    /// `tokio::task::JoinSet::join_all` this never panics.
+    pub async fn join_all(mut self) -> Vec<Result<T, tokio::task::JoinError>> {
+        let mut results = Vec::with_capacity(self.set.len());
+        while let Some(result) = self.set.join_next().await {
+            results.push(result);
+        }
+        results
+    }
+
+    /// Abort every task in the group
+    pub fn abort_all(&mut self) {
+        self.set.abort_all();
+    }
+
+    /// Number of tasks in the group not joined yet
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    /// Check if the group has no tasks left to join
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// Abort the group's tasks once the context is cancelled
+    fn watch(&self) -> tokio_util::sync::DropGuard {
+        let dropped = tokio_util::sync::CancellationToken::new();
+        let context = self.context.clone();
+        let abort_handles = self.abort_handles.clone();
+        let group_dropped = dropped.clone();
+
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = context.cancelled() => {
+                    if let Ok(abort_handles) = abort_handles.lock() {
+                        abort_handles.iter().for_each(tokio::task::AbortHandle::abort);
+                    }
+                }
+                _ = group_dropped.cancelled() => {}
+            }
+        });
+
+        dropped.drop_guard()
+    }
+}
+
+impl crate::Context {
+    /// Create an empty [`crate::TaskGroup`] whose tasks are tracked by this context
+    pub fn task_group<T: Send + 'static>(&self) -> crate::TaskGroup<T> {
+        TaskGroup {
+            context: self.clone(),
+            set: tokio::task::JoinSet::new(),
+            abort_handles: Default::default(),
+            watcher: None,
+        }
+    }
+}
@@ -0,0 +1,93 @@
+/// Termination sequence for load balanced deployments (e.g. Kubernetes pods)
+///
+/// On SIGTERM (or SIGINT / Ctrl+C) the global context is first marked not ready,
+/// so readiness probes (see [`crate::is_ready`]) fail and the load balancer stops
+/// routing new traffic to the process. After `drain_delay` the tree is shut down
+/// phase by phase with [`crate::Context::shutdown`]. Repeated signals escalate as
+/// described by the [`crate::SignalPolicy`], see [`Termination::signal_policy`].
+///
+/// This replaces [`crate::install_signal_handlers`]: installing both (e.g. with
+/// `#[fastn_context::main(signal_handlers)]`) fails.
+///
+/// ```rust,no_run
+/// #[fastn_context::main]
+/// async fn main() -> std::io::Result<()> {
+///     let termination = fastn_context::Termination::new()
+///         .drain_delay(std::time::Duration::from_secs(5))
+///         .grace(std::time::Duration::from_secs(20))
+///         .install()?;
+///
+///     fastn_context::global().ready();
+///     // ... serve ...
+///
+///     let report = termination.await.expect("termination task");
+///     println!("{report}");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Termination {
+    drain_delay: std::time::Duration,
+    grace: std::time::Duration,
+    signal_policy: crate::SignalPolicy,
+}
+
+impl Default for Termination {
+    fn default() -> Self {
+        Termination {
+            drain_delay: std::time::Duration::from_secs(5),
+            grace: std::time::Duration::from_secs(25),
+            signal_policy: crate::SignalPolicy::default(),
+        }
+    }
+}
+
+impl Termination {
+    /// 5s drain delay then 25s grace, within Kubernetes' default 30s
+    /// `terminationGracePeriodSeconds`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time between failing readiness and starting the shutdown, so load balancers
+    /// notice the process is going away
+    pub fn drain_delay(mut self, delay: std::time::Duration) -> Self {
+        self.drain_delay = delay;
+        self
+    }
+
+    /// Grace period of the shutdown, see [`crate::Context::shutdown`]
+    pub fn grace(mut self, grace: std::time::Duration) -> Self {
+        self.grace = grace;
+        self
+    }
+
+    /// How signals repeated during the termination escalate (the default policy
+    /// force-aborts tasks on the second signal and exits on the third)
+    pub fn signal_policy(mut self, policy: crate::SignalPolicy) -> Self {
+        self.signal_policy = policy;
+        self
+    }
+
+    /// Wait for the termination signal in the background
+    ///
+    /// The returned handle resolves to the report of the shutdown once it is done.
+    /// Must be called from within a tokio runtime. Fails with
+    /// [`std::io::ErrorKind::AlreadyExists`] if termination signals are already
+    /// handled, see [`crate::install_signal_handlers`].
+    pub fn install(self) -> std::io::Result<tokio::task::JoinHandle<crate::ShutdownReport>> {
+        let (first_signal, mut first_signals) = tokio::sync::mpsc::unbounded_channel();
+        crate::signal::handle_signals(self.signal_policy, move |signal| {
+            let _ = first_signal.send(signal);
+        })?;
+
+        Ok(tokio::spawn(async move {
+            // The sender lives as long as the signal handler, which never stops
+            let signal = first_signals.recv().await.unwrap_or("stop request");
+            crate::global().not_ready(&format!("terminating ({signal})"));
+
+            tokio::time::sleep(self.drain_delay).await;
+            crate::shutdown(self.grace).await
+        }))
+    }
+}
@@ -0,0 +1,29 @@
+/// Run a test body under a fresh root context, panicking if it leaks work
+///
+/// Used by `#[fastn_context::test]`; a child context is considered leaked when it
+/// still has live tasks or is still referenced outside the tree after the body
+/// returns.
+pub async fn run_test<F, Fut>(name: &str, test: F) -> Fut::Output
+where
+    F: FnOnce(std::sync::Arc<crate::Context>) -> Fut,
+    Fut: std::future::Future,
+{
+    let root = crate::Context::new(name);
+    let output = crate::with_current(root.clone(), test(root.clone())).await;
+
+    let leaks = root.leaks();
+    if !leaks.is_empty() {
+        root.cancel();
+        panic!(
+            "test `{name}` leaked {} context(s):\n{}",
+            leaks.len(),
+            leaks
+                .iter()
+                .map(|leak| format!("- {leak}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+
+    output
+}
@@ -0,0 +1,31 @@
+/// Type-keyed storage for values attached to a context
+#[derive(Default)]
+pub(crate) struct TypeMap {
+    values: std::collections::HashMap<
+        std::any::TypeId,
+        std::sync::Arc<dyn std::any::Any + Send + Sync>,
+    >,
+}
+
+impl TypeMap {
+    /// Store a value, replacing any previous value of the same type
+    pub(crate) fn insert<T: Send + Sync + 'static>(&mut self, value: T) {
+        self.values
+            .insert(std::any::TypeId::of::<T>(), std::sync::Arc::new(value));
+    }
+
+    /// Get the value stored for type `T`, if any
+    pub(crate) fn get<T: Send + Sync + 'static>(&self) -> Option<std::sync::Arc<T>> {
+        self.values
+            .get(&std::any::TypeId::of::<T>())
+            .cloned()
+            .and_then(|value| value.downcast::<T>().ok())
+    }
+
+    /// Remove the value stored for type `T`, returning it
+    pub(crate) fn remove<T: Send + Sync + 'static>(&mut self) -> Option<std::sync::Arc<T>> {
+        self.values
+            .remove(&std::any::TypeId::of::<T>())
+            .and_then(|value| value.downcast::<T>().ok())
+    }
+}
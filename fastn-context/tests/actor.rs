@@ -0,0 +1,61 @@
+type Handled = std::sync::Arc<std::sync::Mutex<Vec<u32>>>;
+
+/// Handler recording every message, taking a moment for each
+fn recording(
+    handled: &Handled,
+) -> impl FnMut((), u32) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>> {
+    let handled = handled.clone();
+    move |(), message| {
+        let handled = handled.clone();
+        Box::pin(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+            handled.lock().unwrap().push(message);
+        })
+    }
+}
+
+#[tokio::test]
+async fn dropping_the_last_address_drains_the_mailbox() {
+    let app = fastn_context::Context::new("actor-drain");
+    let handled = Handled::default();
+    let address = app.actor("worker", (), recording(&handled));
+    let worker = fastn_context::find("actor-drain/worker").pop().unwrap();
+
+    for message in 0..3 {
+        address.send(message).unwrap();
+    }
+    let copy = address.clone();
+    drop(address);
+    assert!(!copy.is_closed());
+    drop(copy);
+    app.close_and_wait().await;
+
+    assert_eq!(*handled.lock().unwrap(), [0, 1, 2]);
+    assert_eq!(worker.state(), fastn_context::ContextState::Completed);
+    assert!(!worker.is_cancelled());
+}
+
+#[tokio::test]
+async fn cancellation_closes_the_mailbox_and_applies_its_policy() {
+    let app = fastn_context::Context::new("actor-discard");
+    let handled = Handled::default();
+    let address = app
+        .child("worker")
+        .mailbox(fastn_context::MailboxPolicy::Discard)
+        .actor((), recording(&handled));
+    let worker = fastn_context::find("actor-discard/worker").pop().unwrap();
+    for message in 0..3 {
+        address.send(message).unwrap();
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+
+    worker.cancel();
+
+    assert_eq!(address.send(3), Err(3));
+    app.close_and_wait().await;
+    let mailbox = worker.status().mailbox.unwrap();
+    assert_eq!(mailbox.processed + mailbox.discarded, 3);
+    assert!(mailbox.discarded >= 1);
+    assert!(mailbox.closed);
+    assert_eq!(handled.lock().unwrap().len() as u64, mailbox.processed);
+}
@@ -0,0 +1,49 @@
+#[tokio::test]
+async fn dropped_builders_leave_no_child() {
+    let app = fastn_context::Context::new("app");
+
+    drop(app.child("worker").label("dropped-builder").max_children(1));
+
+    assert!(app.children().is_empty());
+    assert!(fastn_context::find_by_label("dropped-builder").is_empty());
+}
+
+#[tokio::test]
+async fn detached_children_are_listed_and_registered_once() {
+    let app = fastn_context::Context::new("app");
+    let audit = app
+        .child("audit")
+        .timeout(std::time::Duration::from_secs(60))
+        .detach()
+        .build();
+
+    assert!(audit.is_detached());
+    assert!(audit.deadline().is_some());
+    let listed: Vec<_> = app.children().iter().map(|child| child.id()).collect();
+    assert_eq!(listed, [audit.id()]);
+    let registered = fastn_context::find_by_id(audit.id()).unwrap();
+    assert!(std::sync::Arc::ptr_eq(&registered, &audit));
+
+    app.cancel();
+    assert!(!audit.is_cancelled());
+    audit.cancel();
+}
+
+#[tokio::test]
+async fn try_build_fails_over_max_children() {
+    let app = fastn_context::Context::new("app");
+    let pool = app.child("pool").max_children(1).build();
+    let builder = pool.try_child("worker").unwrap();
+    let _first = pool.child("worker").build();
+
+    let refused = builder.try_build().err().unwrap();
+
+    assert_eq!(
+        refused,
+        fastn_context::ChildError::Quota(fastn_context::QuotaExceeded::Children {
+            path: "app/pool".to_string(),
+            limit: 1,
+        })
+    );
+    assert_eq!(pool.children().len(), 1);
+}
@@ -0,0 +1,84 @@
+#[tokio::test]
+async fn timeout_cancels_with_deadline_exceeded() {
+    let app = fastn_context::Context::new("app");
+    let request = app
+        .child("request")
+        .timeout(std::time::Duration::from_millis(10))
+        .build();
+
+    tokio::time::timeout(std::time::Duration::from_secs(5), request.cancelled())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        request.cancel_reason(),
+        Some(fastn_context::CancelReason::DeadlineExceeded)
+    );
+    assert!(!app.is_cancelled());
+}
+
+#[tokio::test]
+async fn child_deadline_is_clamped_to_the_parent() {
+    let parent_deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    let app = fastn_context::Context::new("app");
+    let parent = app.child("parent").deadline(parent_deadline).build();
+
+    let later = parent
+        .child("later")
+        .timeout(std::time::Duration::from_secs(60))
+        .build();
+    let earlier = parent
+        .child("earlier")
+        .timeout(std::time::Duration::from_secs(1))
+        .build();
+
+    assert_eq!(later.deadline(), Some(parent_deadline));
+    assert!(earlier.deadline().unwrap() < parent_deadline);
+    app.cancel();
+}
+
+#[tokio::test]
+async fn children_inherit_the_cancel_reason() {
+    let app = fastn_context::Context::new("app");
+    let worker = app.child("worker").build();
+
+    app.cancel_with_reason("config reloaded");
+
+    let reason = Some(fastn_context::CancelReason::Custom(
+        "config reloaded".into(),
+    ));
+    assert_eq!(app.cancel_reason(), reason);
+    assert_eq!(worker.cancel_reason(), reason);
+}
+
+#[tokio::test]
+async fn first_cancel_reason_wins() {
+    let ctx = fastn_context::Context::new("app");
+    assert_eq!(ctx.cancel_reason(), None);
+
+    ctx.cancel_with_reason(fastn_context::CancelReason::Shutdown);
+    ctx.cancel();
+
+    assert_eq!(
+        ctx.cancel_reason(),
+        Some(fastn_context::CancelReason::Shutdown)
+    );
+}
+
+#[tokio::test]
+async fn own_reason_takes_precedence_over_the_parent() {
+    let app = fastn_context::Context::new("app");
+    let worker = app.child("worker").build();
+
+    worker.cancel_with_reason(fastn_context::CancelReason::DeadlineExceeded);
+    app.cancel();
+
+    assert_eq!(
+        worker.cancel_reason(),
+        Some(fastn_context::CancelReason::DeadlineExceeded)
+    );
+    assert_eq!(
+        app.cancel_reason(),
+        Some(fastn_context::CancelReason::Requested)
+    );
+}
@@ -0,0 +1,141 @@
+#[tokio::test]
+async fn suffix_appends_the_first_free_number() {
+    let app = fastn_context::Context::new("app");
+    let pool = app
+        .child("pool")
+        .child_names(fastn_context::ChildNamePolicy::Suffix)
+        .build();
+
+    let workers: Vec<_> = (0..3).map(|_| pool.child("worker").build()).collect();
+
+    let names: Vec<_> = workers.iter().map(|worker| worker.name.as_str()).collect();
+    assert_eq!(names, ["worker", "worker-2", "worker-3"]);
+}
+
+#[tokio::test]
+async fn error_fails_try_child_while_the_name_is_active() {
+    let app = fastn_context::Context::new("app");
+    let pool = app
+        .child("pool")
+        .child_names(fastn_context::ChildNamePolicy::Error)
+        .build();
+    let first = pool.child("worker").build();
+
+    let refused = pool.try_child("worker").err().unwrap();
+    assert_eq!(
+        refused,
+        fastn_context::ChildError::NameTaken {
+            path: "app/pool/worker".to_string(),
+        }
+    );
+
+    first.complete();
+    assert!(pool.try_child("worker").is_ok());
+}
+
+#[tokio::test]
+#[should_panic(expected = "app/pool/worker already exists")]
+async fn error_makes_build_panic_while_the_name_is_active() {
+    let app = fastn_context::Context::new("app");
+    let pool = app
+        .child("pool")
+        .child_names(fastn_context::ChildNamePolicy::Error)
+        .build();
+    let _first = pool.child("worker").build();
+
+    pool.child("worker").build();
+}
+
+#[tokio::test]
+async fn replaced_children_are_hidden_but_drained() {
+    let app = fastn_context::Context::new("app");
+    let pool = app
+        .child("pool")
+        .child_names(fastn_context::ChildNamePolicy::Replace)
+        .build();
+    let (release, released) = tokio::sync::oneshot::channel::<()>();
+    let old = pool.child("worker").build();
+    // Ignores cancellation until released, like a task flushing its state
+    old.spawn(async move {
+        let _ = released.await;
+    });
+
+    let new = pool.child("worker").build();
+
+    assert_eq!(
+        old.cancel_reason(),
+        Some(fastn_context::CancelReason::Replaced)
+    );
+    let listed: Vec<_> = pool.children().iter().map(|child| child.id()).collect();
+    assert_eq!(listed, [new.id()]);
+    assert_eq!(pool.status().children.len(), 1);
+
+    let shutdown = tokio::spawn({
+        let app = app.clone();
+        async move { app.shutdown(std::time::Duration::from_secs(5)).await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    assert!(!shutdown.is_finished());
+
+    release.send(()).unwrap();
+    let report = shutdown.await.unwrap();
+    assert!(report.is_clean(), "{report}");
+    assert_eq!(old.task_count(), 0);
+}
+
+#[tokio::test]
+async fn finished_replaced_children_are_pruned() {
+    let app = fastn_context::Context::new("app");
+    let pool = app
+        .child("pool")
+        .child_names(fastn_context::ChildNamePolicy::Replace)
+        .build();
+    let old = pool.child("worker").build();
+    let _new = pool.child("worker").build();
+    let old_id = old.id();
+    assert!(fastn_context::find_by_id(old_id).is_some());
+
+    drop(old);
+
+    assert_eq!(pool.status().children.len(), 1);
+    assert!(fastn_context::find_by_id(old_id).is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_suffixed_children_get_distinct_names() {
+    let app = fastn_context::Context::new("app");
+    let pool = app
+        .child("pool")
+        .child_names(fastn_context::ChildNamePolicy::Suffix)
+        .build();
+
+    let attempts: Vec<_> = (0..32)
+        .map(|_| {
+            let pool = pool.clone();
+            tokio::spawn(async move { pool.child("worker").build() })
+        })
+        .collect();
+    let mut names = std::collections::HashSet::new();
+    for attempt in attempts {
+        names.insert(attempt.await.unwrap().name.clone());
+    }
+
+    assert_eq!(names.len(), 32);
+}
+
+#[tokio::test]
+async fn replacing_does_not_count_against_max_children() {
+    let app = fastn_context::Context::new("app");
+    let pool = app
+        .child("pool")
+        .child_names(fastn_context::ChildNamePolicy::Replace)
+        .max_children(1)
+        .build();
+    let old = pool.child("worker").build();
+
+    let new = pool.try_child("worker").unwrap().build();
+
+    assert!(old.is_cancelled());
+    let listed: Vec<_> = pool.children().iter().map(|child| child.id()).collect();
+    assert_eq!(listed, [new.id()]);
+}
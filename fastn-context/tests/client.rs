@@ -0,0 +1,101 @@
+#![cfg(feature = "client")]
+
+use tower_layer::Layer as _;
+use tower_service::Service as _;
+
+/// Client recording the headers of its last request, answering right away or never
+#[derive(Clone, Default)]
+struct Recorder {
+    headers: std::sync::Arc<std::sync::Mutex<http::HeaderMap>>,
+    hang: bool,
+}
+
+impl tower_service::Service<http::Request<()>> for Recorder {
+    type Response = ();
+    type Error = std::convert::Infallible;
+    type Future = std::pin::Pin<
+        Box<dyn std::future::Future<Output = Result<(), std::convert::Infallible>> + Send>,
+    >;
+
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<()>) -> Self::Future {
+        *self.headers.lock().unwrap() = request.headers().clone();
+        if self.hang {
+            Box::pin(std::future::pending())
+        } else {
+            Box::pin(std::future::ready(Ok(())))
+        }
+    }
+}
+
+#[tokio::test]
+async fn requests_carry_the_context_propagation_headers() {
+    let app = fastn_context::Context::new("app");
+    let request_ctx = app.child("request").build();
+    request_ctx.set_baggage("tenant", "acme");
+    let recorder = Recorder::default();
+    let mut client = fastn_context::ClientLayer::new()
+        .context(request_ctx.clone())
+        .layer(recorder.clone());
+
+    let mut request = http::Request::new(());
+    request
+        .headers_mut()
+        .insert("baggage", http::HeaderValue::from_static("kept=1"));
+    client.call(request).await.unwrap();
+
+    let headers = recorder.headers.lock().unwrap().clone();
+    let trace_parent =
+        fastn_context::TraceParent::parse(headers["traceparent"].to_str().unwrap()).unwrap();
+    assert_eq!(Some(trace_parent.trace_id), request_ctx.trace_id());
+    assert_eq!(headers["baggage"], "kept=1");
+}
+
+#[tokio::test]
+async fn cancelling_the_context_aborts_the_request() {
+    let app = fastn_context::Context::new("app");
+    let mut client = fastn_context::ClientLayer::new()
+        .context(app.clone())
+        .without_propagation()
+        .layer(Recorder {
+            hang: true,
+            ..Recorder::default()
+        });
+
+    let response = client.call(http::Request::new(()));
+    app.cancel();
+
+    assert!(matches!(
+        response.await,
+        Err(fastn_context::ClientError::Cancelled(_))
+    ));
+}
+
+#[tokio::test]
+async fn requests_time_out() {
+    let app = fastn_context::Context::new("app");
+    let recorder = Recorder {
+        hang: true,
+        ..Recorder::default()
+    };
+    let mut client = fastn_context::ClientLayer::new()
+        .context(app.clone())
+        .without_propagation()
+        .timeout(std::time::Duration::from_millis(20))
+        .layer(recorder.clone());
+
+    let response = client.call(http::Request::new(())).await;
+
+    assert!(matches!(
+        response,
+        Err(fastn_context::ClientError::TimedOut)
+    ));
+    assert!(recorder.headers.lock().unwrap().is_empty());
+    assert!(!app.is_cancelled());
+}
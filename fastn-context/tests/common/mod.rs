@@ -0,0 +1,10 @@
+/// Poll `condition` until it holds, failing the test after 5 seconds
+pub async fn wait_until(mut condition: impl FnMut() -> bool) {
+    tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        while !condition() {
+            tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+        }
+    })
+    .await
+    .expect("condition not reached within 5s");
+}
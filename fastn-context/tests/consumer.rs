@@ -0,0 +1,68 @@
+/// Stream over a channel, counting the messages pulled from it
+struct Subscription {
+    receiver: tokio::sync::mpsc::UnboundedReceiver<u32>,
+    pulled: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+}
+
+impl futures_core::Stream for Subscription {
+    type Item = u32;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<u32>> {
+        let message = self.receiver.poll_recv(cx);
+        if let std::task::Poll::Ready(Some(_)) = message {
+            self.pulled
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }
+        message
+    }
+}
+
+#[tokio::test]
+async fn cancellation_stops_pulling_and_waits_for_in_flight_messages() {
+    let app = fastn_context::Context::new("app");
+    let orders = app.child("orders").max_concurrent_tasks(2).build();
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    for order in 0..5 {
+        sender.send(order).unwrap();
+    }
+    let pulled = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let finished = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let subscription = Subscription {
+        receiver,
+        pulled: pulled.clone(),
+    };
+
+    let consuming = tokio::spawn({
+        let orders = orders.clone();
+        let finished = finished.clone();
+        async move {
+            orders
+                .consume(subscription, move |ctx, _order| {
+                    let finished = finished.clone();
+                    async move {
+                        ctx.cancelled().await;
+                        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                        finished.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                        Ok::<_, std::io::Error>(())
+                    }
+                })
+                .await
+        }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    assert_eq!(pulled.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+    orders.cancel();
+    let totals = tokio::time::timeout(std::time::Duration::from_secs(5), consuming)
+        .await
+        .expect("consume did not return")
+        .unwrap();
+
+    assert_eq!(finished.load(std::sync::atomic::Ordering::SeqCst), 2);
+    assert_eq!(pulled.load(std::sync::atomic::Ordering::SeqCst), 2);
+    assert_eq!(totals.processed, 2);
+    drop(sender);
+}
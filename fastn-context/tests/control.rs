@@ -0,0 +1,39 @@
+#![cfg(all(unix, feature = "control"))]
+
+use std::os::unix::fs::PermissionsExt;
+
+mod common;
+
+/// A path in the temp dir unique to this test process
+fn socket_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("fastn-control-{}-{name}", std::process::id()))
+}
+
+#[tokio::test]
+async fn other_files_at_the_path_are_left_alone() {
+    let path = socket_path("regular-file");
+    std::fs::write(&path, "keep me").unwrap();
+
+    let error = fastn_context::control::serve(&path).await.unwrap_err();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::AlreadyExists);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn the_socket_is_only_accessible_to_its_owner() {
+    let path = socket_path("mode");
+    let stale = std::os::unix::net::UnixListener::bind(&path).unwrap();
+    drop(stale);
+
+    let server = tokio::spawn(fastn_context::control::serve(path.clone()));
+    common::wait_until(|| {
+        std::fs::metadata(&path)
+            .is_ok_and(|metadata| metadata.permissions().mode() & 0o777 == 0o600)
+    })
+    .await;
+
+    server.abort();
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,30 @@
+#![cfg(feature = "ffi")]
+
+#[tokio::test]
+async fn refused_children_and_tasks_are_null() {
+    let app = fastn_context::Context::new("ffi-app");
+    let _pool = app
+        .child("pool")
+        .max_children(1)
+        .max_tracked_tasks(1)
+        .build();
+    let name = c"worker";
+
+    // SAFETY: every handle comes from this library and is released once
+    unsafe {
+        let pool = fastn_context::ffi::fastn_context_find(c"ffi-app/pool".as_ptr());
+        assert!(!pool.is_null());
+
+        let first = fastn_context::ffi::fastn_context_child(pool, name.as_ptr());
+        assert!(!first.is_null());
+        assert!(fastn_context::ffi::fastn_context_child(pool, name.as_ptr()).is_null());
+
+        let task = fastn_context::ffi::fastn_context_task_begin(pool, name.as_ptr());
+        assert!(!task.is_null());
+        assert!(fastn_context::ffi::fastn_context_task_begin(pool, name.as_ptr()).is_null());
+
+        fastn_context::ffi::fastn_context_task_end(task);
+        fastn_context::ffi::fastn_context_free(first);
+        fastn_context::ffi::fastn_context_free(pool);
+    }
+}
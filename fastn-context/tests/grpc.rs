@@ -0,0 +1,105 @@
+#![cfg(feature = "tonic")]
+
+use tower_layer::Layer as _;
+use tower_service::Service as _;
+
+/// Service keeping the last request it got, answering right away
+#[derive(Clone, Default)]
+struct Recorder {
+    request: std::sync::Arc<std::sync::Mutex<Option<http::Request<()>>>>,
+}
+
+impl tower_service::Service<http::Request<()>> for Recorder {
+    type Response = ();
+    type Error = std::convert::Infallible;
+    type Future = std::future::Ready<Result<(), std::convert::Infallible>>;
+
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<()>) -> Self::Future {
+        *self.request.lock().unwrap() = Some(request);
+        std::future::ready(Ok(()))
+    }
+}
+
+impl Recorder {
+    fn header(&self, name: &str) -> Option<String> {
+        let request = self.request.lock().unwrap();
+        let value = request.as_ref().unwrap().headers().get(name)?;
+        Some(value.to_str().unwrap().to_string())
+    }
+}
+
+#[tokio::test]
+async fn client_calls_carry_the_remaining_deadline() {
+    let app = fastn_context::Context::new("app");
+    let call_ctx = app
+        .child("call")
+        .timeout(std::time::Duration::from_secs(2))
+        .build();
+    let recorder = Recorder::default();
+    let mut client = fastn_context::grpc::GrpcClientLayer::new()
+        .context(call_ctx)
+        .layer(recorder.clone());
+
+    client.call(http::Request::new(())).await.unwrap();
+    let timeout = recorder.header("grpc-timeout").unwrap();
+    let (amount, unit) = timeout.split_at(timeout.len() - 1);
+    assert_eq!(unit, "u");
+    let micros: u64 = amount.parse().unwrap();
+    assert!((1_900_000..=2_000_000).contains(&micros), "{timeout}");
+    assert!(recorder.header("traceparent").is_some());
+
+    let mut request = http::Request::new(());
+    request
+        .headers_mut()
+        .insert("grpc-timeout", http::HeaderValue::from_static("100m"));
+    client.call(request).await.unwrap();
+    assert_eq!(recorder.header("grpc-timeout").unwrap(), "100m");
+}
+
+#[tokio::test]
+async fn server_calls_get_a_linked_child_context() {
+    let app = fastn_context::Context::new("app");
+    let recorder = Recorder::default();
+    let mut server = fastn_context::grpc::GrpcServerLayer::new(app.clone())
+        .name("rpc")
+        .layer(recorder.clone());
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(
+        "traceparent",
+        http::HeaderValue::from_static("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"),
+    );
+    request
+        .headers_mut()
+        .insert("grpc-timeout", http::HeaderValue::from_static("500m"));
+    server.call(request).await.unwrap();
+
+    let call_ctx = recorder
+        .request
+        .lock()
+        .unwrap()
+        .as_ref()
+        .unwrap()
+        .extensions()
+        .get::<std::sync::Arc<fastn_context::Context>>()
+        .unwrap()
+        .clone();
+    assert_eq!(call_ctx.path(), "app/rpc");
+    assert_eq!(
+        call_ctx.trace_id(),
+        Some(0x4bf92f3577b34da6a3ce929d0e0e4736)
+    );
+    let remaining = call_ctx
+        .deadline()
+        .unwrap()
+        .saturating_duration_since(std::time::Instant::now());
+    assert!(remaining <= std::time::Duration::from_millis(500));
+    assert!(call_ctx.is_cancelled(), "cancelled once the call completed");
+}
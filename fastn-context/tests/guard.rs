@@ -0,0 +1,75 @@
+/// Flag set by a cleanup
+fn flag() -> std::sync::Arc<std::sync::atomic::AtomicBool> {
+    std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false))
+}
+
+#[tokio::test]
+async fn dropped_guards_clean_up_and_shutdown_waits_for_it() {
+    let app = fastn_context::Context::new("app");
+    let cleaned = flag();
+    let guard = app.guard("connection", {
+        let cleaned = cleaned.clone();
+        move |_| async move {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            cleaned.store(true, std::sync::atomic::Ordering::SeqCst);
+        }
+    });
+    assert_eq!(*guard, "connection");
+
+    drop(guard);
+    let report = app.shutdown(std::time::Duration::from_secs(5)).await;
+
+    assert!(report.is_clean(), "{report}");
+    assert!(cleaned.load(std::sync::atomic::Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn released_guards_do_not_clean_up() {
+    let app = fastn_context::Context::new("app");
+    let cleaned = flag();
+    let guard = app.guard(7, {
+        let cleaned = cleaned.clone();
+        move |_| async move { cleaned.store(true, std::sync::atomic::Ordering::SeqCst) }
+    });
+
+    assert_eq!(guard.release(), 7);
+    app.shutdown(std::time::Duration::from_secs(5)).await;
+
+    assert!(!cleaned.load(std::sync::atomic::Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn guards_of_aborted_tasks_clean_up() {
+    let app = fastn_context::Context::new("app");
+    let cleaned = flag();
+    let guard = app.guard((), {
+        let cleaned = cleaned.clone();
+        move |_| async move { cleaned.store(true, std::sync::atomic::Ordering::SeqCst) }
+    });
+    let task = app.spawn(async move {
+        let _guard = guard;
+        std::future::pending::<()>().await;
+    });
+
+    task.abort();
+    let _ = task.await;
+    app.close_and_wait().await;
+
+    assert!(cleaned.load(std::sync::atomic::Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn failed_rollbacks_are_recorded_as_events() {
+    let app = fastn_context::Context::new("app");
+    let transaction = app.rollback_guard("tx", |_| async { Err("connection lost") });
+
+    drop(transaction);
+    app.close_and_wait().await;
+
+    let events: Vec<_> = app
+        .events()
+        .into_iter()
+        .map(|event| event.message)
+        .collect();
+    assert_eq!(events, ["rollback failed: connection lost"]);
+}
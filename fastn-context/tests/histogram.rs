@@ -0,0 +1,52 @@
+#[test]
+fn mean_of_recorded_samples() {
+    let mut histogram = fastn_context::LatencyHistogram::default();
+    assert_eq!(histogram.mean(), None);
+
+    for millis in [10, 20, 60] {
+        histogram.record(std::time::Duration::from_millis(millis));
+    }
+
+    assert_eq!(histogram.mean(), Some(std::time::Duration::from_millis(30)));
+    assert_eq!(histogram.max, std::time::Duration::from_millis(60));
+}
+
+#[test]
+fn mean_beyond_u32_samples() {
+    let at_wraparound = fastn_context::LatencyHistogram {
+        count: 1 << 32,
+        sum: std::time::Duration::from_secs(1 << 33),
+        ..Default::default()
+    };
+    assert_eq!(
+        at_wraparound.mean(),
+        Some(std::time::Duration::from_secs(2))
+    );
+
+    let past_wraparound = fastn_context::LatencyHistogram {
+        count: (1 << 32) + 2,
+        sum: std::time::Duration::from_millis(3 * ((1 << 32) + 2)),
+        ..Default::default()
+    };
+    assert_eq!(
+        past_wraparound.mean(),
+        Some(std::time::Duration::from_millis(3))
+    );
+}
+
+#[test]
+fn percentiles_stay_within_the_bucket() {
+    let mut histogram = fastn_context::LatencyHistogram::default();
+    for _ in 0..99 {
+        histogram.record(std::time::Duration::from_millis(3));
+    }
+    histogram.record(std::time::Duration::from_millis(400));
+
+    let p50 = histogram.percentile(0.5).unwrap();
+    assert!(p50 > std::time::Duration::from_millis(1));
+    assert!(p50 <= std::time::Duration::from_millis(5));
+    assert_eq!(
+        histogram.percentile(1.0),
+        Some(std::time::Duration::from_millis(400))
+    );
+}
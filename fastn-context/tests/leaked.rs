@@ -0,0 +1,94 @@
+/// Leaks reported under the root `root` (the registry is shared by all tests)
+fn leaked_under(root: &str) -> Vec<String> {
+    fastn_context::leaked()
+        .into_iter()
+        .filter(|leak| leak.starts_with(&format!("{root}/")))
+        .collect()
+}
+
+#[tokio::test]
+async fn dropping_the_root_frees_a_finished_subtree() {
+    let app = fastn_context::Context::new("app");
+    let worker = app.child("worker").build();
+    let task = worker.child("task").build();
+    let ids = [worker.id(), task.id()];
+    task.complete();
+    worker.complete();
+
+    drop((task, worker));
+    drop(app);
+
+    for id in ids {
+        assert!(fastn_context::find_by_id(id).is_none());
+    }
+}
+
+#[tokio::test]
+async fn children_do_not_keep_their_parent_alive() {
+    let app = fastn_context::Context::new("app");
+    let worker = app.child("worker").build();
+    assert_eq!(worker.parent().unwrap().id(), app.id());
+
+    drop(app);
+
+    assert!(worker.parent().is_none());
+    assert_eq!(worker.path(), "app/worker");
+}
+
+#[tokio::test]
+async fn tasks_hold_the_handle_of_their_context() {
+    let app = fastn_context::Context::new("app");
+    let current = app
+        .spawn(async { fastn_context::Context::current() })
+        .await
+        .unwrap();
+
+    assert!(std::sync::Arc::ptr_eq(&current, &app));
+}
+
+#[tokio::test]
+async fn extra_handles_of_a_finished_child_are_reported() {
+    let root = fastn_context::Context::new("leaked-handles");
+    let worker = root.child("worker").build();
+    worker.complete();
+    assert_eq!(
+        leaked_under("leaked-handles"),
+        ["leaked-handles/worker (still referenced)"]
+    );
+
+    let extra = [worker.clone(), worker.clone()];
+    assert_eq!(
+        leaked_under("leaked-handles"),
+        ["leaked-handles/worker (still referenced)"]
+    );
+
+    drop((worker, extra));
+    assert!(leaked_under("leaked-handles").is_empty());
+}
+
+#[tokio::test]
+async fn finished_children_only_held_by_the_tree_are_not_reported() {
+    let root = fastn_context::Context::new("leaked-tree");
+    let worker = root.child("worker").build();
+    worker.spawn(async {}).await.unwrap();
+    worker.complete();
+    drop(worker);
+
+    // Still in the children list until the next status walk prunes it
+    assert!(leaked_under("leaked-tree").is_empty());
+    assert!(root.children().is_empty());
+}
+
+#[tokio::test]
+async fn children_outliving_their_parent_are_reported() {
+    let root = fastn_context::Context::new("leaked-orphan");
+    let worker = root.child("worker").build();
+    worker.cancel();
+
+    drop(root);
+
+    assert_eq!(
+        leaked_under("leaked-orphan"),
+        ["leaked-orphan/worker (still referenced)"]
+    );
+}
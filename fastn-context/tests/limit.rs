@@ -0,0 +1,74 @@
+mod common;
+
+fn concurrency(ctx: &fastn_context::Context) -> (usize, usize) {
+    let status = ctx.status().concurrency.unwrap();
+    (status.in_flight, status.queued)
+}
+
+#[tokio::test]
+async fn tasks_over_the_limit_queue() {
+    let app = fastn_context::Context::new("app");
+    let pool = app.child("pool").max_concurrent_tasks(1).build();
+    let (release, released) = tokio::sync::oneshot::channel::<()>();
+
+    let first = pool.spawn(async move {
+        let _ = released.await;
+    });
+    let second = pool.spawn(async {});
+    common::wait_until(|| concurrency(&pool) == (1, 1)).await;
+    assert!(!second.is_finished());
+
+    release.send(()).unwrap();
+    first.await.unwrap();
+    second.await.unwrap();
+    assert_eq!(concurrency(&pool), (0, 0));
+}
+
+#[tokio::test]
+async fn try_spawn_fails_when_every_slot_is_taken() {
+    let app = fastn_context::Context::new("app");
+    let pool = app.child("pool").max_concurrent_tasks(1).build();
+    pool.spawn(std::future::pending::<()>());
+    common::wait_until(|| concurrency(&pool) == (1, 0)).await;
+
+    let refused = pool.try_spawn(async {}).unwrap_err();
+
+    assert_eq!(refused.limit, 1);
+    pool.shutdown(std::time::Duration::ZERO).await;
+}
+
+#[tokio::test]
+async fn aborted_queued_tasks_are_no_longer_queued() {
+    let app = fastn_context::Context::new("app");
+    let pool = app.child("pool").max_concurrent_tasks(1).build();
+    let running = pool.spawn(std::future::pending::<()>());
+    let queued = pool.spawn(async {});
+    common::wait_until(|| concurrency(&pool) == (1, 1)).await;
+
+    queued.abort();
+    running.abort();
+    let _ = (queued.await, running.await);
+
+    assert_eq!(concurrency(&pool), (0, 0));
+}
+
+#[tokio::test]
+async fn forced_shutdown_clears_the_queue() {
+    let app = fastn_context::Context::new("app");
+    let pool = app.child("pool").max_concurrent_tasks(1).build();
+    pool.spawn(std::future::pending::<()>());
+    pool.spawn(std::future::pending::<()>());
+    common::wait_until(|| concurrency(&pool) == (1, 1)).await;
+
+    let report = app.shutdown(std::time::Duration::from_millis(10)).await;
+
+    assert_eq!(report.aborted_tasks(), 2);
+    common::wait_until(|| concurrency(&pool) == (0, 0)).await;
+}
+
+#[tokio::test]
+#[should_panic(expected = "needs a limit of at least 1")]
+async fn a_limit_of_zero_is_refused() {
+    let app = fastn_context::Context::new("app");
+    let _ = app.child("jobs").max_concurrent_tasks(0);
+}
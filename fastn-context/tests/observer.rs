@@ -0,0 +1,55 @@
+/// A context as seen by the observer when it was created
+#[derive(Debug, Clone, PartialEq)]
+struct Created {
+    path: String,
+    labels: Vec<String>,
+    detached: bool,
+}
+
+#[derive(Clone, Default)]
+struct Recorder(std::sync::Arc<std::sync::Mutex<Vec<Created>>>);
+
+impl fastn_context::ContextObserver for Recorder {
+    fn on_created(&self, context: &fastn_context::Context) {
+        self.0.lock().unwrap().push(Created {
+            path: context.path().to_string(),
+            labels: context.labels(),
+            detached: context.is_detached(),
+        });
+    }
+}
+
+// The observer is process wide, so everything is checked in a single test.
+#[tokio::test]
+async fn children_are_announced_fully_configured() {
+    let recorder = Recorder::default();
+    fastn_context::set_observer(recorder.clone());
+    let app = fastn_context::Context::new("observed");
+
+    let _audit = app.child("audit").label("tenant:acme").detach().build();
+    drop(app.child("dropped").label("tenant:acme"));
+
+    let created: Vec<_> = recorder
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|created| created.path.starts_with("observed"))
+        .cloned()
+        .collect();
+    assert_eq!(
+        created,
+        [
+            Created {
+                path: "observed".to_string(),
+                labels: vec![],
+                detached: false,
+            },
+            Created {
+                path: "observed/audit".to_string(),
+                labels: vec!["tenant:acme".to_string()],
+                detached: true,
+            },
+        ]
+    );
+}
@@ -0,0 +1,92 @@
+type Order = std::sync::Arc<std::sync::Mutex<Vec<&'static str>>>;
+
+/// Hook that records `name` in `order` when it runs
+fn hook(order: &Order, name: &'static str) -> impl std::future::Future<Output = ()> {
+    let order = order.clone();
+    async move {
+        tokio::task::yield_now().await;
+        order.lock().unwrap().push(name);
+    }
+}
+
+#[tokio::test]
+async fn registering_a_hook_spawns_nothing() {
+    let ctx = fastn_context::Context::new("app");
+    ctx.on_cancel(async {});
+    ctx.on_cancel(async {});
+
+    assert_eq!(ctx.task_count(), 0);
+}
+
+#[tokio::test]
+async fn hooks_run_lifo_before_shutdown_returns() {
+    let order = Order::default();
+    let ctx = fastn_context::Context::new("app");
+    ctx.on_cancel(hook(&order, "first"));
+    ctx.on_cancel(hook(&order, "second"));
+
+    let report = ctx.shutdown(std::time::Duration::from_secs(5)).await;
+
+    assert!(report.is_clean(), "{report}");
+    assert_eq!(*order.lock().unwrap(), ["second", "first"]);
+    assert_eq!(ctx.task_count(), 0);
+}
+
+#[tokio::test]
+async fn shutdown_waits_for_hooks_of_descendants() {
+    let order = Order::default();
+    let app = fastn_context::Context::new("app");
+    let db = app.child("db").build();
+    db.on_cancel(hook(&order, "db"));
+    let pool = db.child("pool").build();
+    pool.on_cancel(hook(&order, "pool"));
+
+    let report = app.shutdown(std::time::Duration::from_secs(5)).await;
+
+    assert!(report.is_clean(), "{report}");
+    let mut order = order.lock().unwrap().clone();
+    order.sort();
+    assert_eq!(order, ["db", "pool"]);
+}
+
+#[tokio::test]
+async fn hook_registered_after_cancel_runs_right_away() {
+    let order = Order::default();
+    let ctx = fastn_context::Context::new("app");
+    ctx.cancel();
+    ctx.on_cancel(hook(&order, "late"));
+
+    ctx.close_and_wait().await;
+
+    assert_eq!(*order.lock().unwrap(), ["late"]);
+}
+
+#[tokio::test]
+async fn hooks_run_when_the_external_token_is_cancelled() {
+    let order = Order::default();
+    let token = tokio_util::sync::CancellationToken::new();
+    let ctx = fastn_context::Context::from_token("app", &token);
+    ctx.on_cancel(hook(&order, "external"));
+
+    token.cancel();
+    tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        while order.lock().unwrap().is_empty() {
+            tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(*order.lock().unwrap(), ["external"]);
+}
+
+#[tokio::test]
+async fn hooks_do_not_keep_a_finished_child_in_the_tree() {
+    let app = fastn_context::Context::new("app");
+    let child = app.child("worker").build();
+    child.on_cancel(async {});
+    child.complete();
+    drop(child);
+
+    assert!(app.status().children.is_empty());
+}
@@ -0,0 +1,37 @@
+mod common;
+
+// The hook and the global context are process wide, so everything is checked in
+// a single test.
+#[tokio::test]
+async fn only_unhandled_panics_cancel_the_global_context() {
+    fastn_context::install_panic_hook();
+    let app = fastn_context::global().child("app").build();
+
+    let handled = app.spawn(async { panic!("handled by the task's context") });
+    assert!(handled.await.unwrap_err().is_panic());
+    assert_eq!(app.panics(), ["handled by the task's context"]);
+    assert!(!fastn_context::global().is_cancelled());
+
+    let supervisor =
+        fastn_context::Supervisor::new(&app, "workers").policy(fastn_context::RestartPolicy::Never);
+    supervisor
+        .spawn("flaky", |_| async {
+            panic!("handled by the supervisor");
+            #[allow(unreachable_code)]
+            Ok::<_, std::io::Error>(())
+        })
+        .await
+        .unwrap();
+    assert!(!fastn_context::global().is_cancelled());
+
+    let thread = std::thread::spawn(|| panic!("nobody handles this"));
+    assert!(thread.join().is_err());
+
+    common::wait_until(|| fastn_context::global().is_cancelled()).await;
+    match fastn_context::global().cancel_reason() {
+        Some(fastn_context::CancelReason::Panicked(message)) => {
+            assert!(message.starts_with("nobody handles this"), "{message}");
+        }
+        reason => panic!("unexpected reason {reason:?}"),
+    }
+}
@@ -0,0 +1,70 @@
+type Log = std::sync::Arc<std::sync::Mutex<Vec<String>>>;
+
+/// Spawn a task under `name` that logs its cancellation, then takes `cleanup` to
+/// finish
+fn spawn_logging(
+    app: &fastn_context::Context,
+    name: &'static str,
+    phase: Option<u32>,
+    cleanup: std::time::Duration,
+    log: &Log,
+) {
+    let log = log.clone();
+    let mut child = app.child(name);
+    if let Some(phase) = phase {
+        child = child.shutdown_phase(phase);
+    }
+    child.spawn(move |ctx| async move {
+        ctx.cancelled().await;
+        log.lock().unwrap().push(format!("{name} cancelled"));
+        tokio::time::sleep(cleanup).await;
+        log.lock().unwrap().push(format!("{name} done"));
+    });
+}
+
+#[tokio::test(start_paused = true)]
+async fn phases_drain_one_after_the_other() {
+    let app = fastn_context::Context::new("app");
+    let log = Log::default();
+    let cleanup = std::time::Duration::from_millis(10);
+    spawn_logging(&app, "db", Some(2), cleanup, &log);
+    spawn_logging(&app, "cache", None, cleanup, &log);
+    spawn_logging(&app, "http", Some(0), cleanup, &log);
+    spawn_logging(&app, "jobs", Some(1), cleanup, &log);
+
+    let report = app.shutdown(std::time::Duration::from_secs(5)).await;
+
+    assert!(report.is_clean(), "{report}");
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            "http cancelled",
+            "http done",
+            "jobs cancelled",
+            "jobs done",
+            "db cancelled",
+            "db done",
+            "cache cancelled",
+            "cache done",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_phase_that_does_not_drain_holds_back_later_ones() {
+    let app = fastn_context::Context::new("app");
+    let log = Log::default();
+    spawn_logging(
+        &app,
+        "http",
+        Some(0),
+        std::time::Duration::from_secs(60),
+        &log,
+    );
+    spawn_logging(&app, "db", Some(1), std::time::Duration::ZERO, &log);
+
+    let report = app.shutdown(std::time::Duration::from_secs(1)).await;
+
+    assert!(report.timed_out);
+    assert_eq!(*log.lock().unwrap(), ["http cancelled"]);
+}
@@ -0,0 +1,85 @@
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const PARENT_ID: &str = "00f067aa0ba902b7";
+
+fn header(version: &str, trace_id: &str, parent_id: &str, flags: &str) -> String {
+    format!("{version}-{trace_id}-{parent_id}-{flags}")
+}
+
+#[test]
+fn a_valid_traceparent_round_trips() {
+    let value = header("00", TRACE_ID, PARENT_ID, "01");
+
+    let parsed = fastn_context::TraceParent::parse(&value).unwrap();
+
+    assert_eq!(parsed.trace_id, 0x4bf92f3577b34da6a3ce929d0e0e4736);
+    assert_eq!(parsed.parent_id, 0x00f067aa0ba902b7);
+    assert!(parsed.sampled);
+    assert_eq!(parsed.to_string(), value);
+}
+
+#[test]
+fn uppercase_and_signed_hex_are_rejected() {
+    for value in [
+        header("00", &TRACE_ID.to_uppercase(), PARENT_ID, "01"),
+        header("00", TRACE_ID, &PARENT_ID.to_uppercase(), "01"),
+        header("00", TRACE_ID, PARENT_ID, "0A"),
+        header("00", &format!("+{}", &TRACE_ID[1..]), PARENT_ID, "01"),
+        header("00", TRACE_ID, &format!("+{}", &PARENT_ID[1..]), "01"),
+        header("00", TRACE_ID, PARENT_ID, "+1"),
+    ] {
+        assert_eq!(fastn_context::TraceParent::parse(&value), None, "{value}");
+    }
+}
+
+#[test]
+fn fields_of_the_wrong_length_are_rejected() {
+    for value in [
+        header("0", TRACE_ID, PARENT_ID, "01"),
+        header("00", &TRACE_ID[1..], PARENT_ID, "01"),
+        header("00", TRACE_ID, &PARENT_ID[1..], "01"),
+        header("00", TRACE_ID, PARENT_ID, "1"),
+        header("00", TRACE_ID, PARENT_ID, "001"),
+    ] {
+        assert_eq!(fastn_context::TraceParent::parse(&value), None, "{value}");
+    }
+}
+
+#[test]
+fn zero_ids_are_rejected() {
+    let zero_trace = header("00", &"0".repeat(32), PARENT_ID, "01");
+    let zero_parent = header("00", TRACE_ID, &"0".repeat(16), "01");
+
+    assert_eq!(fastn_context::TraceParent::parse(&zero_trace), None);
+    assert_eq!(fastn_context::TraceParent::parse(&zero_parent), None);
+}
+
+#[test]
+fn versions_are_checked() {
+    let invalid = header("ff", TRACE_ID, PARENT_ID, "01");
+    let extra_fields = format!("{}-extra", header("00", TRACE_ID, PARENT_ID, "01"));
+    let future = format!("{}-extra", header("01", TRACE_ID, PARENT_ID, "01"));
+
+    assert_eq!(fastn_context::TraceParent::parse(&invalid), None);
+    assert_eq!(fastn_context::TraceParent::parse(&extra_fields), None);
+    assert!(fastn_context::TraceParent::parse(&future).is_some());
+}
+
+#[tokio::test]
+async fn baggage_values_are_percent_decoded() {
+    let app = fastn_context::Context::new("app");
+
+    let request = app
+        .child("request")
+        .from_headers([(
+            "Baggage",
+            "user=jane%20doe;ttl=5, sign=%+5, trailing=100%, plain=x",
+        )])
+        .build();
+
+    let baggage = request.baggage();
+    assert_eq!(baggage["user"], "jane doe");
+    assert_eq!(baggage["sign"], "%+5");
+    assert_eq!(baggage["trailing"], "100%");
+    assert_eq!(baggage["plain"], "x");
+    assert_eq!(baggage.len(), 4);
+}
@@ -0,0 +1,146 @@
+#[tokio::test]
+async fn try_child_fails_over_max_children() {
+    let app = fastn_context::Context::new("app");
+    let pool = app.child("pool").max_children(1).build();
+    let first = pool.try_child("worker").unwrap().build();
+
+    let refused = pool.try_child("worker").err().unwrap();
+    assert_eq!(
+        refused,
+        fastn_context::ChildError::Quota(fastn_context::QuotaExceeded::Children {
+            path: "app/pool".to_string(),
+            limit: 1,
+        })
+    );
+
+    first.complete();
+    assert!(pool.try_child("worker").is_ok());
+}
+
+#[tokio::test]
+#[should_panic(expected = "app/pool reached its quota of 1 children")]
+async fn build_panics_over_max_children() {
+    let app = fastn_context::Context::new("app");
+    let pool = app.child("pool").max_children(1).build();
+    let _first = pool.child("worker").build();
+
+    pool.child("worker").build();
+}
+
+#[tokio::test]
+async fn max_depth_applies_to_the_whole_subtree() {
+    let app = fastn_context::Context::new("app");
+    let limited = app.child("limited").max_depth(1).build();
+    let worker = limited.child("worker").build();
+
+    let refused = worker.try_child("task").err().unwrap();
+
+    assert_eq!(
+        refused,
+        fastn_context::ChildError::Quota(fastn_context::QuotaExceeded::Depth {
+            path: "app/limited".to_string(),
+            limit: 1,
+        })
+    );
+}
+
+#[tokio::test]
+#[should_panic(expected = "app/limited allows at most 1 levels of children")]
+async fn child_or_existing_panics_over_max_depth() {
+    let app = fastn_context::Context::new("app");
+    let limited = app.child("limited").max_depth(1).build();
+
+    limited
+        .child_or_existing("worker")
+        .child_or_existing("task");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_children_do_not_overshoot_the_quota() {
+    let app = fastn_context::Context::new("app");
+    let pool = app.child("pool").max_children(3).build();
+
+    let attempts: Vec<_> = (0..32)
+        .map(|_| {
+            let pool = pool.clone();
+            tokio::spawn(async move { pool.child("worker").try_build() })
+        })
+        .collect();
+    let mut created = Vec::new();
+    for attempt in attempts {
+        created.extend(attempt.await.unwrap().ok());
+    }
+
+    assert_eq!(created.len(), 3);
+    assert_eq!(pool.children().len(), 3);
+}
+
+#[tokio::test]
+async fn try_spawn_tracked_fails_over_max_tracked_tasks() {
+    let app = fastn_context::Context::new("app");
+    let jobs = app.child("jobs").max_tracked_tasks(1).build();
+    jobs.spawn(std::future::pending::<()>());
+
+    let refused = jobs.try_spawn_tracked(async {}).unwrap_err();
+
+    assert_eq!(
+        refused,
+        fastn_context::QuotaExceeded::TrackedTasks {
+            path: "app/jobs".to_string(),
+            limit: 1,
+        }
+    );
+    assert_eq!(jobs.task_count(), 1);
+    app.shutdown(std::time::Duration::ZERO).await;
+}
+
+#[tokio::test]
+#[should_panic(expected = "app/jobs reached its quota of 1 tasks")]
+async fn spawn_panics_over_max_tracked_tasks() {
+    let app = fastn_context::Context::new("app");
+    let jobs = app.child("jobs").max_tracked_tasks(1).build();
+    jobs.spawn(std::future::pending::<()>());
+
+    jobs.spawn_blocking(|_| ());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_spawns_do_not_overshoot_the_quota() {
+    let app = fastn_context::Context::new("app");
+    let jobs = app.child("jobs").max_tracked_tasks(3).build();
+
+    let attempts: Vec<_> = (0..32)
+        .map(|_| {
+            let jobs = jobs.clone();
+            tokio::spawn(
+                async move { jobs.try_spawn_tracked(std::future::pending::<()>()).is_ok() },
+            )
+        })
+        .collect();
+    let mut spawned = 0;
+    for attempt in attempts {
+        spawned += usize::from(attempt.await.unwrap());
+    }
+
+    assert_eq!(spawned, 3);
+    assert_eq!(jobs.task_count(), 3);
+    app.shutdown(std::time::Duration::ZERO).await;
+}
+
+#[tokio::test]
+async fn cleanup_runs_on_a_context_at_its_quota() {
+    let app = fastn_context::Context::new("app");
+    let jobs = app.child("jobs").max_tracked_tasks(1).build();
+    let task_ctx = jobs.clone();
+    jobs.spawn(async move { task_ctx.cancelled().await });
+    let cleaned = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let hook_cleaned = cleaned.clone();
+    jobs.on_cancel(async move {
+        hook_cleaned.store(true, std::sync::atomic::Ordering::SeqCst);
+    });
+
+    let report = app.shutdown(std::time::Duration::from_secs(5)).await;
+
+    assert!(report.is_clean(), "{report}");
+    assert!(cleaned.load(std::sync::atomic::Ordering::SeqCst));
+}
@@ -0,0 +1,59 @@
+#[tokio::test(start_paused = true)]
+async fn a_second_of_permits_is_available_as_a_burst() {
+    let app = fastn_context::Context::new("app");
+    app.rate_limit(10.0);
+    let start = tokio::time::Instant::now();
+
+    for _ in 0..10 {
+        app.acquire().await.unwrap();
+    }
+    assert_eq!(start.elapsed(), std::time::Duration::ZERO);
+
+    app.acquire().await.unwrap();
+    assert!(start.elapsed() >= std::time::Duration::from_millis(100));
+}
+
+#[tokio::test(start_paused = true)]
+async fn permits_refill_at_the_configured_rate() {
+    let app = fastn_context::Context::new("app");
+    let worker = app.child("worker").build();
+    app.rate_limit(10.0);
+    for _ in 0..10 {
+        worker.acquire().await.unwrap();
+    }
+    let start = tokio::time::Instant::now();
+
+    for _ in 0..20 {
+        worker.acquire().await.unwrap();
+    }
+
+    let elapsed = start.elapsed();
+    assert!(
+        elapsed >= std::time::Duration::from_millis(2000),
+        "{elapsed:?}"
+    );
+    assert!(
+        elapsed < std::time::Duration::from_millis(2100),
+        "{elapsed:?}"
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancelling_a_waiting_acquirer_fails_it() {
+    let app = fastn_context::Context::new("app");
+    app.rate_limit(1.0);
+    app.acquire().await.unwrap();
+    let worker = app.child("worker").build();
+
+    let waiting = tokio::spawn({
+        let worker = worker.clone();
+        async move { worker.acquire().await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert!(!waiting.is_finished());
+
+    worker.cancel();
+
+    assert!(waiting.await.unwrap().is_err());
+    assert!(!app.is_cancelled());
+}
@@ -0,0 +1,41 @@
+#[tokio::test]
+async fn finished_children_are_counted_instead_of_listed() {
+    let app = fastn_context::Context::new("app");
+    let requests = app.child("requests").roll_up().build();
+    for _ in 0..3 {
+        requests.child("request").spawn(|_| async {}).await.unwrap();
+    }
+
+    let status = requests.status();
+
+    assert!(status.children.is_empty());
+    let roll_up = status.roll_up.unwrap();
+    assert_eq!((roll_up.completed, roll_up.errors), (3, 0));
+}
+
+#[tokio::test]
+async fn shutdown_waits_for_tasks_left_behind_by_a_rolled_up_child() {
+    let app = fastn_context::Context::new("app");
+    let requests = app.child("requests").roll_up().build();
+    let flushed = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let task_flushed = flushed.clone();
+    requests
+        .child("request")
+        .spawn(|ctx| async move {
+            ctx.spawn(async move {
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                task_flushed.store(true, std::sync::atomic::Ordering::SeqCst);
+            });
+        })
+        .await
+        .unwrap();
+    assert_eq!(requests.status().children.len(), 1);
+
+    let report = app.shutdown(std::time::Duration::from_secs(5)).await;
+
+    assert!(report.is_clean(), "{report}");
+    assert!(flushed.load(std::sync::atomic::Ordering::SeqCst));
+    let status = requests.status();
+    assert!(status.children.is_empty());
+    assert_eq!(status.roll_up.unwrap().completed, 1);
+}
@@ -0,0 +1,95 @@
+/// Run `scope` with a timeout, so a scope that never returns fails the test
+async fn bounded<T>(scope: impl std::future::Future<Output = T>) -> T {
+    tokio::time::timeout(std::time::Duration::from_secs(5), scope)
+        .await
+        .expect("scope did not return")
+}
+
+#[tokio::test]
+async fn success_waits_for_tasks_before_cancelling() {
+    let app = fastn_context::Context::new("app");
+    let finished = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+    let scope = bounded(app.scope("batch", |scope| {
+        let finished = finished.clone();
+        async move {
+            let task_ctx = scope.clone();
+            scope.spawn(async move {
+                tokio::select! {
+                    _ = task_ctx.cancelled() => {}
+                    _ = tokio::time::sleep(std::time::Duration::from_millis(20)) => {
+                        finished.store(true, std::sync::atomic::Ordering::SeqCst);
+                    }
+                }
+            });
+            Ok::<_, std::io::Error>(scope)
+        }
+    }))
+    .await
+    .unwrap();
+
+    assert!(finished.load(std::sync::atomic::Ordering::SeqCst));
+    assert_eq!(scope.task_count(), 0);
+}
+
+#[tokio::test]
+async fn success_runs_on_cancel_hooks() {
+    let app = fastn_context::Context::new("app");
+    let cleaned = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+    bounded(app.scope("batch", |scope| {
+        let cleaned = cleaned.clone();
+        async move {
+            scope.on_cancel(async move {
+                cleaned.store(true, std::sync::atomic::Ordering::SeqCst);
+            });
+            Ok::<_, std::io::Error>(())
+        }
+    }))
+    .await
+    .unwrap();
+
+    assert!(cleaned.load(std::sync::atomic::Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn success_stops_children_waiting_for_cancellation() {
+    let app = fastn_context::Context::new("app");
+
+    let listener = bounded(app.scope("server", |scope| async move {
+        let listener = scope.child("listener").build();
+        let task_ctx = listener.clone();
+        listener.spawn(async move { task_ctx.cancelled().await });
+        Ok::<_, std::io::Error>(listener)
+    }))
+    .await
+    .unwrap();
+
+    assert!(listener.is_cancelled());
+    assert_eq!(listener.task_count(), 0);
+    assert!(!app.is_cancelled());
+}
+
+#[tokio::test]
+async fn error_cancels_and_drains_right_away() {
+    let app = fastn_context::Context::new("app");
+    let (scope_sender, scope_receiver) = tokio::sync::oneshot::channel();
+
+    let result: Result<(), String> = bounded(app.scope("batch", |scope| async move {
+        let task_ctx = scope.clone();
+        scope.spawn(async move { task_ctx.cancelled().await });
+        scope_sender.send(scope).ok();
+        Err("boom".to_string())
+    }))
+    .await;
+
+    assert_eq!(result, Err("boom".to_string()));
+    let scope = scope_receiver.await.unwrap();
+    assert_eq!(
+        scope.cancel_reason(),
+        Some(fastn_context::CancelReason::Failure(
+            "app/batch: boom".to_string()
+        ))
+    );
+    assert_eq!(scope.task_count(), 0);
+}
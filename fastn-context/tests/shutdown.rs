@@ -0,0 +1,87 @@
+#[tokio::test]
+async fn cooperative_tasks_drain_cleanly() {
+    let app = fastn_context::Context::new("app");
+    app.child("worker")
+        .spawn(|ctx| async move { ctx.cancelled().await });
+    let task_ctx = app.clone();
+    app.spawn(async move { task_ctx.cancelled().await });
+
+    let report = app.shutdown(std::time::Duration::from_secs(5)).await;
+
+    assert!(report.is_clean(), "{report}");
+    assert!(!report.timed_out);
+    assert!(report.finished.contains(&"app".to_string()));
+    assert!(report.finished.contains(&"app/worker".to_string()));
+    assert_eq!(
+        app.cancel_reason(),
+        Some(fastn_context::CancelReason::Shutdown)
+    );
+    assert_eq!(app.task_count(), 0);
+}
+
+#[tokio::test]
+async fn tasks_ignoring_cancellation_are_force_aborted() {
+    let app = fastn_context::Context::new("app");
+    let worker = app.child("worker").build();
+    let stuck = worker.spawn_named("stuck", std::future::pending::<()>());
+
+    let report = app.shutdown(std::time::Duration::from_millis(20)).await;
+
+    assert!(report.timed_out);
+    assert_eq!(report.aborted_tasks(), 1);
+    assert_eq!(report.aborted[0].path, "app/worker");
+    assert_eq!(report.aborted[0].tasks[0].name.as_deref(), Some("stuck"));
+    assert!(stuck.await.unwrap_err().is_cancelled());
+    assert_eq!(worker.task_count(), 0);
+}
+
+#[tokio::test]
+async fn slow_cleanup_within_the_grace_period_is_waited_for() {
+    let app = fastn_context::Context::new("app");
+    let flushed = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let task_flushed = flushed.clone();
+    app.child("writer").spawn(|ctx| async move {
+        ctx.cancelled().await;
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        task_flushed.store(true, std::sync::atomic::Ordering::SeqCst);
+    });
+
+    let report = app.shutdown(std::time::Duration::from_secs(5)).await;
+
+    assert!(report.is_clean(), "{report}");
+    assert!(flushed.load(std::sync::atomic::Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn phases_shut_down_in_ascending_order() {
+    let order = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let app = fastn_context::Context::new("app");
+    for (name, phase) in [("db", 2), ("http", 0), ("jobs", 1)] {
+        let order = order.clone();
+        app.child(name)
+            .shutdown_phase(phase)
+            .spawn(move |ctx| async move {
+                ctx.cancelled().await;
+                order.lock().unwrap().push(name);
+            });
+    }
+
+    let report = app.shutdown(std::time::Duration::from_secs(5)).await;
+
+    assert!(report.is_clean(), "{report}");
+    assert_eq!(*order.lock().unwrap(), ["http", "jobs", "db"]);
+}
+
+#[tokio::test]
+async fn detached_children_are_left_running() {
+    let app = fastn_context::Context::new("app");
+    let audit = app.child("audit").detach().build();
+    audit.spawn(std::future::pending::<()>());
+
+    let report = app.shutdown(std::time::Duration::from_millis(20)).await;
+
+    assert!(report.is_clean(), "{report}");
+    assert!(!audit.is_cancelled());
+    assert_eq!(audit.task_count(), 1);
+    audit.shutdown(std::time::Duration::ZERO).await;
+}
@@ -0,0 +1,11 @@
+// Signal handlers are process wide, so everything is checked in a single test.
+#[tokio::test]
+async fn termination_signals_are_handled_once() {
+    fastn_context::install_signal_handlers().unwrap();
+
+    let again = fastn_context::install_signal_handlers().unwrap_err();
+    let termination = fastn_context::Termination::new().install().unwrap_err();
+
+    assert_eq!(again.kind(), std::io::ErrorKind::AlreadyExists);
+    assert_eq!(termination.kind(), std::io::ErrorKind::AlreadyExists);
+}
@@ -0,0 +1,56 @@
+mod common;
+
+/// Executor running every task to completion on its own thread
+fn thread_per_task(task: std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>) {
+    std::thread::spawn(move || tokio_test::block_on(task));
+}
+
+#[tokio::test]
+async fn tasks_on_other_executors_run_under_the_context() {
+    let app = fastn_context::Context::new("app");
+
+    let current = app
+        .spawn_with(&thread_per_task, async {
+            fastn_context::Context::current()
+        })
+        .await
+        .unwrap();
+
+    assert!(std::sync::Arc::ptr_eq(&current, &app));
+    assert_eq!(app.task_count(), 0);
+}
+
+#[tokio::test]
+async fn close_and_wait_waits_for_tasks_on_other_executors() {
+    let app = fastn_context::Context::new("app");
+    let (release, released) = tokio::sync::oneshot::channel::<()>();
+    let task = app.spawn_with(&thread_per_task, async move {
+        let _ = released.await;
+    });
+
+    let waiting = tokio::spawn({
+        let app = app.clone();
+        async move { app.close_and_wait().await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    assert!(!waiting.is_finished());
+    assert_eq!(app.task_count(), 1);
+
+    release.send(()).unwrap();
+    tokio::time::timeout(std::time::Duration::from_secs(5), waiting)
+        .await
+        .expect("close_and_wait() did not return")
+        .unwrap();
+    assert_eq!(task.await, Ok(()));
+}
+
+#[tokio::test]
+async fn aborted_tasks_report_aborted() {
+    let app = fastn_context::Context::new("app");
+    let task = app.spawn_with(&thread_per_task, std::future::pending::<()>());
+
+    task.abort();
+
+    assert_eq!(task.await, Err(fastn_context::TaskError::Aborted));
+    common::wait_until(|| app.task_count() == 0).await;
+}
@@ -0,0 +1,23 @@
+#[tokio::test]
+#[allow(deprecated)]
+async fn timing_of_running_and_finished_contexts() {
+    let app = fastn_context::Context::new("app");
+    let before = std::time::SystemTime::now();
+    let worker = app.child("worker").build();
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+    let running = worker.status();
+    assert!(running.created_at >= before);
+    assert!(running.elapsed >= std::time::Duration::from_millis(10));
+    assert_eq!(running.duration, running.elapsed);
+    assert_eq!(running.total_duration, None);
+
+    worker.complete();
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+    let finished = worker.status();
+    let total = finished.total_duration.unwrap();
+    assert!(total >= std::time::Duration::from_millis(10));
+    assert!(total < finished.elapsed);
+    assert_eq!(worker.total_duration(), Some(total));
+}
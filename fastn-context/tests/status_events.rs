@@ -0,0 +1,65 @@
+#![cfg(feature = "http")]
+
+/// Next SSE event of `body` as (name, JSON data), skipping keep-alives
+async fn next_event(body: &mut fastn_context::http::StatusBody) -> (String, serde_json::Value) {
+    loop {
+        let frame = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            std::future::poll_fn(|cx| {
+                http_body::Body::poll_frame(std::pin::Pin::new(&mut *body), cx)
+            }),
+        )
+        .await
+        .expect("no event within 5s")
+        .expect("stream ended")
+        .unwrap();
+        let data = frame.into_data().unwrap();
+        let text = std::str::from_utf8(&data).unwrap();
+        if text.starts_with(':') {
+            continue;
+        }
+
+        let (name, data) = text
+            .trim_end()
+            .strip_prefix("event: ")
+            .and_then(|event| event.split_once("\ndata: "))
+            .expect("malformed event");
+        return (name.to_string(), serde_json::from_str(data).unwrap());
+    }
+}
+
+fn changed_ids(delta: &serde_json::Value) -> Vec<u64> {
+    delta["changed"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|node| node["id"].as_u64().unwrap())
+        .collect()
+}
+
+// The status stream is process wide, so everything is checked in a single test.
+#[tokio::test]
+async fn deltas_only_carry_changed_contexts_and_reconnects_start_with_a_snapshot() {
+    let busy = fastn_context::global().child("sse-busy").build();
+    let idle = fastn_context::global().child("sse-idle").build();
+
+    let mut body = fastn_context::http::status_events();
+    let (name, snapshot) = next_event(&mut body).await;
+    assert_eq!(name, "snapshot");
+    assert!(snapshot.to_string().contains("sse-idle"));
+
+    busy.set_message("working");
+    let (name, delta) = next_event(&mut body).await;
+    assert_eq!(name, "delta");
+    assert!(changed_ids(&delta).contains(&busy.id()));
+    assert!(!changed_ids(&delta).contains(&idle.id()));
+    assert!(!changed_ids(&delta).contains(&fastn_context::global().id()));
+    drop(body);
+
+    let late = fastn_context::global().child("sse-late").build();
+    let mut body = fastn_context::http::status_events();
+    let (name, snapshot) = next_event(&mut body).await;
+    assert_eq!(name, "snapshot");
+    assert!(snapshot.to_string().contains("sse-late"));
+    drop(late);
+}
@@ -0,0 +1,25 @@
+/// Subscribe on a fresh runtime and wait for the snapshot after a tree change
+fn next_snapshot_on_a_new_runtime(name: &str) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let mut snapshots = fastn_context::status_stream();
+        let ctx = fastn_context::global().child(name).build();
+
+        tokio::time::timeout(std::time::Duration::from_secs(5), snapshots.changed())
+            .await
+            .expect("no snapshot published")
+            .unwrap();
+        ctx.complete();
+    });
+}
+
+// Every `#[tokio::test]` (and `#[fastn_context::test]`) gets its own runtime, the
+// publisher must survive the first one shutting down
+#[test]
+fn snapshots_are_published_across_runtimes() {
+    next_snapshot_on_a_new_runtime("first");
+    next_snapshot_on_a_new_runtime("second");
+}
@@ -0,0 +1,120 @@
+/// Run `task` under a supervisor configured by `configure`, returning the
+/// (virtual) times it was started at and its context
+async fn supervise(
+    configure: impl FnOnce(fastn_context::Supervisor) -> fastn_context::Supervisor,
+    result: Result<(), &'static str>,
+) -> (
+    Vec<std::time::Duration>,
+    std::sync::Arc<fastn_context::Context>,
+) {
+    let app = fastn_context::Context::new("app");
+    let supervisor = configure(fastn_context::Supervisor::new(&app, "workers"));
+    let start = tokio::time::Instant::now();
+    let starts = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let task_ctx = std::sync::Arc::new(std::sync::OnceLock::new());
+
+    supervisor
+        .spawn("worker", {
+            let starts = starts.clone();
+            let task_ctx = task_ctx.clone();
+            move |ctx| {
+                starts.lock().unwrap().push(start.elapsed());
+                task_ctx.get_or_init(|| ctx);
+                async move { result }
+            }
+        })
+        .await
+        .unwrap();
+
+    let starts = starts.lock().unwrap().clone();
+    (starts, task_ctx.get().unwrap().clone())
+}
+
+fn millis(starts: &[u64]) -> Vec<std::time::Duration> {
+    starts
+        .iter()
+        .map(|ms| std::time::Duration::from_millis(*ms))
+        .collect()
+}
+
+#[tokio::test(start_paused = true)]
+async fn failures_restart_with_a_capped_backoff_until_max_restarts() {
+    let (starts, task_ctx) = supervise(
+        |supervisor| {
+            supervisor
+                .backoff(
+                    std::time::Duration::from_millis(100),
+                    std::time::Duration::from_millis(300),
+                )
+                .max_restarts(3)
+        },
+        Err("broken"),
+    )
+    .await;
+
+    assert_eq!(starts, millis(&[0, 100, 300, 600]));
+    assert_eq!(task_ctx.counter("restarts").get(), 3);
+    assert_eq!(
+        task_ctx.state(),
+        fastn_context::ContextState::Failed("broken".to_string())
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn on_failure_does_not_restart_a_successful_task() {
+    let (starts, task_ctx) = supervise(|supervisor| supervisor.max_restarts(3), Ok(())).await;
+
+    assert_eq!(starts, millis(&[0]));
+    assert_eq!(task_ctx.state(), fastn_context::ContextState::Completed);
+}
+
+#[tokio::test(start_paused = true)]
+async fn always_restarts_a_successful_task() {
+    let (starts, task_ctx) = supervise(
+        |supervisor| {
+            supervisor
+                .policy(fastn_context::RestartPolicy::Always)
+                .max_restarts(2)
+        },
+        Ok(()),
+    )
+    .await;
+
+    assert_eq!(starts, millis(&[0, 100, 300]));
+    assert_eq!(task_ctx.state(), fastn_context::ContextState::Completed);
+}
+
+#[tokio::test(start_paused = true)]
+async fn never_runs_a_failing_task_once() {
+    let (starts, task_ctx) = supervise(
+        |supervisor| supervisor.policy(fastn_context::RestartPolicy::Never),
+        Err("broken"),
+    )
+    .await;
+
+    assert_eq!(starts, millis(&[0]));
+    assert_eq!(
+        task_ctx.state(),
+        fastn_context::ContextState::Failed("broken".to_string())
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancelling_the_supervisor_stops_restarts() {
+    let app = fastn_context::Context::new("app");
+    let supervisor = fastn_context::Supervisor::new(&app, "workers");
+    let starts = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+    let supervised = supervisor.spawn("worker", {
+        let starts = starts.clone();
+        move |_| {
+            starts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            async { Err("broken") }
+        }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    supervisor.context().cancel();
+    supervised.await.unwrap();
+
+    assert_eq!(starts.load(std::sync::atomic::Ordering::SeqCst), 1);
+}
@@ -0,0 +1,45 @@
+#[tokio::test]
+async fn cancel_flags_wake_parked_threads() {
+    let app = fastn_context::Context::new("app");
+    let flag = app.cancel_flag();
+    assert!(!flag.park_until_cancelled(std::time::Duration::from_millis(10)));
+
+    let parked =
+        std::thread::spawn(move || flag.park_until_cancelled(std::time::Duration::from_secs(5)));
+    app.cancel();
+
+    assert!(parked.join().unwrap());
+}
+
+#[tokio::test]
+async fn block_until_cancelled_returns_on_cancellation() {
+    let app = fastn_context::Context::new("app");
+    let ctx = app.clone();
+    let blocked = std::thread::spawn(move || ctx.block_until_cancelled());
+
+    app.cancel();
+
+    blocked.join().unwrap();
+}
+
+#[tokio::test]
+async fn shutdown_waits_for_sync_tasks() {
+    let app = fastn_context::Context::new("app");
+    let task = app.sync_task("worker");
+    let finished = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let thread_finished = finished.clone();
+    std::thread::spawn(move || {
+        while !task.is_cancelled() {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        thread_finished.store(true, std::sync::atomic::Ordering::SeqCst);
+        task.finish();
+    });
+    assert_eq!(app.task_count(), 1);
+
+    let report = app.shutdown(std::time::Duration::from_secs(5)).await;
+
+    assert!(report.is_clean(), "{report}");
+    assert!(finished.load(std::sync::atomic::Ordering::SeqCst));
+}
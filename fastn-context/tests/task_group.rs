@@ -0,0 +1,56 @@
+#[tokio::test]
+async fn tasks_count_against_the_context_until_joined() {
+    let app = fastn_context::Context::new("app");
+    let mut group = app.task_group();
+    let (release, released) = tokio::sync::oneshot::channel::<()>();
+
+    group.spawn(async move {
+        let _ = released.await;
+        1
+    });
+    group.spawn(async { panic!("broken") });
+    assert_eq!(app.task_count(), 2);
+
+    release.send(()).unwrap();
+    let results = group.join_all().await;
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+    assert!(
+        results
+            .iter()
+            .any(|result| matches!(result, Err(e) if e.is_panic()))
+    );
+    assert_eq!(app.task_count(), 0);
+}
+
+#[tokio::test]
+async fn cancelling_the_context_aborts_the_group() {
+    let app = fastn_context::Context::new("app");
+    let mut group = app.task_group::<()>();
+    group.spawn(std::future::pending());
+    group.spawn(std::future::pending());
+
+    app.cancel();
+
+    let results = tokio::time::timeout(std::time::Duration::from_secs(5), group.join_all())
+        .await
+        .expect("group not aborted");
+    assert!(
+        results
+            .iter()
+            .all(|result| matches!(result, Err(e) if e.is_cancelled()))
+    );
+}
+
+#[tokio::test]
+async fn dropping_the_group_aborts_its_tasks() {
+    let app = fastn_context::Context::new("app");
+    let mut group = app.task_group::<()>();
+    group.spawn(std::future::pending());
+
+    drop(group);
+
+    let report = app.shutdown(std::time::Duration::from_secs(5)).await;
+    assert!(report.is_clean(), "{report}");
+}
@@ -0,0 +1,114 @@
+#[tokio::test]
+async fn live_tasks_are_counted_until_they_finish() {
+    let ctx = fastn_context::Context::new("app");
+    let (release, released) = tokio::sync::oneshot::channel::<()>();
+
+    let handle = ctx.spawn(async move {
+        let _ = released.await;
+    });
+    assert_eq!(ctx.task_count(), 1);
+
+    release.send(()).unwrap();
+    handle.await.unwrap();
+    assert_eq!(ctx.task_count(), 0);
+}
+
+#[tokio::test]
+async fn aborted_tasks_are_no_longer_counted() {
+    let ctx = fastn_context::Context::new("app");
+    let handle = ctx.spawn(std::future::pending::<()>());
+    assert_eq!(ctx.task_count(), 1);
+
+    handle.abort();
+    assert!(handle.await.unwrap_err().is_cancelled());
+    assert_eq!(ctx.task_count(), 0);
+}
+
+#[tokio::test]
+async fn tasks_run_with_their_context_as_current() {
+    let ctx = fastn_context::Context::new("app");
+    let current = ctx
+        .spawn(async { fastn_context::Context::current().id() })
+        .await
+        .unwrap();
+    assert_eq!(current, ctx.id());
+}
+
+#[tokio::test]
+async fn tasks_are_notified_of_cancellation() {
+    let ctx = fastn_context::Context::new("app");
+    let task_ctx = ctx.clone();
+    let handle = ctx.spawn(async move {
+        task_ctx.cancelled().await;
+        "stopped"
+    });
+
+    ctx.cancel();
+    assert_eq!(handle.await.unwrap(), "stopped");
+}
+
+#[tokio::test]
+async fn status_reports_live_tasks() {
+    let ctx = fastn_context::Context::new("app");
+    let worker = ctx.child("worker").build();
+    worker.spawn(std::future::pending::<()>());
+    worker.spawn(std::future::pending::<()>());
+
+    let status = ctx.status();
+    assert_eq!(status.task_count, 0);
+    assert_eq!(status.children[0].task_count, 2);
+    ctx.shutdown(std::time::Duration::ZERO).await;
+}
+
+#[tokio::test]
+async fn spawn_child_tracks_the_task_on_the_child() {
+    let ctx = fastn_context::Context::new("app");
+    let (release, released) = tokio::sync::oneshot::channel::<()>();
+
+    let handle = ctx.spawn_child("worker", |_| async move {
+        let _ = released.await;
+    });
+    let worker = ctx.children().pop().unwrap();
+    assert_eq!((ctx.task_count(), worker.task_count()), (0, 1));
+
+    release.send(()).unwrap();
+    handle.await.unwrap();
+    assert_eq!(worker.task_count(), 0);
+}
+
+#[tokio::test]
+async fn close_and_wait_waits_for_the_whole_subtree() {
+    let app = fastn_context::Context::new("app");
+    let done = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    for delay in [10, 20] {
+        let done = done.clone();
+        app.child("worker").spawn(move |ctx| async move {
+            tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+            ctx.spawn(async move {
+                done.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            });
+        });
+    }
+
+    app.close_and_wait().await;
+
+    assert_eq!(done.load(std::sync::atomic::Ordering::SeqCst), 2);
+    assert!(!app.is_cancelled());
+}
+
+#[tokio::test]
+async fn close_and_wait_does_not_cancel() {
+    let app = fastn_context::Context::new("app");
+    let worker = app.child("worker").build();
+    let task_ctx = worker.clone();
+    worker.spawn(async move { task_ctx.cancelled().await });
+
+    let waited =
+        tokio::time::timeout(std::time::Duration::from_millis(20), app.close_and_wait()).await;
+
+    assert!(waited.is_err());
+    assert!(!worker.is_cancelled());
+    app.cancel();
+    app.close_and_wait().await;
+    assert_eq!(worker.task_count(), 0);
+}
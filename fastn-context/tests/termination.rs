@@ -0,0 +1,20 @@
+// Signal handlers are process wide, so everything is checked in a single test.
+#[tokio::test]
+async fn a_stop_request_drains_and_shuts_down() {
+    let termination = fastn_context::Termination::new()
+        .drain_delay(std::time::Duration::ZERO)
+        .grace(std::time::Duration::from_secs(5))
+        .install()
+        .unwrap();
+    fastn_context::global().ready();
+
+    fastn_context::request_stop();
+    let report = tokio::time::timeout(std::time::Duration::from_secs(5), termination)
+        .await
+        .expect("termination did not finish")
+        .unwrap();
+
+    assert!(report.is_clean(), "{report}");
+    assert!(!fastn_context::is_ready());
+    assert!(fastn_context::install_signal_handlers().is_err());
+}
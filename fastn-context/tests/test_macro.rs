@@ -0,0 +1,58 @@
+#[fastn_context::test]
+async fn body_runs_under_its_own_root() {
+    let ctx = fastn_context::Context::current();
+    assert_eq!(ctx.path(), "body_runs_under_its_own_root");
+    assert!(ctx.parent().is_none());
+}
+
+#[fastn_context::test]
+async fn root_is_passed_as_argument(ctx: std::sync::Arc<fastn_context::Context>) {
+    assert_eq!(ctx.id(), fastn_context::Context::current().id());
+}
+
+#[fastn_context::test]
+async fn finished_tasks_are_not_leaks(ctx: std::sync::Arc<fastn_context::Context>) {
+    let answer = ctx.spawn_child("worker", |_| async { 42 }).await.unwrap();
+    assert_eq!(answer, 42);
+    ctx.spawn(async {}).await.unwrap();
+}
+
+#[fastn_context::test]
+async fn cancelled_children_are_not_leaks(ctx: std::sync::Arc<fastn_context::Context>) {
+    let handle = ctx.spawn_child("worker", |task_ctx| async move {
+        task_ctx.cancelled().await;
+    });
+
+    ctx.cancel();
+    handle.await.unwrap();
+}
+
+#[fastn_context::test]
+async fn on_cancel_hooks_are_not_leaks(ctx: std::sync::Arc<fastn_context::Context>) {
+    ctx.on_cancel(async {});
+    ctx.child("db").build().on_cancel(async {});
+}
+
+#[fastn_context::test]
+async fn result_errors_fail_the_test() -> Result<(), std::num::ParseIntError> {
+    assert_eq!("42".parse::<u32>()?, 42);
+    Ok(())
+}
+
+#[fastn_context::test]
+#[should_panic(expected = "leaked 1 context(s)")]
+async fn running_task_is_a_leak(ctx: std::sync::Arc<fastn_context::Context>) {
+    ctx.spawn_child("stuck", |_| std::future::pending::<()>());
+}
+
+#[fastn_context::test]
+#[should_panic(expected = "(1 task(s))")]
+async fn task_on_the_root_is_a_leak(ctx: std::sync::Arc<fastn_context::Context>) {
+    ctx.spawn(std::future::pending::<()>());
+}
+
+#[fastn_context::test]
+#[should_panic(expected = "still referenced")]
+async fn stray_child_handle_is_a_leak(ctx: std::sync::Arc<fastn_context::Context>) {
+    std::mem::forget(ctx.child("stray").build());
+}
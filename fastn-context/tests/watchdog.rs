@@ -0,0 +1,52 @@
+#[tokio::test]
+async fn watchdog_cancels_without_heartbeats() {
+    let app = fastn_context::Context::new("app");
+    let worker = app
+        .child("worker")
+        .watchdog(std::time::Duration::from_millis(20))
+        .build();
+
+    tokio::time::timeout(std::time::Duration::from_secs(5), worker.cancelled())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        worker.cancel_reason(),
+        Some(fastn_context::CancelReason::Watchdog(
+            std::time::Duration::from_millis(20)
+        ))
+    );
+    assert!(!app.is_cancelled());
+}
+
+#[tokio::test]
+async fn heartbeats_keep_the_watchdog_quiet() {
+    let app = fastn_context::Context::new("app");
+    let worker = app
+        .child("worker")
+        .watchdog(std::time::Duration::from_millis(50))
+        .build();
+
+    for _ in 0..10 {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        worker.heartbeat();
+    }
+
+    assert!(!worker.is_cancelled());
+    app.cancel();
+}
+
+#[tokio::test]
+async fn finished_contexts_are_not_cancelled_by_the_watchdog() {
+    let app = fastn_context::Context::new("app");
+    let worker = app
+        .child("worker")
+        .watchdog(std::time::Duration::from_millis(10))
+        .build();
+
+    worker.complete();
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    assert!(!worker.is_cancelled());
+    assert_eq!(worker.cancel_reason(), None);
+}